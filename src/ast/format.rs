@@ -0,0 +1,292 @@
+// format specs of placeholders, `{price:.2}`, `{n:>8}`, `{n:08}`, `{x:x}`, `{v:?}`
+use super::LiteralType;
+use crate::{
+    errors::{Error, ErrorCode::*},
+    interpreter::expr::Expression,
+    manifest::Project,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, process::exit};
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Kind {
+    #[default]
+    Display,
+    /// `?`
+    Debug,
+    /// `x`
+    Hex,
+    /// `X`
+    UpperHex,
+    /// `b`
+    Binary,
+    /// `o`
+    Octal,
+    /// `e`
+    Exp,
+}
+
+/// `[[fill]align][0][width][.precision][kind]`
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct FormatSpec {
+    pub fill: char,
+    pub align: Option<Align>,
+    /// pads numbers with zeros after their sign
+    pub zero: bool,
+    pub width: usize,
+    pub precision: Option<usize>,
+    pub kind: Kind,
+}
+
+impl Default for FormatSpec {
+    fn default() -> Self {
+        Self {
+            fill: ' ',
+            align: None,
+            zero: false,
+            width: 0,
+            precision: None,
+            kind: Kind::Display,
+        }
+    }
+}
+
+fn align(c: char) -> Option<Align> {
+    match c {
+        '<' => Some(Align::Left),
+        '>' => Some(Align::Right),
+        '^' => Some(Align::Center),
+        _ => None,
+    }
+}
+
+impl FormatSpec {
+    /// the spec after the `:` of a placeholder, None if it isn't one
+    pub fn parse(spec: &str) -> Option<Self> {
+        let chars: Vec<char> = spec.chars().collect();
+        let mut i = 0;
+        let mut fmt = Self::default();
+        match (chars.first(), chars.get(1).and_then(|c| align(*c))) {
+            (Some(fill), Some(a)) => {
+                (fmt.fill, fmt.align, i) = (*fill, Some(a), 2);
+            }
+            (Some(c), None) if align(*c).is_some() => {
+                (fmt.align, i) = (align(*c), 1);
+            }
+            _ => {}
+        }
+        if chars.get(i) == Some(&'0') {
+            fmt.zero = true;
+            i += 1;
+        }
+        let digits = |i: &mut usize| {
+            let start = *i;
+            while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+                *i += 1;
+            }
+            chars[start..*i].iter().collect::<String>().parse().ok()
+        };
+        // a width too large to hold makes the spec invalid
+        fmt.width = match chars.get(i) {
+            Some(c) if c.is_ascii_digit() => digits(&mut i)?,
+            _ => 0,
+        };
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            fmt.precision = Some(digits(&mut i)?);
+        }
+        fmt.kind = match chars[i..] {
+            [] => Kind::Display,
+            ['?'] => Kind::Debug,
+            ['x'] => Kind::Hex,
+            ['X'] => Kind::UpperHex,
+            ['b'] => Kind::Binary,
+            ['o'] => Kind::Octal,
+            ['e'] => Kind::Exp,
+            _ => return None,
+        };
+        Some(fmt)
+    }
+
+    /// the `value` formatted by the spec, reports values the spec can't show
+    pub fn apply(&self, value: &LiteralType) -> String {
+        let text = match (self.kind, value, self.precision) {
+            (Kind::Debug, _, _) => debug(value),
+            (Kind::Hex, _, _) => format!("{:x}", self.integer(value)),
+            (Kind::UpperHex, _, _) => format!("{:X}", self.integer(value)),
+            (Kind::Binary, _, _) => format!("{:b}", self.integer(value)),
+            (Kind::Octal, _, _) => format!("{:o}", self.integer(value)),
+            (Kind::Exp, LiteralType::Number(n), Some(p)) => format!("{:.*e}", p, n),
+            (Kind::Exp, LiteralType::Number(n), None) => format!("{:e}", n),
+            (_, LiteralType::Number(n), Some(p)) => format!("{:.*}", p, n),
+            (_, LiteralType::String(s), Some(p)) => s.chars().take(p).collect(),
+            _ => value.to_string(),
+        };
+        self.pad(text, matches!(value, LiteralType::Number(_)))
+    }
+
+    /// the value of `x`, `X`, `b` and `o` specs, only non-negative integers have digits in them
+    fn integer(&self, value: &LiteralType) -> u64 {
+        match value {
+            LiteralType::Number(n) if n.fract() == 0.0 && *n >= 0.0 => *n as u64,
+            _ => {
+                Error::new("", Project::new()).throw(
+                    E0x421,
+                    0,
+                    (0, 0),
+                    vec![self.to_string(), debug(value)],
+                );
+                exit(1)
+            }
+        }
+    }
+
+    /// pads the `text` up to the width, numbers are aligned right unless told otherwise
+    fn pad(&self, text: String, numeric: bool) -> String {
+        let gap = self.width.saturating_sub(text.chars().count());
+        if gap == 0 {
+            return text;
+        }
+        if self.zero && numeric && self.align.is_none() {
+            let (sign, digits) = match text.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", text.as_str()),
+            };
+            return format!("{}{}{}", sign, "0".repeat(gap), digits);
+        }
+        let fill = |n: usize| self.fill.to_string().repeat(n);
+        let align = match (self.align, numeric) {
+            (Some(align), _) => align,
+            (None, true) => Align::Right,
+            (None, false) => Align::Left,
+        };
+        match align {
+            Align::Left => text + &fill(gap),
+            Align::Right => fill(gap) + &text,
+            Align::Center => fill(gap / 2) + &text + &fill(gap - gap / 2),
+        }
+    }
+}
+
+impl fmt::Display for FormatSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(align) = self.align {
+            let align = match align {
+                Align::Left => '<',
+                Align::Right => '>',
+                Align::Center => '^',
+            };
+            match self.fill {
+                ' ' => write!(f, "{}", align)?,
+                fill => write!(f, "{}{}", fill, align)?,
+            }
+        }
+        if self.zero {
+            write!(f, "0")?;
+        }
+        if self.width > 0 {
+            write!(f, "{}", self.width)?;
+        }
+        if let Some(p) = self.precision {
+            write!(f, ".{}", p)?;
+        }
+        let kind = match self.kind {
+            Kind::Display => "",
+            Kind::Debug => "?",
+            Kind::Hex => "x",
+            Kind::UpperHex => "X",
+            Kind::Binary => "b",
+            Kind::Octal => "o",
+            Kind::Exp => "e",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+// specs are shown as they're written, `Format(">8")` in the bytecode
+impl fmt::Debug for FormatSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+/// `{v:?}`, strings and chars are quoted and enums keep their parent
+fn debug(value: &LiteralType) -> String {
+    let list = |items: &[LiteralType]| items.iter().map(debug).collect::<Vec<_>>().join(", ");
+    match value {
+        LiteralType::String(s) => format!("{:?}", s),
+        LiteralType::Char(c) => format!("{:?}", c),
+        LiteralType::Vec(items) => format!("[{}]", list(items)),
+        LiteralType::Tuple(items) => format!("({})", list(items)),
+        LiteralType::Record(fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(name, field)| match field {
+                    Expression::Value { value, .. } => format!("{}: {}", name, debug(value)),
+                    _ => format!("{}: {}", name, field),
+                })
+                .collect();
+            format!("{{ {} }}", fields.join(", "))
+        }
+        LiteralType::Enum {
+            parent,
+            name,
+            value: Some(value),
+        } => format!("{}::{}({})", parent.lexeme, name.lexeme, debug(value)),
+        LiteralType::Enum { parent, name, .. } => format!("{}::{}", parent.lexeme, name.lexeme),
+        _ => value.to_string(),
+    }
+}
+
+/// the length and spec of the `{}` or `{:spec}` slot at the start of `rest`,
+/// a spec that isn't one is the error
+pub fn slot(rest: &str) -> Option<(usize, Result<FormatSpec, &str>)> {
+    let inner = rest.strip_prefix('{')?;
+    let end = inner.find('}')?;
+    let spec = match &inner[..end] {
+        "" => Ok(FormatSpec::default()),
+        spec => {
+            let spec = spec.strip_prefix(':')?;
+            FormatSpec::parse(spec).ok_or(spec)
+        }
+    };
+    Some((end + 2, spec))
+}
+
+/// fills the slots of the `template` with the `values` in order, slots without a value are kept
+pub fn fill(template: &str, values: &[LiteralType]) -> String {
+    let mut out = String::new();
+    let mut values = values.iter();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let len = match slot(rest) {
+            Some((_, Err(spec))) => {
+                Error::new("", Project::new()).throw(E0x108, 0, (0, 0), vec![spec.to_string()]);
+                exit(1)
+            }
+            Some((len, Ok(spec))) => {
+                match values.next() {
+                    Some(value) => out.push_str(&spec.apply(value)),
+                    None => out.push_str(&rest[..len]),
+                }
+                len
+            }
+            None => {
+                out.push('{');
+                1
+            }
+        };
+        rest = &rest[len..];
+    }
+    out.push_str(rest);
+    out
+}
@@ -95,7 +95,7 @@ impl fmt::Display for LiteralType {
             Self::Record(val) => {
                 let n: Vec<String> = val
                     .iter()
-                    .map(|(name, value)| format!("{}:{}", name, value.to_string()))
+                    .map(|(name, value)| format!("{}: {}", name, value))
                     .collect();
                 let c = n.join(", ");
                 write!(f, "{{ {} }}", c)
@@ -116,7 +116,7 @@ impl fmt::Display for LiteralType {
                 write!(f, "[{}]", s)
             }
             Self::Void => write!(f, "void"),
-            Self::Func(func) => write!(f, "{:?}", func.name),
+            Self::Func(func) => write!(f, "<func {}>", func.name),
             Self::DeclrFunc(declr_func) => write!(f, "{}()", declr_func.name),
         }
     }
@@ -1,13 +1,19 @@
+pub mod format;
 pub mod literals;
 use std::{
-    cell::RefCell,
+    cell::{OnceCell, RefCell},
     fmt::{self, Debug},
     rc::Rc,
 };
 pub mod token;
-use crate::interpreter::{env::Env, expr::Expression, types::TypeKind};
+use crate::{
+    interpreter::{env::Env, expr::Expression, types::TypeKind},
+    manifest::Project,
+    vm::Chunk,
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum TokenType {
     // primary keywords
     /// variable (let)
@@ -205,7 +211,7 @@ pub enum TokenType {
     Eof,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum Base {
     Binary = 2,
     Octal = 8,
@@ -213,7 +219,7 @@ pub enum Base {
     Hexadecimal = 16,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum LiteralType {
     Number(f32),
     String(String),
@@ -224,7 +230,10 @@ pub enum LiteralType {
     Vec(Vec<LiteralType>),
     Tuple(Vec<LiteralType>),
     Record(Vec<(String, Expression)>),
+    // functions are values of running programs, they're never cached
+    #[serde(skip)]
     Func(FuncImpl),
+    #[serde(skip)]
     DeclrFunc(DeclrFuncType),
     Enum {
         parent: Token,
@@ -274,7 +283,7 @@ impl FuncValType for Wrapper {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum LiteralKind {
     Number { base: Base, value: f32 },
     String { value: String },
@@ -293,9 +302,13 @@ pub struct FuncImpl {
     pub is_async: bool,
     pub is_pub: bool,
     pub env: Rc<RefCell<Env>>,
+    /// project of the file it's defined in, its `mod`s and `args()` come from it
+    pub project: Rc<Project>,
+    /// bytecode of the body, compiled on the first call on the vm
+    pub chunk: Rc<OnceCell<Chunk>>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Token {
     pub token: TokenType,
     pub lexeme: String,
@@ -331,22 +344,21 @@ impl Token {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum CallType {
     Func,
     Struct,
     Vector,
-    Enum,
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone, Eq, Serialize, Deserialize)]
 pub enum Destruct {
     Vector,
     Record,
     Tuple,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct RecordField {
     pub name: Token,
     pub value: Token,
@@ -355,7 +367,7 @@ pub struct RecordField {
     pub default_value: Option<Expression>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Statement {
     Record {
         name: Token,
@@ -370,6 +382,7 @@ pub enum Statement {
     },
     Sh {
         cmd: String,
+        line: usize,
     },
     Type {
         name: Token,
@@ -383,6 +396,8 @@ pub enum Statement {
         stmts: Vec<Statement>,
     },
     Var {
+        #[serde(deserialize_with = "crate::cache::node_id")]
+        id: usize,
         names: Vec<Token>,
         destruct: Option<Destruct>,
         value_type: Token,
@@ -393,6 +408,8 @@ pub enum Statement {
         is_func: bool,
     },
     Func {
+        #[serde(deserialize_with = "crate::cache::node_id")]
+        id: usize,
         name: Token,
         value_type: Token,
         body: FuncBody,
@@ -423,7 +440,9 @@ pub enum Statement {
         iter: Option<usize>,
         body: Vec<Statement>,
     },
-    Break {},
+    Break {
+        line: usize,
+    },
     Match {
         cond: Expression,
         cases: Vec<(Expression, FuncBody)>,
@@ -432,15 +451,44 @@ pub enum Statement {
     Mod {
         src: String,
         name: Option<String>,
+        line: usize,
     },
     Use {
+        #[serde(deserialize_with = "crate::cache::node_id")]
+        id: usize,
         src: String,
         names: Vec<(Token, Option<Token>)>,
         all: bool,
     },
 }
 
-#[derive(Debug, PartialEq, Clone)]
+impl Statement {
+    /// line of the first token in the statement, 0 if the statement has no tokens
+    pub fn line(&self) -> usize {
+        match self {
+            Statement::Record { name, .. }
+            | Statement::Enum { name, .. }
+            | Statement::Type { name, .. }
+            | Statement::Func { name, .. } => name.line,
+            Statement::Var { names, .. } => names.first().map_or(0, |n| n.line),
+            Statement::Use { names, .. } => names.first().map_or(0, |(n, _)| n.line),
+            Statement::For { iterator, .. } => iterator.line,
+            Statement::Expression { expr }
+            | Statement::Return { expr }
+            | Statement::If { cond: expr, .. }
+            | Statement::While { cond: expr, .. }
+            | Statement::Match { cond: expr, .. } => expr.line(),
+            Statement::Block { stmts } | Statement::Loop { body: stmts, .. } => {
+                stmts.first().map_or(0, |s| s.line())
+            }
+            Statement::Sh { line, .. }
+            | Statement::Mod { line, .. }
+            | Statement::Break { line } => *line,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum FuncBody {
     Statements(Vec<Statement>),
     Expression(Box<Expression>),
@@ -1,14 +1,18 @@
 // bundles multiple parts together
 use coloredpp::Colorize;
-use std::{cell::RefCell, rc::Rc, time::Instant};
+use std::{cell::RefCell, mem::replace, rc::Rc, time::Instant};
 
 use crate::{
     ast::Statement,
+    cache,
     errors::Error,
     interpreter::{env::Env, expr::Expression, Interpreter},
-    manifest::Project,
-    parser::{scanner::Scanner, Parser},
+    manifest::{Engine, Project},
+    optimizer::optimize,
+    parser::{next_id, scanner::Scanner, Parser},
+    profiler,
     resolver::Resolver,
+    vm,
 };
 
 pub fn parser(src: &str, err: Error, log: bool) -> Vec<Statement> {
@@ -50,21 +54,39 @@ pub fn interpreter_raw(src: &str, project: Project, log: bool) {
     let full_start = Instant::now();
     let err = Error::new(src, project.clone());
 
-    let stmts = parser(src, err.clone(), log);
-
-    let mut start = None;
-    if log {
-        println!("{}", "resolving...".yellow());
-        start = Some(Instant::now());
-    }
     let mut int = Interpreter::new(project.clone(), err.clone());
-    let mut resolver = Resolver::new(err.clone());
-    let locals = resolver.resolve(&stmts, &mut int.env);
-    if log {
-        let resolver_duration = start.unwrap().elapsed();
-        let text = format!("{:?}", resolver_duration);
-        println!("{} {}", "completed resolving in".green(), text.blue());
-    }
+    let (stmts, locals) = match cache::load(src, &project, false) {
+        Some(program) => {
+            if log {
+                println!("{}", "loaded from the cache".green());
+            }
+            program
+        }
+        None => {
+            let first = next_id();
+            let stmts = parser(src, err.clone(), log);
+
+            let mut start = None;
+            if log {
+                println!("{}", "resolving...".yellow());
+                start = Some(Instant::now());
+            }
+            let mut resolver = Resolver::new(err.clone());
+            let locals = resolver.resolve(&stmts, &int.env);
+            if log {
+                let resolver_duration = start.unwrap().elapsed();
+                let text = format!("{:?}", resolver_duration);
+                println!("{} {}", "completed resolving in".green(), text.blue());
+            }
+            let stmts = if project.optimize {
+                optimize(stmts, false)
+            } else {
+                stmts
+            };
+            cache::store(src, &project, false, first..next_id(), &stmts, &locals);
+            (stmts, locals)
+        }
+    };
 
     let mut start = None;
     if log {
@@ -73,7 +95,18 @@ pub fn interpreter_raw(src: &str, project: Project, log: bool) {
     }
 
     int.env.borrow_mut().resolve(locals);
-    int.interpret(stmts.iter().collect(), 0);
+    if project.profile.is_some() {
+        profiler::start();
+    }
+    match project.engine {
+        Engine::Tree => {
+            int.interpret(stmts.iter().collect(), 0);
+        }
+        Engine::Vm => vm::run(&stmts, &mut int),
+    }
+    if let Some(path) = &project.profile {
+        profiler::finish(path);
+    }
     if log {
         let interpreter_duration = start.unwrap().elapsed();
         let text = format!("{:?}", interpreter_duration);
@@ -91,10 +124,25 @@ pub fn interpreter_mod(
     project: Project,
 ) -> Rc<RefCell<Env>> {
     let err = Error::new(src, project.clone());
-    let mut int = Interpreter::new_with_env(env, true, src, mod_src, 0);
-    let stmts = parser(src, err.clone(), false);
-    let mut resolver = Resolver::new(err);
-    let locals = resolver.resolve(&stmts, &mut int.env);
+    // modules share the env of the importer, its project is back once the module ran
+    let importer = replace(&mut env.borrow_mut().project, Rc::new(project));
+    let mut int = Interpreter::new_with_env(Rc::clone(&env), true, src, mod_src, 0);
+    let (stmts, locals) = cache::load(src, &int.project, true).unwrap_or_else(|| {
+        let first = next_id();
+        let stmts = parser(src, err.clone(), false);
+        let mut resolver = Resolver::new(err);
+        let locals = resolver.resolve(&stmts, &int.env);
+        let stmts = if int.project.optimize {
+            optimize(stmts, true)
+        } else {
+            stmts
+        };
+        cache::store(src, &int.project, true, first..next_id(), &stmts, &locals);
+        (stmts, locals)
+    });
     int.env.borrow_mut().resolve(locals);
-    int.interpret(stmts.iter().collect(), 0)
+    // modules are mostly declarations, the bodies of their functions still run on the vm
+    let module = int.interpret(stmts.iter().collect(), 0);
+    env.borrow_mut().project = importer;
+    module
 }
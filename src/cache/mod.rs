@@ -0,0 +1,90 @@
+// parsed and resolved programs cached in `.absurd-cache/<hash>.absc`, keyed by the source and the VERSION
+mod format;
+
+use serde::{Deserialize, Deserializer};
+use std::{cell::Cell, fs, ops::Range, path::PathBuf};
+
+use crate::{
+    ast::Statement, interpreter::env::Locals, manifest::Project, package::archive::sha256,
+    parser::reserve_ids, VERSION,
+};
+
+/// directory of the cache, in the project or workspace root or next to the script
+pub const CACHE: &str = ".absurd-cache";
+/// starts the cache files, followed by the range of node ids, the statements and the locals
+const MAGIC: &[u8] = b"ABSC";
+/// bumped when the shape of the cached statements changes between releases
+const FORMAT: u32 = 5;
+
+thread_local! {
+    /// node ids of the cached program being loaded and the first id they move to
+    static REBASE: Cell<(usize, usize, usize)> = const { Cell::new((0, 0, 0)) };
+}
+
+/// node ids of a cached program move to ids reserved in this run, so they can't overlap
+/// with the ids of programs parsed by this run
+pub fn node_id<'de, D: Deserializer<'de>>(d: D) -> Result<usize, D::Error> {
+    usize::deserialize(d).map(rebase)
+}
+
+fn rebase(id: usize) -> usize {
+    let (start, end, base) = REBASE.with(|r| r.get());
+    match (start..end).contains(&id) {
+        true => id - start + base,
+        false => id,
+    }
+}
+
+/// the cache file of the `src`, the cache is only kept for files, packages cache into the
+/// root of the project using them so their installed files keep the locked hash
+fn path(src: &str, project: &Project, is_mod: bool) -> Option<PathBuf> {
+    let dir = match project.lock_root() {
+        Some(root) => root.clone(),
+        None => project.file.as_ref()?.parent()?.to_path_buf(),
+    };
+    // optimized programs are cached apart from the ones as written, and modules apart from
+    // scripts, the optimizer keeps the top-level `if`s of a module since they never run
+    let key = format!(
+        "{}\0{}\0{}\0{}\0{}",
+        VERSION, FORMAT, project.optimize, is_mod, src
+    );
+    let hash = sha256(key.as_bytes());
+    let hash = hash.trim_start_matches("sha256:");
+    Some(dir.join(CACHE).join(format!("{}.absc", hash)))
+}
+
+/// statements and locals of the `src`, if they were cached by this version
+pub fn load(src: &str, project: &Project, is_mod: bool) -> Option<(Vec<Statement>, Locals)> {
+    let bytes = fs::read(path(src, project, is_mod)?).ok()?;
+    let (ids, bytes): (Range<usize>, _) = format::from_prefix(bytes.strip_prefix(MAGIC)?).ok()?;
+    let base = reserve_ids(ids.len());
+    REBASE.with(|r| r.set((ids.start, ids.end, base)));
+    let program = format::from_bytes(bytes)
+        .ok()
+        .map(|(stmts, locals): (_, Locals)| {
+            let locals = locals.into_iter().map(|(id, a)| (rebase(id), a)).collect();
+            (stmts, locals)
+        });
+    REBASE.with(|r| r.set((0, 0, 0)));
+    program
+}
+
+/// caches the statements and locals of the `src`, parsed into the node `ids`,
+/// failing to write the cache isn't an error
+pub fn store(
+    src: &str,
+    project: &Project,
+    is_mod: bool,
+    ids: Range<usize>,
+    stmts: &[Statement],
+    locals: &Locals,
+) {
+    let Some(path) = path(src, project, is_mod) else {
+        return;
+    };
+    let bytes = format::to_bytes(&ids)
+        .and_then(|ids| Ok([ids, format::to_bytes(&(stmts, locals))?].concat()));
+    if let (Ok(bytes), Some(dir)) = (bytes, path.parent()) {
+        let _ = fs::create_dir_all(dir).and_then(|_| fs::write(&path, [MAGIC, &bytes].concat()));
+    }
+}
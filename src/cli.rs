@@ -1,35 +1,240 @@
 use abs_cli::CLI;
 use coloredpp::Colorize;
 use std::{
+    env::current_dir,
     fs::File,
     io::{stdin, Read, Write},
+    path::Path,
     process::{exit, Command, Stdio},
 };
 
-use crate::{bundler::interpreter_raw, errors::raw, manifest::Project, VERSION};
+use crate::{
+    bundler::{interpreter_raw, parse_expr},
+    dap,
+    debugger::{self, Frontend, StepMode},
+    dump,
+    errors::{raw, Error},
+    interpreter::Interpreter,
+    manifest::{Engine, Project},
+    package, scaffold, workspace, VERSION,
+};
 pub fn cli_new(project: &mut Project) {
     let mut program = CLI::new();
     program
         .name("Absurd")
         .version(VERSION)
         .description("The Absurd Programming Language")
+        .option("--json", "print dumps as JSON")
+        .option(
+            "--profile",
+            "profile function calls, optionally set the folded stacks file",
+        )
+        .option(
+            "--engine",
+            "run on the tree walker or compile to bytecode for the vm, --engine=<tree|vm>",
+        )
         .option("-s, --side-effects", "disable side-effects")
         .option("-l, --log", "enable logging mode")
         .option("-t, --test", "enable testing mode")
-        .arg("run", "run [file]", "interpret the file")
+        .arg(
+            "run",
+            "run [file] [-O]",
+            "interpret the file, -O folds constants and drops dead code first",
+        )
+        .arg(
+            "debug",
+            "debug [file]",
+            "interpret the file in the step debugger",
+        )
+        .arg("dap", "dap", "serve the debug adapter protocol over stdio")
+        .arg(
+            "dump",
+            "dump [file] <tokens|ast|resolved|bytecode>",
+            "print the tokens, syntax tree, resolved locals or bytecode of the file",
+        )
+        .arg("new", "new [name]", "create a new project in the directory")
+        .arg(
+            "init",
+            "init",
+            "create a new project in the current directory",
+        )
+        .arg("info", "info", "show the package metadata of the project")
         .arg("update", "update", "update to the latest version")
         .arg("ci", "ci", "enter source from the CLI")
+        .arg(
+            "eval",
+            "eval [expr]",
+            "evaluate the expression and print the result",
+        )
         .arg(
             "add",
-            "add [repo]/[name] <new_name>",
+            "add [owner/repo|url|path|name] <name> <--rev rev> <--registry dir>",
             "add a new package to the project",
         )
         .arg(
             "remove",
             "remove [name]",
             "remove the package from the project",
+        )
+        .arg(
+            "install",
+            "install",
+            "install the dependencies pinned in absurd.lock",
+        )
+        .arg(
+            "test",
+            "test",
+            "run the tests/ of the package or of every workspace member",
+        )
+        .arg(
+            "check",
+            "check",
+            "parse and resolve every file without running it",
+        )
+        .arg(
+            "fmt",
+            "fmt <--check>",
+            "format every file, or list the unformatted ones",
+        )
+        .arg(
+            "tree",
+            "tree",
+            "print the dependency graph with the locked versions",
+        )
+        .arg(
+            "outdated",
+            "outdated",
+            "list the dependencies with newer versions available",
+        )
+        .arg(
+            "pack",
+            "pack",
+            "archive the package into dist/<name>-<version>.tar",
+        )
+        .arg(
+            "publish",
+            "publish <--registry dir>",
+            "pack the package and add it to a file-system registry",
         );
-    program.parse();
+
+    let mut args: Vec<String> = std::env::args().collect();
+    // options only match whole arguments, so `--engine=vm` and `--profile=out` are taken out here
+    if let Some(name) = take_value(&mut args, "--engine") {
+        project.engine = engine(&name);
+    }
+    if let Some(path) = take_value(&mut args, "--profile") {
+        project.profile = Some(profile_path(&path));
+    }
+    // so is `-O`, it may come before the file in `absurd -O file.abs`
+    project.optimize = take_flag(&mut args, &["-O", "--optimize"]);
+    match args.get(1) {
+        Some(a) if a == "init" => {
+            let dir = current_dir().expect("failed to get current directory");
+            let name = dir
+                .file_name()
+                .map_or("project".to_string(), |n| n.to_string_lossy().to_string());
+            scaffold::create(&dir, &name);
+            exit(0);
+        }
+        Some(a) if a == "eval" => {
+            load_manifest(project, None);
+            match args.get(2) {
+                Some(expr) => eval(expr, project.clone()),
+                None => raw("expected an expression"),
+            }
+            exit(0);
+        }
+        Some(a) if a == "add" => {
+            load_manifest(project, None);
+            let mut rest: Vec<&str> = args[2..].iter().map(|a| a.as_str()).collect();
+            let rev = option(&mut rest, "--rev");
+            let registry = option(&mut rest, "--registry");
+            match rest.first() {
+                Some(spec) => package::add(project, spec, rest.get(1).copied(), rev, registry),
+                None => raw("expected a package, e.g. owner/repo"),
+            }
+            exit(0);
+        }
+        Some(a) if a == "remove" => {
+            load_manifest(project, None);
+            match args.get(2) {
+                Some(name) => package::remove(project, name),
+                None => raw("expected a package name"),
+            }
+            exit(0);
+        }
+        Some(a) if a == "install" => {
+            load_manifest(project, None);
+            package::install(project);
+            exit(0);
+        }
+        Some(a) if a == "test" => {
+            load_manifest(project, None);
+            workspace::test(project);
+            exit(0);
+        }
+        Some(a) if a == "check" => {
+            load_manifest(project, None);
+            workspace::check(project);
+            exit(0);
+        }
+        Some(a) if a == "fmt" => {
+            load_manifest(project, None);
+            workspace::fmt(project, args.get(2).is_some_and(|a| a == "--check"));
+            exit(0);
+        }
+        Some(a) if a == "tree" => {
+            load_manifest(project, None);
+            package::report::tree(project);
+            exit(0);
+        }
+        Some(a) if a == "outdated" => {
+            load_manifest(project, None);
+            package::report::outdated(project);
+            exit(0);
+        }
+        Some(a) if a == "pack" => {
+            load_manifest(project, None);
+            package::archive::pack(project);
+            exit(0);
+        }
+        Some(a) if a == "publish" => {
+            load_manifest(project, None);
+            let mut rest: Vec<&str> = args[2..].iter().map(|a| a.as_str()).collect();
+            match package::registry::directory(option(&mut rest, "--registry")) {
+                Some(registry) => package::registry::publish(project, &registry),
+                None => raw(format!(
+                    "expected a registry, use --registry <dir> or set {}",
+                    package::registry::REGISTRY_ENV
+                )
+                .as_str()),
+            }
+            exit(0);
+        }
+        // `absurd file.abs a b c`, used by scripts with a shebang line
+        Some(f) if Path::new(f).is_file() && !program.args.iter().any(|a| a.name == f) => {
+            let skip = if args.get(2).is_some_and(|a| a == "--") {
+                3
+            } else {
+                2
+            };
+            project.args = args[skip..].to_vec();
+            load_manifest(project, Some(f));
+            run_file(f.clone(), project.clone());
+            exit(0);
+        }
+        _ => {}
+    }
+    // what follows `--` belongs to the script, `absurd run a.abs -- info` doesn't run info
+    let end = args.iter().position(|a| a == "--").unwrap_or(args.len());
+    parse(&mut program, &args[..end]);
+
+    // the manifest is searched from the directory of the script
+    let script = ["run", "debug", "dump"]
+        .iter()
+        .find_map(|cmd| program.get(cmd).and_then(|v| v.first()))
+        .filter(|f| !f.is_empty() && *f != "-");
+    load_manifest(project, script.map(|f| f.as_str()));
 
     if program.get("--test").is_some() {
         project.test = true
@@ -40,30 +245,105 @@ pub fn cli_new(project: &mut Project) {
     if program.get("--log").is_some() {
         project.log = true
     }
+    project.args = args.iter().skip(end + 1).cloned().collect();
+    if let Some(name) = program.get("--engine") {
+        project.engine = engine(&name[0]);
+    }
+    if let Some(profile) = program.get("--profile") {
+        project.profile = Some(profile_path(&profile[0]));
+    }
+    if let Some(new) = program.get("new") {
+        match new.first().map(|n| n.as_str()) {
+            Some("") | None => raw("expected a project name"),
+            // `absurd new apps/web` names the package after the directory
+            Some(path) => {
+                let dir = Path::new(path);
+                let name = dir.file_name().map_or(path.into(), |n| n.to_string_lossy());
+                scaffold::create(dir, &name)
+            }
+        }
+        exit(0);
+    }
+    if program.get("info").is_some() {
+        info(project);
+        exit(0);
+    }
     if program.get("update").is_some() {
         update();
         exit(0);
     }
 
     let run = program.get("run");
+    // `absurd run -` reads the program from stdin
+    if run.is_some()
+        && args
+            .iter()
+            .skip_while(|a| *a != "run")
+            .nth(1)
+            .is_some_and(|a| a == "-")
+    {
+        let mut code = String::new();
+        if let Err(e) = stdin().read_to_string(&mut code) {
+            raw(format!("failed to read stdin: {}", e).as_str());
+        }
+        run_code(code, project.clone());
+        exit(0);
+    }
     if run.is_some() {
-        run_file(
-            run.unwrap().get(0).expect("expected a file").clone(),
-            project.clone(),
-        );
+        let file = match run.unwrap().get(0).map(|f| f.as_str()) {
+            // `absurd run` runs the entry of the manifest
+            Some("") | None => match project.entry() {
+                Some(entry) => entry.to_string_lossy().to_string(),
+                None => {
+                    raw("expected a file or an 'entry' in the [package] of project.toml");
+                    exit(1);
+                }
+            },
+            Some(f) => f.to_string(),
+        };
+        run_file(file, project.clone());
         exit(1);
     }
 
-    let add = program.get("add");
-    if add.is_some() {
-        add_mod(add.unwrap().get(0), add.unwrap().get(1));
-        exit(1);
+    let debug = program.get("debug");
+    if let Some(debug) = debug {
+        let f = debug.first().expect("expected a file").clone();
+        let contents = read_file(&f);
+        if project.engine == Engine::Vm {
+            println!(
+                "{}",
+                "the debugger runs on the tree walker, --engine=vm is ignored".yellow()
+            );
+        }
+        // compiled statements don't reach the debugger hooks
+        project.engine = Engine::Tree;
+        debugger::start(&f, &contents, Frontend::Terminal, StepMode::Step);
+        project.file = Path::new(&f).canonicalize().ok();
+        run_code(contents, project.clone());
+        exit(0);
     }
 
-    let remove = program.get("add");
-    if remove.is_some() {
-        remove_mod(remove.unwrap().get(0));
-        exit(1);
+    let dump = program.get("dump");
+    if let Some(dump) = dump {
+        let f = dump.first().expect("expected a file").clone();
+        let stage = match dump.get(1).map(|s| s.as_str()) {
+            Some("") | None => "ast",
+            Some(s) => s,
+        };
+        dump::dump(
+            &read_file(&f),
+            stage,
+            program.get("--json").is_some(),
+            project.clone(),
+        );
+        exit(0);
+    }
+
+    if program.get("dap").is_some() {
+        // like `debug`, stdout carries the protocol so the engine is switched without a note
+        project.engine = Engine::Tree;
+        dap::serve(project.clone());
+        exit(0);
     }
 
     if program.get("ci").is_some() {
@@ -77,8 +357,186 @@ pub fn cli_new(project: &mut Project) {
     }
 }
 
-fn run_file(f: String, project: Project) {
-    let mut file = match File::open(f.clone()) {
+fn run_file(f: String, mut project: Project) {
+    let contents = read_file(&f);
+    project.file = Path::new(&f).canonicalize().ok();
+    interpreter_raw(&contents, project.clone(), project.log);
+}
+
+fn info(project: &Project) {
+    let manifest = &project.manifest;
+    let package = match &manifest.package {
+        Some(p) => p,
+        None => {
+            raw("no [package] found in project.toml");
+            exit(1);
+        }
+    };
+    println!(
+        "{} {}",
+        package.name.get_ref().blue(),
+        format!("v{}", package.version.get_ref()).yellow()
+    );
+    if let Some(desc) = &package.description {
+        println!("{}", desc);
+    }
+    if !package.authors.is_empty() {
+        println!("authors: {}", package.authors.join(", "));
+    }
+    if let Some(license) = &package.license {
+        println!("license: {}", license);
+    }
+    if let Some(entry) = &package.entry {
+        println!("entry: {}", entry);
+    }
+    for (title, deps) in [
+        ("dependencies", &manifest.dependencies),
+        ("dev-dependencies", &manifest.dev_dependencies),
+    ] {
+        if !deps.is_empty() {
+            println!(
+                "{}: {}",
+                title,
+                deps.keys().cloned().collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+    for (name, level) in &manifest.lints {
+        println!("lint {}: {:?}", name, level);
+    }
+    for (name, cmd) in &manifest.scripts {
+        println!("script {}: {}", name, cmd);
+    }
+}
+
+/// loads the manifest closest to the `file`, or to the current directory
+fn load_manifest(project: &mut Project, file: Option<&str>) {
+    let dir = match file.and_then(|f| Path::new(f).parent()) {
+        Some(d) if !d.as_os_str().is_empty() => d.to_path_buf(),
+        _ => current_dir().expect("failed to get current directory"),
+    };
+    project.load(&dir);
+}
+
+/// removes `name <value>` from the `args` and returns the value
+fn option<'a>(args: &mut Vec<&'a str>, name: &str) -> Option<&'a str> {
+    let i = args.iter().position(|a| *a == name)?;
+    if i + 1 >= args.len() {
+        raw(format!("expected a value after '{}'", name).as_str());
+        exit(1);
+    }
+    let value = args[i + 1];
+    args.drain(i..i + 2);
+    Some(value)
+}
+
+/// evaluates a single expression with std::core::io loaded
+fn eval(expr: &str, project: Project) {
+    let err = Error::new(expr, project.clone());
+    let int = Interpreter::new(project, err.clone());
+    println!("{}", parse_expr(expr, err).eval(int.env));
+}
+
+/// removes `<name>=<value>` from the arguments before the script's `--` and returns the value
+fn take_value(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let end = args.iter().position(|a| a == "--").unwrap_or(args.len());
+    let prefix = format!("{}=", name);
+    let i = args[..end].iter().position(|a| a.starts_with(&prefix))?;
+    Some(args.remove(i)[prefix.len()..].to_string())
+}
+
+/// the folded stacks file of `--profile`, `profile.folded` without one
+fn profile_path(path: &str) -> String {
+    match path {
+        "" => "profile.folded".to_string(),
+        path => path.to_string(),
+    }
+}
+
+/// matches the `args` against the options and commands of the `program`, abs-cli's own parser
+/// rereads the process arguments and matches options by any of their letters
+fn parse(program: &mut CLI, args: &[String]) {
+    let mut iter = args.iter().skip(1).peekable();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--help" | "-h" => help(program),
+            "--version" | "-v" => println!("{}{}", "v".blue().bold(), VERSION.blue().bold()),
+            other => {
+                let commands: Vec<&str> = program.args.iter().map(|a| a.name).collect();
+                if let Some(opt) = program
+                    .options
+                    .iter_mut()
+                    .find(|o| o.name == other || o.short == Some(other))
+                {
+                    // only `--profile [file]` and `--engine <name>` take a value
+                    let valued = ["--profile", "--engine"].contains(&opt.name);
+                    let value = iter.next_if(|v| {
+                        valued && !v.starts_with('-') && !commands.contains(&v.as_str())
+                    });
+                    opt.value = Some(value.cloned().unwrap_or_default());
+                } else if let Some(cmd) = program.args.iter_mut().find(|a| a.name == other) {
+                    while let Some(value) = iter.next_if(|v| !v.starts_with('-')) {
+                        cmd.values.push(value.clone());
+                    }
+                    cmd.values.push("".to_string());
+                }
+            }
+        }
+    }
+}
+
+/// the same help abs-cli prints from its own parser
+fn help(program: &CLI) {
+    println!(
+        "\n{}, {}{}",
+        program.name.unwrap_or_default().red().bold(),
+        "v".cyan().bold(),
+        VERSION.cyan().bold()
+    );
+    println!("{}", program.description.unwrap_or_default().italic());
+    println!("\n{}", "Options:".yellow().bold());
+    let builtin = [
+        ("--help", "-h", "Print this message"),
+        ("--version", "-v", "Print the application version"),
+    ];
+    let options = program
+        .options
+        .iter()
+        .map(|o| (o.name, o.short.unwrap_or_default(), o.description));
+    for (name, short, description) in builtin.into_iter().chain(options) {
+        println!("\t{}, {} \t{}", name.blue(), short.blue(), description);
+    }
+    println!("\n{}", "Arguments:".yellow().bold());
+    for arg in &program.args {
+        println!("\t{} \t{}", arg.manual.blue(), arg.description);
+    }
+}
+
+/// removes the flag from the arguments before `--`, true if it was there
+fn take_flag(args: &mut Vec<String>, names: &[&str]) -> bool {
+    let end = args.iter().position(|a| a == "--").unwrap_or(args.len());
+    match args[..end].iter().position(|a| names.contains(&a.as_str())) {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    }
+}
+
+fn engine(name: &str) -> Engine {
+    match name {
+        "tree" => Engine::Tree,
+        "vm" => Engine::Vm,
+        _ => {
+            raw(format!("unknown engine '{}', expected tree or vm", name).as_str());
+            exit(1);
+        }
+    }
+}
+
+fn read_file(f: &str) -> String {
+    let mut file = match File::open(f) {
         Ok(s) => s,
         Err(_) => {
             raw(format!("failed to open file '{f}'").as_str());
@@ -93,8 +551,7 @@ fn run_file(f: String, project: Project) {
             exit(1);
         }
     }
-
-    interpreter_raw(&contents, project.clone(), project.log);
+    contents
 }
 
 fn run_code(code: String, project: Project) {
@@ -134,89 +591,3 @@ fn update() {
         exit(1);
     }
 }
-
-fn add_mod(first: Option<&String>, second: Option<&String>) {
-    let name = if let Some(n) = first {
-        n.clone()
-    } else {
-        raw("expected a module name");
-        exit(1);
-    };
-
-    if let Some(alias) = second {
-        println!("adding: {} as {}", name, alias);
-        return;
-    }
-
-    let dir_name = if second.is_some() {
-        second.unwrap()
-    } else {
-        name.split('/').last().unwrap_or("mods")
-    };
-    println!("{}", format!("cloning module {}...", name).yellow());
-    let output = match Command::new("git")
-        .arg("clone")
-        .arg("--depth")
-        .arg("1")
-        .arg(format!("https://github.com/{}.git", name))
-        .arg(format!("./mods/{}", dir_name))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-    {
-        Ok(c) => c,
-        Err(e) => {
-            raw(format!("module error: {}", e).as_str());
-            exit(1);
-        }
-    };
-    println!(
-        "{}",
-        format!("module {} successfully cloned", dir_name).green()
-    );
-    /* @todo:
-        - add metadata to the project.toml
-        - add package to the environment (lib.abs)
-    */
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        raw(format!("git error: {}", stderr).as_str());
-        exit(1);
-    }
-
-    println!("{}", "module successfully installed".green());
-}
-
-fn remove_mod(first: Option<&String>) {
-    let name = if let Some(n) = first {
-        n.clone()
-    } else {
-        raw("expected a module name");
-        exit(1);
-    };
-
-    let output = match Command::new("rm")
-        .arg("-rf")
-        .arg(format!("./mods/{}", name))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-    {
-        Ok(c) => c,
-        Err(e) => {
-            raw(format!("module error: {}", e).as_str());
-            exit(1);
-        }
-    };
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        raw(format!("rm error: {}", stderr).as_str());
-        exit(1);
-    }
-
-    println!(
-        "{}",
-        format!("module {} successfully removed", name).green()
-    );
-}
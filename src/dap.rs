@@ -0,0 +1,497 @@
+// Debug Adapter Protocol server over stdio, started with `absurd dap`
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs::read_to_string,
+    io::{stdin, stdout, BufRead, Read, Write},
+    path::{Path, PathBuf},
+    process::exit,
+    rc::Rc,
+};
+
+use crate::{
+    ast::LiteralType,
+    bundler::interpreter_raw,
+    debugger::{self, Frontend, Resume, StepMode},
+    interpreter::env::Env,
+    json::Json,
+    manifest::Project,
+};
+
+thread_local! {
+    static DAP: RefCell<Option<Dap>> = const { RefCell::new(None) };
+}
+
+/// value behind a `variablesReference`
+#[derive(Debug, Clone)]
+enum VarRef {
+    Scope(Rc<RefCell<Env>>),
+    Value(LiteralType),
+}
+
+#[derive(Debug, Default)]
+struct Dap {
+    seq: usize,
+    /// references are valid until the execution resumes
+    refs: Vec<VarRef>,
+    /// the launched program, breakpoints of other sources aren't set
+    program: Option<PathBuf>,
+}
+
+/// checks if the program runs under the debug adapter
+pub fn is_active() -> bool {
+    DAP.with(|d| d.borrow().is_some())
+}
+
+/// sends program output to the client
+pub fn output(category: &str, text: &str) {
+    event(
+        "output",
+        Json::obj(vec![("category", Json::str(category)), ("output", Json::str(text))]),
+    );
+}
+
+/// serves the protocol until the client disconnects
+pub fn serve(project: Project) {
+    DAP.with(|d| *d.borrow_mut() = Some(Dap::default()));
+    let mut program = None;
+    let mut stop_on_entry = false;
+    // breakpoints can come before the launch, they're kept by source until the program is known
+    let mut breakpoints: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+
+    // configuration phase, lasts until the program is launched and configured
+    let mut configured = false;
+    while !(configured && program.is_some()) {
+        let req = read_message();
+        let cmd = command(&req);
+        let args = req.get("arguments").cloned().unwrap_or(Json::Null);
+        match cmd.as_str() {
+            "initialize" => {
+                respond(
+                    &req,
+                    Json::obj(vec![
+                        ("supportsConfigurationDoneRequest", Json::Bool(true)),
+                        ("supportsEvaluateForHovers", Json::Bool(true)),
+                    ]),
+                );
+                event("initialized", Json::Null);
+            }
+            "launch" => match args.get("program").and_then(|p| p.as_str()) {
+                Some(p) => {
+                    program = Some(p.to_string());
+                    stop_on_entry = args
+                        .get("stopOnEntry")
+                        .and_then(|s| s.as_bool())
+                        .unwrap_or(false);
+                    respond(&req, Json::Null);
+                }
+                None => respond_err(&req, "expected a 'program' argument"),
+            },
+            "setBreakpoints" => {
+                let lines = breakpoint_lines(&args);
+                let source = source_path(&args);
+                let launched = program
+                    .as_deref()
+                    .and_then(|p| Path::new(p).canonicalize().ok());
+                let verified = source.is_some() && (launched.is_none() || source == launched);
+                respond(&req, breakpoints_body(&lines, verified));
+                if let Some(source) = source {
+                    breakpoints.insert(source, lines);
+                }
+            }
+            "configurationDone" => {
+                configured = true;
+                respond(&req, Json::Null);
+            }
+            "threads" => respond(&req, threads_body()),
+            "disconnect" => {
+                respond(&req, Json::Null);
+                exit(0);
+            }
+            _ => respond_err(&req, &format!("'{}' isn't supported", cmd)),
+        }
+    }
+
+    let program = program.unwrap_or_default();
+    let source = match read_to_string(&program) {
+        Ok(s) => s,
+        Err(e) => {
+            output("stderr", &format!("failed to open file '{}': {}\n", program, e));
+            terminate();
+            return;
+        }
+    };
+    let mode = if stop_on_entry {
+        StepMode::Step
+    } else {
+        StepMode::Continue
+    };
+    let path = Path::new(&program).canonicalize().ok();
+    DAP.with(|d| {
+        if let Some(d) = d.borrow_mut().as_mut() {
+            d.program = path.clone();
+        }
+    });
+    debugger::start(&program, &source, Frontend::Dap, mode);
+    debugger::set_lines(
+        path.as_ref()
+            .and_then(|p| breakpoints.remove(p))
+            .unwrap_or_default(),
+    );
+    let mut project = project.clone();
+    project.file = path;
+    interpreter_raw(&source, project, false);
+    terminate();
+}
+
+/// reports the end of the program and waits for the client to disconnect
+fn terminate() {
+    event("exited", Json::obj(vec![("exitCode", Json::Number(0.0))]));
+    event("terminated", Json::Null);
+    loop {
+        let req = read_message();
+        match command(&req).as_str() {
+            "disconnect" => {
+                respond(&req, Json::Null);
+                exit(0);
+            }
+            "threads" => respond(&req, threads_body()),
+            cmd => respond_err(&req, &format!("'{}' isn't available, program has exited", cmd)),
+        }
+    }
+}
+
+/// called by the debugger when execution pauses, blocks until the client resumes it
+pub fn stopped(reason: &str) {
+    DAP.with(|d| {
+        if let Some(d) = d.borrow_mut().as_mut() {
+            d.refs.clear()
+        }
+    });
+    event(
+        "stopped",
+        Json::obj(vec![
+            ("reason", Json::str(reason)),
+            ("threadId", Json::Number(1.0)),
+            ("allThreadsStopped", Json::Bool(true)),
+        ]),
+    );
+
+    loop {
+        let req = read_message();
+        let cmd = command(&req);
+        let args = req.get("arguments").cloned().unwrap_or(Json::Null);
+        match cmd.as_str() {
+            "continue" => {
+                debugger::resume(Resume::Continue);
+                respond(
+                    &req,
+                    Json::obj(vec![("allThreadsContinued", Json::Bool(true))]),
+                );
+                return;
+            }
+            "next" | "stepIn" | "stepOut" => {
+                debugger::resume(match cmd.as_str() {
+                    "next" => Resume::Next,
+                    "stepIn" => Resume::Step,
+                    _ => Resume::Finish,
+                });
+                respond(&req, Json::Null);
+                return;
+            }
+            "setBreakpoints" => {
+                let lines = breakpoint_lines(&args);
+                let program = DAP.with(|d| d.borrow().as_ref().and_then(|d| d.program.clone()));
+                let verified = program.is_some() && source_path(&args) == program;
+                if verified {
+                    debugger::set_lines(lines.clone());
+                }
+                respond(&req, breakpoints_body(&lines, verified));
+            }
+            "threads" => respond(&req, threads_body()),
+            "stackTrace" => respond(&req, stack_trace()),
+            "scopes" => {
+                let frame = args.get("frameId").and_then(|f| f.as_f64()).unwrap_or(0.0);
+                respond(&req, scopes(frame as usize));
+            }
+            "variables" => {
+                let var_ref = args
+                    .get("variablesReference")
+                    .and_then(|f| f.as_f64())
+                    .unwrap_or(0.0);
+                respond(&req, variables(var_ref as usize));
+            }
+            "evaluate" => {
+                let frame = args.get("frameId").and_then(|f| f.as_f64()).unwrap_or(0.0);
+                let expr = args.get("expression").and_then(|e| e.as_str()).unwrap_or("");
+                match frame_env(frame as usize) {
+                    Some(env) => match debugger::evaluate(expr, &env) {
+                        Ok(value) => respond(
+                            &req,
+                            Json::obj(vec![
+                                ("result", Json::String(value.to_string())),
+                                ("type", Json::String(value.type_name())),
+                                ("variablesReference", Json::Number(value_ref(&value) as f64)),
+                            ]),
+                        ),
+                        Err(e) => respond_err(&req, &e),
+                    },
+                    None => respond_err(&req, "invalid frame"),
+                }
+            }
+            "pause" => respond(&req, Json::Null),
+            "disconnect" => {
+                respond(&req, Json::Null);
+                exit(0);
+            }
+            _ => respond_err(&req, &format!("'{}' isn't supported", cmd)),
+        }
+    }
+}
+
+fn breakpoint_lines(args: &Json) -> Vec<usize> {
+    args.get("breakpoints")
+        .and_then(|b| b.as_array())
+        .map(|b| {
+            b.iter()
+                .filter_map(|b| b.get("line").and_then(|l| l.as_f64()))
+                .map(|l| l as usize)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// canonical path of the `source` of a request
+fn source_path(args: &Json) -> Option<PathBuf> {
+    let path = args.get("source")?.get("path")?.as_str()?;
+    Path::new(path).canonicalize().ok()
+}
+
+/// breakpoints are only verified in the launched program
+fn breakpoints_body(lines: &[usize], verified: bool) -> Json {
+    let breakpoints = lines
+        .iter()
+        .map(|l| {
+            Json::obj(vec![
+                ("verified", Json::Bool(verified)),
+                ("line", Json::Number(*l as f64)),
+            ])
+        })
+        .collect();
+    Json::obj(vec![("breakpoints", Json::Array(breakpoints))])
+}
+
+fn threads_body() -> Json {
+    Json::obj(vec![(
+        "threads",
+        Json::Array(vec![Json::obj(vec![
+            ("id", Json::Number(1.0)),
+            ("name", Json::str("main")),
+        ])]),
+    )])
+}
+
+fn stack_trace() -> Json {
+    let file = debugger::file();
+    let name = Path::new(&file)
+        .file_name()
+        .map_or(file.clone(), |n| n.to_string_lossy().to_string());
+    let frames: Vec<Json> = debugger::frames()
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            Json::obj(vec![
+                ("id", Json::Number(i as f64)),
+                ("name", Json::String(f.name.clone())),
+                ("line", Json::Number(f.line as f64)),
+                ("column", Json::Number(1.0)),
+                (
+                    "source",
+                    Json::obj(vec![
+                        ("name", Json::String(name.clone())),
+                        ("path", Json::String(file.clone())),
+                    ]),
+                ),
+            ])
+        })
+        .collect();
+    Json::obj(vec![
+        ("totalFrames", Json::Number(frames.len() as f64)),
+        ("stackFrames", Json::Array(frames)),
+    ])
+}
+
+fn frame_env(frame: usize) -> Option<Rc<RefCell<Env>>> {
+    debugger::frames().get(frame).and_then(|f| f.env.clone())
+}
+
+/// stores the reference and returns its id
+fn add_ref(var_ref: VarRef) -> usize {
+    DAP.with(|d| match d.borrow_mut().as_mut() {
+        Some(d) => {
+            d.refs.push(var_ref);
+            d.refs.len()
+        }
+        None => 0,
+    })
+}
+
+/// compound values can be expanded in the variables view
+fn value_ref(value: &LiteralType) -> usize {
+    match value {
+        LiteralType::Vec(v) | LiteralType::Tuple(v) if !v.is_empty() => {
+            add_ref(VarRef::Value(value.clone()))
+        }
+        LiteralType::Record(r) if !r.is_empty() => add_ref(VarRef::Value(value.clone())),
+        LiteralType::Enum { value: Some(_), .. } => add_ref(VarRef::Value(value.clone())),
+        _ => 0,
+    }
+}
+
+fn scopes(frame: usize) -> Json {
+    let mut scopes = vec![];
+    let mut env = frame_env(frame);
+    while let Some(e) = env {
+        let next = e.borrow().enclosing.clone();
+        let name = match (scopes.len(), &next) {
+            (0, Some(_)) => "Locals".to_string(),
+            (_, None) => "Globals".to_string(),
+            (i, _) => format!("Scope {}", i),
+        };
+        scopes.push(Json::obj(vec![
+            ("name", Json::String(name)),
+            ("variablesReference", Json::Number(add_ref(VarRef::Scope(e)) as f64)),
+            ("expensive", Json::Bool(false)),
+        ]));
+        env = next;
+    }
+    Json::obj(vec![("scopes", Json::Array(scopes))])
+}
+
+fn variables(var_ref: usize) -> Json {
+    let var_ref = DAP.with(|d| {
+        d.borrow()
+            .as_ref()
+            .and_then(|d| d.refs.get(var_ref.wrapping_sub(1)).cloned())
+    });
+    let vars: Vec<(String, LiteralType)> = match var_ref {
+        Some(VarRef::Scope(env)) => {
+            let env = env.borrow();
+            let mut vars: Vec<(String, LiteralType)> = env
+                .entries()
+                .into_iter()
+                .filter(|(_, v)| !matches!(v.value, LiteralType::DeclrFunc(_)))
+                .map(|(k, v)| (k, v.value))
+                .collect();
+            vars.sort_by(|a, b| a.0.cmp(&b.0));
+            vars
+        }
+        Some(VarRef::Value(LiteralType::Vec(items) | LiteralType::Tuple(items))) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v.clone()))
+            .collect(),
+        Some(VarRef::Value(LiteralType::Record(fields))) => fields
+            .iter()
+            .map(|(k, v)| (k.clone(), v.to_literal()))
+            .collect(),
+        Some(VarRef::Value(LiteralType::Enum {
+            value: Some(value), ..
+        })) => vec![("0".to_string(), *value)],
+        _ => vec![],
+    };
+
+    let vars = vars
+        .iter()
+        .map(|(name, value)| {
+            Json::obj(vec![
+                ("name", Json::String(name.clone())),
+                ("value", Json::String(value.to_string())),
+                ("type", Json::String(value.type_name())),
+                ("variablesReference", Json::Number(value_ref(value) as f64)),
+            ])
+        })
+        .collect();
+    Json::obj(vec![("variables", Json::Array(vars))])
+}
+
+fn command(req: &Json) -> String {
+    req.get("command")
+        .and_then(|c| c.as_str())
+        .unwrap_or("")
+        .to_string()
+}
+
+/// reads a single `Content-Length` framed message, exits if stdin is closed
+fn read_message() -> Json {
+    let mut stdin = stdin().lock();
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if stdin.read_line(&mut header).unwrap_or(0) == 0 {
+            exit(0);
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some(len) = header.strip_prefix("Content-Length:") {
+            length = len.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; length.unwrap_or(0)];
+    if stdin.read_exact(&mut body).is_err() {
+        exit(0);
+    }
+    Json::parse(&String::from_utf8_lossy(&body)).unwrap_or(Json::Null)
+}
+
+fn send(mut fields: Vec<(&str, Json)>) {
+    let seq = DAP.with(|d| match d.borrow_mut().as_mut() {
+        Some(d) => {
+            d.seq += 1;
+            d.seq
+        }
+        None => 0,
+    });
+    fields.insert(0, ("seq", Json::Number(seq as f64)));
+    let body = Json::obj(fields).to_string();
+    let mut out = stdout().lock();
+    write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+        .and_then(|_| out.flush())
+        .expect("failed to write to stdout");
+}
+
+fn respond(req: &Json, body: Json) {
+    let mut fields = vec![
+        ("type", Json::str("response")),
+        ("request_seq", req.get("seq").cloned().unwrap_or(Json::Null)),
+        ("success", Json::Bool(true)),
+        ("command", Json::String(command(req))),
+    ];
+    if body != Json::Null {
+        fields.push(("body", body));
+    }
+    send(fields);
+}
+
+fn respond_err(req: &Json, msg: &str) {
+    send(vec![
+        ("type", Json::str("response")),
+        ("request_seq", req.get("seq").cloned().unwrap_or(Json::Null)),
+        ("success", Json::Bool(false)),
+        ("command", Json::String(command(req))),
+        ("message", Json::str(msg)),
+    ]);
+}
+
+fn event(name: &str, body: Json) {
+    let mut fields = vec![("type", Json::str("event")), ("event", Json::str(name))];
+    if body != Json::Null {
+        fields.push(("body", body));
+    }
+    send(fields);
+}
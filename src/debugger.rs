@@ -0,0 +1,370 @@
+// interactive step debugger, enabled with `absurd debug <file>` or `absurd dap`
+use coloredpp::Colorize;
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    io::{stdin, stdout, Write},
+    path::{Path, PathBuf},
+    process::exit,
+    rc::Rc,
+};
+
+use crate::{
+    ast::{LiteralType, Statement},
+    bundler::parse_expr,
+    dap,
+    errors::{catch, Error},
+    interpreter::env::Env,
+    manifest::Project,
+};
+
+thread_local! {
+    static DEBUGGER: RefCell<Option<Debugger>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepMode {
+    /// run until a breakpoint is reached
+    Continue,
+    /// stop at the next statement
+    Step,
+    /// stop at the next statement in the same or an outer function
+    Next(usize),
+    /// stop at the next statement after returning from the current function
+    Finish(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resume {
+    Step,
+    Next,
+    Finish,
+    Continue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frontend {
+    /// interactive prompt in the terminal
+    Terminal,
+    /// debug adapter protocol over stdio
+    Dap,
+}
+
+#[derive(Debug, Clone)]
+pub struct DebugFrame {
+    pub name: String,
+    pub line: usize,
+    /// environment of the last executed statement in the frame
+    pub env: Option<Rc<RefCell<Env>>>,
+}
+
+#[derive(Debug)]
+pub struct Debugger {
+    file: String,
+    /// the debugged file, line breakpoints are only set in it
+    path: Option<PathBuf>,
+    source: String,
+    lines: HashSet<usize>,
+    funcs: HashSet<String>,
+    mode: StepMode,
+    frontend: Frontend,
+    frames: Vec<DebugFrame>,
+    /// statement of the last pause, a loop coming back to it pauses there again
+    paused: Option<Statement>,
+    /// disables hooks while the debugger evaluates expressions
+    evaluating: bool,
+}
+
+/// starts the debugger, `mode` decides if execution pauses at the first statement
+pub fn start(file: &str, source: &str, frontend: Frontend, mode: StepMode) {
+    DEBUGGER.with(|d| {
+        *d.borrow_mut() = Some(Debugger {
+            file: file.to_string(),
+            path: Path::new(file).canonicalize().ok(),
+            source: source.to_string(),
+            lines: HashSet::new(),
+            funcs: HashSet::new(),
+            mode,
+            frontend,
+            frames: vec![DebugFrame {
+                name: "main".to_string(),
+                line: 0,
+                env: None,
+            }],
+            paused: None,
+            evaluating: false,
+        })
+    });
+}
+
+/// called by the interpreter before every statement
+pub fn hook(stmt: &Statement, env: &Rc<RefCell<Env>>) {
+    let pause = DEBUGGER.with(|d| match d.borrow_mut().as_mut() {
+        Some(d) if !d.evaluating => {
+            if let Some(frame) = d.frames.last_mut() {
+                frame.env = Some(Rc::clone(env));
+            }
+            // modules run in the env of the importer, with their own file in its project
+            let file = env.borrow().project.file.clone();
+            d.should_pause(stmt, file.as_deref())
+                .map(|reason| (reason, d.frontend.clone()))
+        }
+        _ => None,
+    });
+    match pause {
+        Some((_, Frontend::Terminal)) => repl(env),
+        Some((reason, Frontend::Dap)) => dap::stopped(reason),
+        None => {}
+    }
+}
+
+/// pushes a function frame, called when the function body starts
+pub fn enter(name: &str) {
+    DEBUGGER.with(|d| {
+        if let Some(d) = d.borrow_mut().as_mut() {
+            if d.evaluating {
+                return;
+            }
+            d.frames.push(DebugFrame {
+                name: name.to_string(),
+                line: 0,
+                env: None,
+            });
+            if d.funcs.contains(name) {
+                d.mode = StepMode::Step;
+            }
+        }
+    });
+}
+
+/// pops the function frame
+pub fn exit_frame() {
+    DEBUGGER.with(|d| {
+        if let Some(d) = d.borrow_mut().as_mut() {
+            if !d.evaluating && d.frames.len() > 1 {
+                d.frames.pop();
+            }
+        }
+    });
+}
+
+impl Debugger {
+    /// returns the reason of the pause, if execution should pause
+    fn should_pause(&mut self, stmt: &Statement, file: Option<&Path>) -> Option<&'static str> {
+        let line = stmt.line();
+        let depth = self.frames.len();
+        let prev_line = self.frames.last().map_or(0, |f| f.line);
+        if let Some(frame) = self.frames.last_mut() {
+            frame.line = line;
+        }
+        let reason = match self.mode {
+            StepMode::Step => Some("step"),
+            StepMode::Next(d) if depth <= d => Some("step"),
+            StepMode::Finish(d) if depth < d => Some("step"),
+            // statements nested in the one that stopped on the same line don't stop again
+            _ if line != 0
+                && file == self.path.as_deref()
+                && self.lines.contains(&line)
+                && (line != prev_line || self.paused.as_ref() == Some(stmt)) =>
+            {
+                Some("breakpoint")
+            }
+            _ => None,
+        };
+        if reason.is_some() {
+            self.paused = Some(stmt.clone());
+        }
+        reason
+    }
+
+    fn source_line(&self, line: usize) -> String {
+        self.source
+            .lines()
+            .nth(line.wrapping_sub(1))
+            .unwrap_or("")
+            .to_string()
+    }
+}
+
+fn with<T>(f: impl FnOnce(&mut Debugger) -> T) -> T {
+    DEBUGGER.with(|d| f(d.borrow_mut().as_mut().expect("debugger isn't running")))
+}
+
+/// sets how the execution continues after a pause
+pub fn resume(resume: Resume) {
+    with(|d| {
+        d.mode = match resume {
+            Resume::Step => StepMode::Step,
+            Resume::Next => StepMode::Next(d.frames.len()),
+            Resume::Finish => StepMode::Finish(d.frames.len()),
+            Resume::Continue => StepMode::Continue,
+        }
+    });
+}
+
+/// replaces all line breakpoints
+pub fn set_lines(lines: Vec<usize>) {
+    with(|d| d.lines = lines.into_iter().collect());
+}
+
+/// call stack, innermost frame first
+pub fn frames() -> Vec<DebugFrame> {
+    with(|d| d.frames.iter().rev().cloned().collect())
+}
+
+pub fn file() -> String {
+    with(|d| d.file.clone())
+}
+
+/// evaluates an expression without triggering the debugger hooks, a bad expression
+/// returns its error and the program keeps running
+pub fn evaluate(src: &str, env: &Rc<RefCell<Env>>) -> Result<LiteralType, String> {
+    with(|d| d.evaluating = true);
+    let value = catch(|| parse_expr(src, Error::new(src, Project::new())).eval(Rc::clone(env)));
+    with(|d| d.evaluating = false);
+    value
+}
+
+fn repl(env: &Rc<RefCell<Env>>) {
+    let (file, line, source) = with(|d| {
+        let line = d.frames.last().map_or(0, |f| f.line);
+        (d.file.clone(), line, d.source_line(line))
+    });
+    if line == 0 {
+        println!("{}", format!("{}:?", file).blue());
+    } else {
+        println!(
+            "{} {} {}",
+            format!("{}:{}", file, line).blue(),
+            "|".yellow(),
+            source.trim()
+        );
+    }
+
+    loop {
+        print!("{}", "(debug) ".yellow());
+        stdout().flush().expect("failed to flush stdout");
+        let mut input = String::new();
+        if stdin().read_line(&mut input).unwrap_or(0) == 0 {
+            exit(0);
+        }
+        let input = input.trim();
+        let (cmd, arg) = match input.split_once(' ') {
+            Some((c, a)) => (c, a.trim()),
+            None => (input, ""),
+        };
+
+        match cmd {
+            "" => {}
+            "s" | "step" => return resume(Resume::Step),
+            "n" | "next" => return resume(Resume::Next),
+            "f" | "finish" => return resume(Resume::Finish),
+            "c" | "continue" => return resume(Resume::Continue),
+            "b" | "break" => breakpoint(arg, true),
+            "d" | "delete" => breakpoint(arg, false),
+            "p" | "print" => print_expr(arg, env),
+            "l" | "locals" => print_locals(env),
+            "bt" | "stack" => print_stack(),
+            "q" | "quit" => exit(0),
+            "h" | "help" => help(),
+            _ => println!("{}", format!("unknown command '{}', try 'help'", cmd).red()),
+        }
+    }
+}
+
+fn breakpoint(arg: &str, add: bool) {
+    if arg.is_empty() {
+        with(|d| {
+            let mut lines: Vec<&usize> = d.lines.iter().collect();
+            lines.sort();
+            lines.iter().for_each(|l| println!("  line {}", l));
+            d.funcs.iter().for_each(|f| println!("  func {}", f));
+        });
+        return;
+    }
+    with(|d| match arg.parse::<usize>() {
+        Ok(line) if add => {
+            d.lines.insert(line);
+        }
+        Ok(line) => {
+            d.lines.remove(&line);
+        }
+        Err(_) if add => {
+            d.funcs.insert(arg.to_string());
+        }
+        Err(_) => {
+            d.funcs.remove(arg);
+        }
+    });
+}
+
+fn print_expr(src: &str, env: &Rc<RefCell<Env>>) {
+    if src.is_empty() {
+        println!("{}", "expected an expression".red());
+        return;
+    }
+    match evaluate(src, env) {
+        Ok(value) => println!("{}", value),
+        Err(e) => println!("{}", e.red()),
+    }
+}
+
+fn print_locals(env: &Rc<RefCell<Env>>) {
+    let mut scope = Some(Rc::clone(env));
+    let mut depth = 0;
+    while let Some(env) = scope {
+        let env = env.borrow();
+        let mut vals: Vec<(String, LiteralType)> = env
+            .entries()
+            .into_iter()
+            .filter(|(_, v)| !matches!(v.value, LiteralType::DeclrFunc(_)))
+            .map(|(k, v)| (k, v.value))
+            .collect();
+        vals.sort_by(|a, b| a.0.cmp(&b.0));
+        println!("{}", format!("scope {}:", depth).yellow());
+        for (name, value) in vals {
+            println!("  {} = {}", name.blue(), value);
+        }
+        scope = env.enclosing.clone();
+        depth += 1;
+    }
+}
+
+fn print_stack() {
+    with(|d| {
+        for (i, frame) in d.frames.iter().rev().enumerate() {
+            println!(
+                "  #{} {} at {}:{}",
+                i,
+                frame.name.blue(),
+                d.file,
+                frame.line
+            );
+        }
+    });
+}
+
+fn help() {
+    println!("  {}          stop at the next statement", "s, step".blue());
+    println!("  {}          step over function calls", "n, next".blue());
+    println!(
+        "  {}        run until the current function returns",
+        "f, finish".blue()
+    );
+    println!("  {}      run until a breakpoint", "c, continue".blue());
+    println!(
+        "  {}  add a breakpoint, lists them without an argument",
+        "b, break <line|func>".blue()
+    );
+    println!("  {} remove a breakpoint", "d, delete <line|func>".blue());
+    println!(
+        "  {}    evaluate an expression in the current scope",
+        "p, print <expr>".blue()
+    );
+    println!(
+        "  {}        show locals of every enclosing scope",
+        "l, locals".blue()
+    );
+    println!("  {}        show the call stack", "bt, stack".blue());
+    println!("  {}          quit the debugger", "q, quit".blue());
+}
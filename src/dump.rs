@@ -0,0 +1,623 @@
+// prints tokens, syntax trees, resolved locals and bytecode, used by `absurd dump`
+use std::collections::HashMap;
+
+use crate::{
+    ast::{FuncBody, LiteralKind, LiteralType, Statement, Token},
+    bundler::parser,
+    errors::{raw, Error},
+    interpreter::{env::Locals, expr::Expression, Interpreter},
+    json::Json,
+    manifest::Project,
+    optimizer::optimize,
+    parser::scanner::Scanner,
+    resolver::Resolver,
+    vm::Compiler,
+};
+
+/// prints the requested stage of the `src`, either as text or JSON
+pub fn dump(src: &str, stage: &str, json: bool, project: Project) {
+    let err = Error::new(src, project.clone());
+    // `-O` shows the statements as they run, resolving doesn't depend on the pass
+    let optimized = project.optimize;
+    let parse = |err| match parser(src, err, false) {
+        stmts if optimized => optimize(stmts, false),
+        stmts => stmts,
+    };
+    let out = match stage {
+        "tokens" => {
+            let tokens = Scanner::new(src, err, false).scan().clone();
+            Json::Array(tokens.iter().map(token_node).collect())
+        }
+        "ast" => Json::Array(parse(err).iter().map(stmt).collect()),
+        "resolved" => {
+            let stmts = parse(err.clone());
+            let int = Interpreter::new(project, err.clone());
+            let locals = Resolver::new(err).resolve(&stmts, &int.env);
+            resolved(&stmts, locals)
+        }
+        "bytecode" => {
+            let chunk = Compiler::new().compile(&parse(err));
+            Json::Array(chunk.disassemble().into_iter().map(Json::String).collect())
+        }
+        _ => {
+            raw(format!(
+                "unknown dump stage '{}', expected tokens, ast, resolved or bytecode",
+                stage
+            )
+            .as_str());
+            return;
+        }
+    };
+
+    if json {
+        println!("{}", out);
+        return;
+    }
+    let mut text = String::new();
+    match (stage, &out) {
+        ("tokens", Json::Array(tokens)) => {
+            tokens.iter().for_each(|t| text.push_str(&token_line(t)))
+        }
+        ("bytecode", Json::Array(ops)) => ops
+            .iter()
+            .for_each(|o| text.push_str(&format!("{}\n", o.as_str().unwrap_or("")))),
+        (_, Json::Array(nodes)) => nodes.iter().for_each(|n| render(n, 0, &mut text)),
+        _ => {}
+    }
+    print!("{}", text);
+}
+
+fn token_node(token: &Token) -> Json {
+    let value = match &token.value {
+        Some(LiteralKind::Number { value, .. }) => Json::Number(*value as f64),
+        Some(LiteralKind::String { value }) => Json::String(value.clone()),
+        Some(LiteralKind::Char { value }) => Json::String(value.to_string()),
+        Some(LiteralKind::Bool { value }) => Json::Bool(*value),
+        Some(LiteralKind::Type(t)) => Json::String(format!("{:?}", t)),
+        Some(LiteralKind::Null) | None => Json::Null,
+    };
+    Json::obj(vec![
+        ("kind", Json::String(format!("{:?}", token.token))),
+        ("lexeme", Json::String(token.lexeme.clone())),
+        ("value", value),
+        ("line", Json::Number(token.line as f64)),
+        ("start", Json::Number(token.pos.0 as f64)),
+        ("end", Json::Number(token.pos.1 as f64)),
+    ])
+}
+
+/// `line:start-end  Kind  "lexeme"  value`
+fn token_line(token: &Json) -> String {
+    let field = |k: &str| token.get(k).cloned().unwrap_or(Json::Null);
+    let mut line = format!(
+        "{:<10} {:<12} {}",
+        format!("{}:{}-{}", field("line"), field("start"), field("end")),
+        field("kind").as_str().unwrap_or(""),
+        field("lexeme")
+    );
+    if field("value") != Json::Null {
+        line.push_str(&format!("  {}", field("value")));
+    }
+    line.push('\n');
+    line
+}
+
+fn name(token: &Token) -> Json {
+    Json::String(token.lexeme.clone())
+}
+
+fn names(tokens: &[Token]) -> Json {
+    Json::Array(tokens.iter().map(name).collect())
+}
+
+fn opt_name(token: &Option<Token>) -> Json {
+    token.as_ref().map_or(Json::Null, name)
+}
+
+fn params(params: &[(Token, Token)]) -> Json {
+    Json::Array(
+        params
+            .iter()
+            .map(|(n, t)| Json::String(format!("{}: {}", n.lexeme, t.lexeme)))
+            .collect(),
+    )
+}
+
+fn stmts(stmts: &[Statement]) -> Json {
+    Json::Array(stmts.iter().map(stmt).collect())
+}
+
+fn body(body: &FuncBody) -> Json {
+    match body {
+        FuncBody::Statements(s) => stmts(s),
+        FuncBody::Expression(e) => expr(e),
+    }
+}
+
+fn node(kind: &str, mut fields: Vec<(&str, Json)>) -> Json {
+    fields.insert(0, ("kind", Json::str(kind)));
+    Json::obj(fields)
+}
+
+fn stmt(stmt: &Statement) -> Json {
+    match stmt {
+        Statement::Record {
+            name: n,
+            extends,
+            is_strict,
+            fields,
+        } => node(
+            "RecordStmt",
+            vec![
+                ("name", name(n)),
+                ("extends", names(extends)),
+                ("strict", Json::Bool(*is_strict)),
+                (
+                    "fields",
+                    Json::Array(
+                        fields
+                            .iter()
+                            .map(|f| {
+                                node(
+                                    "Field",
+                                    vec![
+                                        ("name", name(&f.name)),
+                                        ("type", name(&f.value)),
+                                        ("strict", Json::Bool(f.is_strict)),
+                                        ("optional", Json::Bool(f.is_optional)),
+                                        (
+                                            "default",
+                                            f.default_value.as_ref().map_or(Json::Null, expr),
+                                        ),
+                                    ],
+                                )
+                            })
+                            .collect(),
+                    ),
+                ),
+            ],
+        ),
+        Statement::Enum {
+            name: n,
+            is_pub,
+            items,
+        } => node(
+            "EnumStmt",
+            vec![
+                ("name", name(n)),
+                ("pub", Json::Bool(*is_pub)),
+                (
+                    "items",
+                    Json::Array(
+                        items
+                            .iter()
+                            .map(|(i, t)| match t {
+                                Some(t) => Json::String(format!("{}({})", i.lexeme, t.lexeme)),
+                                None => name(i),
+                            })
+                            .collect(),
+                    ),
+                ),
+            ],
+        ),
+        Statement::Sh { cmd, .. } => node("Sh", vec![("cmd", Json::String(cmd.clone()))]),
+        Statement::Type {
+            name: n,
+            is_pub,
+            value,
+        } => node(
+            "TypeStmt",
+            vec![
+                ("name", name(n)),
+                ("pub", Json::Bool(*is_pub)),
+                ("value", name(value)),
+            ],
+        ),
+        Statement::Expression { expr: e } => node("ExprStmt", vec![("expr", expr(e))]),
+        Statement::Block { stmts: s } => node("Block", vec![("stmts", stmts(s))]),
+        Statement::Var {
+            id,
+            names: n,
+            destruct,
+            value_type,
+            value,
+            is_mut,
+            is_pub,
+            pub_names,
+            is_func,
+        } => node(
+            "Let",
+            vec![
+                ("id", Json::Number(*id as f64)),
+                ("names", names(n)),
+                (
+                    "destruct",
+                    destruct
+                        .as_ref()
+                        .map_or(Json::Null, |d| Json::String(format!("{:?}", d))),
+                ),
+                ("type", name(value_type)),
+                ("mut", Json::Bool(*is_mut)),
+                ("pub", Json::Bool(*is_pub)),
+                ("pub_names", names(pub_names)),
+                ("func", Json::Bool(*is_func)),
+                ("value", value.as_ref().map_or(Json::Null, expr)),
+            ],
+        ),
+        Statement::Func {
+            id,
+            name: n,
+            value_type,
+            body: b,
+            params: p,
+            is_async,
+            is_pub,
+        } => node(
+            "FuncStmt",
+            vec![
+                ("id", Json::Number(*id as f64)),
+                ("name", name(n)),
+                ("params", params(p)),
+                ("type", name(value_type)),
+                ("async", Json::Bool(*is_async)),
+                ("pub", Json::Bool(*is_pub)),
+                ("body", body(b)),
+            ],
+        ),
+        Statement::If {
+            cond,
+            body: b,
+            else_if_branches,
+            else_branch,
+        } => node(
+            "IfStmt",
+            vec![
+                ("cond", expr(cond)),
+                ("body", stmts(b)),
+                (
+                    "elifs",
+                    Json::Array(
+                        else_if_branches
+                            .iter()
+                            .map(|(c, b)| node("Elif", vec![("cond", expr(c)), ("body", stmts(b))]))
+                            .collect(),
+                    ),
+                ),
+                (
+                    "else",
+                    else_branch.as_ref().map_or(Json::Null, |b| stmts(b)),
+                ),
+            ],
+        ),
+        Statement::Return { expr: e } => node("Return", vec![("expr", expr(e))]),
+        Statement::For {
+            iterator,
+            index,
+            expr: e,
+            body: b,
+        } => node(
+            "For",
+            vec![
+                ("iterator", name(iterator)),
+                ("index", opt_name(index)),
+                ("expr", expr(e)),
+                ("body", stmts(b)),
+            ],
+        ),
+        Statement::While { cond, body: b } => {
+            node("While", vec![("cond", expr(cond)), ("body", stmts(b))])
+        }
+        Statement::Loop { iter, body: b } => node(
+            "Loop",
+            vec![
+                ("iter", iter.map_or(Json::Null, |i| Json::Number(i as f64))),
+                ("body", stmts(b)),
+            ],
+        ),
+        Statement::Break { .. } => node("Break", vec![]),
+        Statement::Match {
+            cond,
+            cases,
+            def_case,
+        } => node(
+            "Match",
+            vec![
+                ("cond", expr(cond)),
+                (
+                    "cases",
+                    Json::Array(
+                        cases
+                            .iter()
+                            .map(|(p, b)| {
+                                node("Case", vec![("pattern", expr(p)), ("body", body(b))])
+                            })
+                            .collect(),
+                    ),
+                ),
+                ("default", body(def_case)),
+            ],
+        ),
+        Statement::Mod { src, name: n, .. } => node(
+            "Mod",
+            vec![
+                ("src", Json::String(src.clone())),
+                (
+                    "name",
+                    n.as_ref().map_or(Json::Null, |n| Json::String(n.clone())),
+                ),
+            ],
+        ),
+        Statement::Use {
+            id,
+            src,
+            names: n,
+            all,
+        } => node(
+            "Use",
+            vec![
+                ("id", Json::Number(*id as f64)),
+                ("src", Json::String(src.clone())),
+                (
+                    "names",
+                    Json::Array(
+                        n.iter()
+                            .map(|(n, alias)| match alias {
+                                Some(a) => Json::String(format!("{} as {}", n.lexeme, a.lexeme)),
+                                None => name(n),
+                            })
+                            .collect(),
+                    ),
+                ),
+                ("all", Json::Bool(*all)),
+            ],
+        ),
+    }
+}
+
+fn expr(expr_: &Expression) -> Json {
+    let id = ("id", Json::Number(expr_.id() as f64));
+    match expr_ {
+        Expression::If {
+            cond,
+            body,
+            else_branch,
+            ..
+        } => node(
+            "If",
+            vec![
+                id,
+                ("cond", expr(cond)),
+                ("body", expr(body)),
+                ("else", else_branch.as_ref().map_or(Json::Null, |e| expr(e))),
+            ],
+        ),
+        Expression::Record { fields, .. } => node("Record", vec![id, ("fields", record(fields))]),
+        Expression::Assign {
+            name: n,
+            value,
+            kind,
+            ..
+        } => node(
+            "Assign",
+            vec![
+                id,
+                ("name", name(n)),
+                ("op", Json::String(format!("{:?}", kind))),
+                ("value", expr(value)),
+            ],
+        ),
+        Expression::Vec { items, .. } => node(
+            "Vec",
+            vec![id, ("items", Json::Array(items.iter().map(expr).collect()))],
+        ),
+        Expression::Tuple { items, .. } => node(
+            "Tuple",
+            vec![id, ("items", Json::Array(items.iter().map(expr).collect()))],
+        ),
+        Expression::Var { name: n, .. } => node("Var", vec![id, ("name", name(n))]),
+        Expression::Path { names: n, .. } => node("Path", vec![id, ("names", names(n))]),
+        Expression::Call {
+            name: n,
+            args,
+            call_type,
+            ..
+        } => node(
+            "Call",
+            vec![
+                id,
+                ("type", Json::String(format!("{:?}", call_type))),
+                ("callee", expr(n)),
+                ("args", Json::Array(args.iter().map(expr).collect())),
+            ],
+        ),
+        Expression::Unary { left, operator, .. } => node(
+            "Unary",
+            vec![id, ("op", name(operator)), ("expr", expr(left))],
+        ),
+        Expression::Binary {
+            left,
+            operator,
+            right,
+            ..
+        } => node(
+            "Binary",
+            vec![
+                id,
+                ("op", name(operator)),
+                ("left", expr(left)),
+                ("right", expr(right)),
+            ],
+        ),
+        Expression::Grouping { expression, .. } => {
+            node("Grouping", vec![id, ("expr", expr(expression))])
+        }
+        Expression::Interpolation { parts, .. } => node(
+            "Interpolation",
+            vec![
+                id,
+                (
+                    "parts",
+                    Json::Array(
+                        parts
+                            .iter()
+                            .map(|(e, spec)| match spec {
+                                Some(spec) => node(
+                                    "Format",
+                                    vec![
+                                        ("spec", Json::String(spec.to_string())),
+                                        ("expr", expr(e)),
+                                    ],
+                                ),
+                                None => expr(e),
+                            })
+                            .collect(),
+                    ),
+                ),
+            ],
+        ),
+        Expression::Value { value, .. } => node(
+            "Value",
+            vec![
+                id,
+                ("type", Json::String(value.type_name())),
+                ("value", literal(value)),
+            ],
+        ),
+        Expression::Func {
+            name: n,
+            value_type,
+            body: b,
+            params: p,
+            is_async,
+            is_pub,
+            ..
+        } => node(
+            "Func",
+            vec![
+                id,
+                ("name", name(n)),
+                ("params", params(p)),
+                ("type", name(value_type)),
+                ("async", Json::Bool(*is_async)),
+                ("pub", Json::Bool(*is_pub)),
+                ("body", body(b)),
+            ],
+        ),
+        Expression::Await { expr: e, .. } => node("Await", vec![id, ("expr", expr(e))]),
+        Expression::Range { lhs, rhs, .. } => node(
+            "Range",
+            vec![
+                id,
+                ("lhs", Json::Number(*lhs as f64)),
+                ("rhs", Json::Number(*rhs as f64)),
+            ],
+        ),
+    }
+}
+
+fn record(fields: &[(String, Expression)]) -> Json {
+    Json::Array(
+        fields
+            .iter()
+            .map(|(k, v)| {
+                node(
+                    "Field",
+                    vec![("name", Json::String(k.clone())), ("value", expr(v))],
+                )
+            })
+            .collect(),
+    )
+}
+
+fn literal(value: &LiteralType) -> Json {
+    match value {
+        LiteralType::Number(n) => Json::Number(*n as f64),
+        LiteralType::String(s) => Json::String(s.clone()),
+        LiteralType::Char(c) => Json::String(c.to_string()),
+        LiteralType::Boolean(b) => Json::Bool(*b),
+        LiteralType::Null | LiteralType::Void => Json::Null,
+        LiteralType::Vec(items) | LiteralType::Tuple(items) => {
+            Json::Array(items.iter().map(literal).collect())
+        }
+        LiteralType::Record(fields) => record(fields),
+        LiteralType::Func(_) | LiteralType::DeclrFunc(_) | LiteralType::Enum { .. } => {
+            Json::String(value.to_string())
+        }
+    }
+}
+
+/// resolved locals as `{ id, name, depth, slot }`, names are taken from the syntax tree
+fn resolved(stmts_: &[Statement], locals: Locals) -> Json {
+    let mut ids = HashMap::new();
+    collect_names(&stmts(stmts_), &mut ids);
+    let mut locals: Vec<(usize, (usize, usize))> = locals.into_iter().collect();
+    locals.sort();
+    Json::Array(
+        locals
+            .into_iter()
+            .map(|(id, (depth, slot))| {
+                node(
+                    "Local",
+                    vec![
+                        ("id", Json::Number(id as f64)),
+                        ("name", ids.get(&id).cloned().unwrap_or(Json::Null)),
+                        ("depth", Json::Number(depth as f64)),
+                        ("slot", Json::Number(slot as f64)),
+                    ],
+                )
+            })
+            .collect(),
+    )
+}
+
+/// maps ids of variable and assignment expressions to their names,
+/// and ids of declarations to their first name
+fn collect_names(node: &Json, ids: &mut HashMap<usize, Json>) {
+    match node {
+        Json::Object(fields) => {
+            let kind = node.get("kind").and_then(|k| k.as_str());
+            let name = node
+                .get("name")
+                .or_else(|| node.get("names")?.as_array()?.first());
+            if let (Some("Var" | "Assign" | "Let" | "FuncStmt" | "Use"), Some(id), Some(name)) =
+                (kind, node.get("id").and_then(|i| i.as_f64()), name)
+            {
+                ids.insert(id as usize, name.clone());
+            }
+            fields.iter().for_each(|(_, v)| collect_names(v, ids));
+        }
+        Json::Array(items) => items.iter().for_each(|i| collect_names(i, ids)),
+        _ => {}
+    }
+}
+
+fn is_node(value: &Json) -> bool {
+    match value {
+        Json::Object(_) => true,
+        Json::Array(items) => items.iter().any(is_node),
+        _ => false,
+    }
+}
+
+/// indented text form, `Kind key=value` with child nodes on the following lines
+fn render(value: &Json, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    match value {
+        Json::Object(fields) => {
+            out.push_str(&indent);
+            out.push_str(value.get("kind").and_then(|k| k.as_str()).unwrap_or("?"));
+            for (k, v) in fields {
+                if k != "kind" && *v != Json::Null && !is_node(v) {
+                    out.push_str(&format!(" {}={}", k, v));
+                }
+            }
+            out.push('\n');
+            for (k, v) in fields.iter().filter(|(_, v)| is_node(v)) {
+                out.push_str(&format!("{}  {}:\n", indent, k));
+                render(v, depth + 2, out);
+            }
+        }
+        Json::Array(items) => items.iter().for_each(|i| render(i, depth, out)),
+        v => out.push_str(&format!("{}{}\n", indent, v)),
+    }
+}
@@ -1,9 +1,38 @@
 // handles Absurd errors
 use super::manifest::Project;
 use coloredpp::Colorize;
-use std::process::exit;
+use std::{
+    cell::Cell,
+    panic::{self, AssertUnwindSafe},
+    process::exit,
+};
 use ErrorCode::*;
 
+thread_local! {
+    /// set while errors are returned by `catch` instead of ending the process
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// runs `f`, an error it throws is returned as its message instead of being printed
+/// and ending the process, used by the debugger to evaluate what the user typed
+pub fn catch<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    let prev = CATCHING.with(|c| c.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|c| c.set(prev));
+    result.map_err(|e| match e.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(e) => panic::resume_unwind(e),
+    })
+}
+
+/// unwinds to the `catch` with the message, if errors are being caught
+fn caught(msg: String) {
+    if CATCHING.with(|c| c.get()) {
+        // `resume_unwind` skips the panic hook, nothing is printed
+        panic::resume_unwind(Box::new(msg));
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ErrorCode {
     /// `syntax error (E0x101): malformed or unterminated char`
@@ -25,6 +54,9 @@ pub enum ErrorCode {
     E0x106,
     /// `sybtax error (E0x107): invalid assignment target`
     E0x107,
+    /// `syntax error (E0x108): invalid format spec '{0}'`
+    /// - {0}: spec
+    E0x108,
     /// `runtime error (E0x301): type mismatch: expected '{0}', got '{1}'`
     /// - {0}: expected type
     /// - {1}: actual type
@@ -67,10 +99,33 @@ pub enum ErrorCode {
     /// `runtime error (E0x416): failed to get values from {0}`
     /// - {0}: source
     E0x416,
+    /// `runtime error (E0x417): failed to open module {0}: {1}`
+    /// - {0}: source
+    /// - {1}: reason
+    E0x417,
+    /// `runtime error (E0x418): circular module import: {0}`
+    /// - {0}: import chain
+    E0x418,
+    /// `runtime error (E0x419): '{0}' is private to module {1}`
+    /// - {0}: name
+    /// - {1}: module
+    E0x419,
+    /// `runtime error (E0x420): module {0} has no '{1}'`
+    /// - {0}: module
+    /// - {1}: name
+    E0x420,
+    /// `runtime error (E0x421): format spec '{0}' expects a non-negative integer, got {1}`
+    /// - {0}: spec
+    /// - {1}: value
+    E0x421,
     /// `environment error (E0x501): failed to get a distance`
     E0x501,
     /// `environment error (E0x502): failed to resolve a value`
     E0x502,
+    /// `environment error (E0x503): slot {1} of the scope doesn't hold '{0}'`
+    /// - {0}: name
+    /// - {1}: slot
+    E0x503,
 }
 
 #[derive(Debug, Clone)]
@@ -132,6 +187,13 @@ impl Error {
                 line,
                 pos,
             ),
+            E0x108 => self.error(
+                108,
+                "syntax",
+                format!("invalid format spec '{}'", args[0]),
+                line,
+                pos,
+            ),
             E0x301 => self.error(
                 301,
                 "runtime",
@@ -253,6 +315,44 @@ impl Error {
                 line,
                 pos,
             ),
+            E0x417 => self.error(
+                417,
+                "runtime",
+                format!("failed to open module {}: {}", args[0], args[1]),
+                line,
+                pos,
+            ),
+            E0x418 => self.error(
+                418,
+                "runtime",
+                format!("circular module import: {}", args[0]),
+                line,
+                pos,
+            ),
+            E0x419 => self.error(
+                419,
+                "runtime",
+                format!("'{}' is private to module {}", args[0], args[1]),
+                line,
+                pos,
+            ),
+            E0x420 => self.error(
+                420,
+                "runtime",
+                format!("module {} has no '{}'", args[0], args[1]),
+                line,
+                pos,
+            ),
+            E0x421 => self.error(
+                421,
+                "runtime",
+                format!(
+                    "format spec '{}' expects a non-negative integer, got {}",
+                    args[0], args[1]
+                ),
+                line,
+                pos,
+            ),
             E0x501 => self.error(
                 501,
                 "environment",
@@ -267,12 +367,20 @@ impl Error {
                 line,
                 pos,
             ),
+            E0x503 => self.error(
+                503,
+                "environment",
+                format!("slot {} of the scope doesn't hold '{}'", args[1], args[0]),
+                line,
+                pos,
+            ),
         };
     }
 }
 
 impl Error {
     pub fn error(&self, code: usize, head: &str, msg: String, line: usize, pos: (usize, usize)) {
+        caught(format!("{} error E0x{}: {}", head, code, msg));
         let mut is_snippet = false;
         if line != 0 || pos != (0, 0) {
             is_snippet = true;
@@ -353,6 +461,7 @@ impl Error {
 }
 
 pub fn raw(msg: &str) {
+    caught(msg.to_string());
     eprintln!("{}", msg.red());
     exit(0);
 }
@@ -0,0 +1,81 @@
+// source formatter used by `absurd fmt`
+const INDENT: &str = "    ";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Code,
+    Str,
+    Char,
+    Comment,
+}
+
+/// indents lines by their bracket depth, removes trailing whitespace,
+/// keeps at most one blank line in a row and ends the file with a newline
+pub fn format(src: &str) -> String {
+    let mut lines: Vec<String> = vec![];
+    let mut depth = 0;
+    let mut state = State::Code;
+    for (i, line) in src.lines().enumerate() {
+        let trimmed = line.trim();
+        if i == 0 && line.starts_with("#!") {
+            lines.push(line.trim_end().to_string());
+            continue;
+        }
+        // strings and block comments spanning lines are kept as they are
+        if state != State::Code {
+            lines.push(line.trim_end().to_string());
+        } else if trimmed.is_empty() {
+            if lines.last().is_some_and(|l| !l.is_empty()) {
+                lines.push(String::new());
+            }
+        } else {
+            let closing = trimmed
+                .chars()
+                .take_while(|c| matches!(c, '}' | ']' | ')'))
+                .count();
+            let level: usize = depth - closing.min(depth);
+            lines.push(format!("{}{}", INDENT.repeat(level), trimmed));
+        }
+        depth = scan(line, depth, &mut state);
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    match lines.is_empty() {
+        true => String::new(),
+        false => lines.join("\n") + "\n",
+    }
+}
+
+/// bracket depth after the `line`, brackets in strings and comments are skipped
+fn scan(line: &str, mut depth: usize, state: &mut State) -> usize {
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (*state, c) {
+            (State::Code, '"') => *state = State::Str,
+            (State::Code, '\'') => *state = State::Char,
+            (State::Code, '#') => break,
+            (State::Code, '/') if chars.peek() == Some(&'/') => break,
+            (State::Code, '/') if chars.peek() == Some(&'*') => {
+                chars.next();
+                *state = State::Comment;
+            }
+            (State::Code, '{' | '[' | '(') => depth += 1,
+            (State::Code, '}' | ']' | ')') => depth = depth.saturating_sub(1),
+            (State::Str | State::Char, '\\') => {
+                chars.next();
+            }
+            (State::Str, '"') | (State::Char, '\'') => *state = State::Code,
+            (State::Comment, '*') if chars.peek() == Some(&'/') => {
+                chars.next();
+                *state = State::Code;
+            }
+            _ => {}
+        }
+    }
+    // chars can't span lines
+    if *state == State::Char {
+        *state = State::Code;
+    }
+    depth
+}
@@ -3,12 +3,16 @@ use crate::{
     errors::{Error, ErrorCode::*},
     manifest::Project,
 };
-use std::{borrow::Borrow, cell::RefCell, collections::HashMap, process::exit, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, process::exit, rc::Rc};
 
 type EnvValueType = Rc<RefCell<HashMap<String, ValueType>>>;
 type EnvTypeValueType = Rc<RefCell<HashMap<String, Token>>>;
 type EnvEnumValueType = Rc<RefCell<HashMap<String, Vec<(Token, Option<Token>)>>>>;
 type ModEnvValueType = Rc<RefCell<HashMap<String, Vec<(String, ValueType)>>>>;
+type EnvSlotsType = Rc<RefCell<Vec<Option<(String, ValueType)>>>>;
+/// `(depth, slot)` of the resolved variables by the id of their expressions,
+/// and the first slot of the names declared by local statements by their id
+pub type Locals = HashMap<usize, (usize, usize)>;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ValueKind {
@@ -16,6 +20,10 @@ pub enum ValueKind {
     Func(FuncKind),
     Type(Token),
     Enum(Vec<(Token, Option<Token>)>),
+    /// module loaded by a module, with its exports
+    Mod(Vec<(String, ValueType)>),
+    /// name of a module that isn't `pub`
+    Private,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -42,14 +50,19 @@ pub struct ValueType {
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Env {
+    /// values of the global scope
     pub values: EnvValueType,
+    /// values of a local scope, in the order of their resolver slots
+    pub slots: EnvSlotsType,
     pub pub_vals: EnvValueType,
     pub mod_vals: ModEnvValueType,
     pub type_values: EnvTypeValueType,
     pub enums: EnvEnumValueType,
     pub mods: Vec<Env>,
-    pub locals: Rc<RefCell<HashMap<usize, usize>>>,
+    pub locals: Rc<RefCell<Locals>>,
     pub enclosing: Option<Rc<RefCell<Env>>>,
+    /// project of the code running in the scope, functions defined in it keep it
+    pub project: Rc<Project>,
 }
 
 impl Env {
@@ -57,9 +70,10 @@ impl Env {
         Error::new("", Project::new())
     }
 
-    pub fn new(locals: HashMap<usize, usize>) -> Self {
+    pub fn new(locals: Locals) -> Self {
         Self {
             values: get_empty_rc(),
+            slots: get_empty_sl(),
             pub_vals: get_empty_rc(),
             mod_vals: get_empty_md(),
             type_values: get_empty_tc(),
@@ -67,33 +81,71 @@ impl Env {
             mods: Vec::new(),
             locals: Rc::new(RefCell::new(locals)),
             enclosing: None,
+            project: Rc::new(Project::new()),
         }
     }
 
-    pub fn enclose(&self) -> Env {
+    /// a local scope inside of the `parent`, which stays shared with it
+    pub fn enclose(parent: &Rc<RefCell<Env>>) -> Env {
+        let env = parent.borrow();
         Self {
             values: get_empty_rc(),
+            slots: get_empty_sl(),
             pub_vals: get_empty_rc(),
             mod_vals: get_empty_md(),
             type_values: get_empty_tc(),
             enums: get_empty_ec(),
-            mods: self.mods.clone(),
-            locals: Rc::clone(&self.locals),
-            enclosing: Some(Rc::new(RefCell::new(self.clone()))),
+            mods: env.mods.clone(),
+            locals: Rc::clone(&env.locals),
+            enclosing: Some(Rc::clone(parent)),
+            project: Rc::clone(&env.project),
         }
     }
 
-    pub fn resolve(&self, locals: HashMap<usize, usize>) {
+    pub fn resolve(&self, locals: Locals) {
         for (k, v) in locals.iter() {
             self.locals.borrow_mut().insert(*k, *v);
         }
     }
 
+    /// first slot the resolver gave to the names of the statement `id`,
+    /// none for statements of the global scope
+    pub fn slot(&self, id: usize) -> Option<usize> {
+        self.locals.borrow().get(&id).map(|(_, slot)| *slot)
+    }
+
+    /// defines `k` in the `slot` the resolver gave it, or by name without one
+    pub fn define(&self, k: String, slot: Option<usize>, v: ValueType) {
+        let Some(slot) = slot else {
+            self.values.borrow_mut().insert(k, v);
+            return;
+        };
+        let mut slots = self.slots.borrow_mut();
+        if slots.len() <= slot {
+            slots.resize(slot + 1, None);
+        }
+        slots[slot] = Some((k, v));
+    }
+
+    /// values defined in this scope, by name
+    pub fn entries(&self) -> Vec<(String, ValueType)> {
+        let values = self.values.borrow();
+        let pub_vals = self.pub_vals.borrow();
+        let slots = self.slots.borrow();
+        values
+            .iter()
+            .chain(pub_vals.iter())
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .chain(slots.iter().flatten().cloned())
+            .collect()
+    }
+
     // variable value definitions
 
-    pub fn define_var(&self, k: String, v: LiteralType, f: VarKind) {
-        self.values.borrow_mut().insert(
+    pub fn define_var(&self, k: String, slot: Option<usize>, v: LiteralType, f: VarKind) {
+        self.define(
             k,
+            slot,
             ValueType {
                 value: v,
                 kind: ValueKind::Var(f),
@@ -125,9 +177,10 @@ impl Env {
 
     // function value definitions
 
-    pub fn define_func(&self, k: String, v: LiteralType, f: FuncKind) {
-        self.values.borrow_mut().insert(
+    pub fn define_func(&self, k: String, slot: Option<usize>, v: LiteralType, f: FuncKind) {
+        self.define(
             k,
+            slot,
             ValueType {
                 value: v,
                 kind: ValueKind::Func(f),
@@ -231,80 +284,171 @@ impl Env {
         self.enums.borrow_mut().get(k).unwrap_or(&vec![]).clone()
     }
 
-    // global
-    pub fn remove(&self, name: String) {
-        self.values.borrow_mut().remove(&name);
+    /// if `k` is an enum of this scope or of the enclosing ones
+    pub fn is_enum(&self, k: &str) -> bool {
+        self.enums.borrow_mut().contains_key(k)
+            || matches!(
+                self.get_own(k),
+                Some(ValueType {
+                    kind: ValueKind::Enum(_),
+                    ..
+                })
+            )
+            || self
+                .enclosing
+                .as_ref()
+                .is_some_and(|env| env.borrow_mut().is_enum(k))
     }
 
-    pub fn get(&self, name: String, id: usize) -> Option<ValueType> {
-        let d = self.locals.borrow_mut().get(&id).cloned();
-        self.get_int(name.as_str(), d)
-    }
-
-    pub fn get_int(&self, name: &str, d: Option<usize>) -> Option<ValueType> {
-        match d {
-            None => match &self.enclosing {
-                Some(env) => env.borrow_mut().get_int(name, None),
-                None => self
-                    .values
-                    .borrow_mut()
-                    .get(name)
-                    .cloned()
-                    .or_else(|| self.pub_vals.borrow_mut().get(name).cloned()),
+    // module definitions
+
+    /// adds a module loaded by the module `source` to its exports
+    pub fn define_mod_mod(&self, source: String, k: String, v: Vec<(String, ValueType)>) {
+        let mut mod_vals = self.mod_vals.borrow_mut();
+        let entry = mod_vals.entry(source).or_default();
+        entry.push((
+            k,
+            ValueType {
+                value: LiteralType::Void,
+                kind: ValueKind::Mod(v),
+            },
+        ));
+    }
+
+    /// records a name of the module `source` that can't be reached from outside
+    pub fn define_mod_private(&self, source: String, k: String) {
+        let mut mod_vals = self.mod_vals.borrow_mut();
+        let entry = mod_vals.entry(source).or_default();
+        entry.push((
+            k,
+            ValueType {
+                value: LiteralType::Null,
+                kind: ValueKind::Private,
             },
-            Some(depth) => {
-                if depth == 0 {
-                    self.values.borrow_mut().get(name).cloned()
-                } else {
-                    match &self.enclosing {
-                        Some(env) => env.borrow_mut().get_int(name, Some(depth - 1)),
-                        None => {
-                            self.err().throw(E0x502, 0, (0, 0), vec![]);
-                            exit(1);
-                        }
-                    }
+        ));
+    }
+
+    /// exports of the module named `k`, from this scope or the enclosing ones
+    pub fn get_mod(&self, k: &str) -> Option<Vec<(String, ValueType)>> {
+        let key = format!("\"{}\"", k);
+        match self.mod_vals.borrow_mut().get(&key) {
+            Some(vals) => Some(vals.clone()),
+            None => self.enclosing.as_ref()?.borrow_mut().get_mod(k),
+        }
+    }
+
+    // lookup
+
+    /// the variable `name` of the expression `id`, by its resolved slot or by name
+    pub fn get(&self, name: String, id: usize) -> Option<ValueType> {
+        let local = self.locals.borrow_mut().get(&id).cloned();
+        match local {
+            Some((depth, slot)) => self.get_at(name.as_str(), depth, slot),
+            None => self.get_int(name.as_str()),
+        }
+    }
+
+    /// looks up `name` by name, from this scope to the global one
+    pub fn get_int(&self, name: &str) -> Option<ValueType> {
+        if let Some(v) = self.get_own(name) {
+            return Some(v);
+        }
+        match &self.enclosing {
+            Some(env) => env.borrow_mut().get_int(name),
+            None => None,
+        }
+    }
+
+    /// the value in the `slot` of the scope `depth` levels up, none if it isn't defined yet
+    fn get_at(&self, name: &str, depth: usize, slot: usize) -> Option<ValueType> {
+        if depth > 0 {
+            return match &self.enclosing {
+                Some(env) => env.borrow_mut().get_at(name, depth - 1, slot),
+                None => {
+                    self.err().throw(E0x502, 0, (0, 0), vec![]);
+                    exit(1);
                 }
-            }
+            };
+        }
+        match self.slots.borrow().get(slot) {
+            Some(Some((k, v))) if k == name => Some(v.clone()),
+            Some(Some(_)) => self.missing(name, slot),
+            _ => None,
         }
     }
 
+    /// the slot holds another name, so the resolver and the runtime disagree on its scopes
+    fn missing(&self, name: &str, slot: usize) -> ! {
+        self.err()
+            .throw(E0x503, 0, (0, 0), vec![name.to_string(), slot.to_string()]);
+        exit(1);
+    }
+
+    fn get_own(&self, name: &str) -> Option<ValueType> {
+        if let Some((_, v)) = self
+            .slots
+            .borrow()
+            .iter()
+            .flatten()
+            .find(|(k, _)| k == name)
+        {
+            return Some(v.clone());
+        }
+        self.values
+            .borrow_mut()
+            .get(name)
+            .cloned()
+            .or_else(|| self.pub_vals.borrow_mut().get(name).cloned())
+    }
+
+    /// assigns to the variable `name` of the expression `id`, false if it isn't defined
     pub fn assing(&self, name: String, value: ValueType, id: usize) -> bool {
-        let d = self.locals.borrow_mut().get(&id).cloned();
-        self.set_int(name.as_str(), value, d)
-    }
-
-    pub fn set_int(&self, name: &str, value: ValueType, d: Option<usize>) -> bool {
-        if d.is_none() {
-            match &self.enclosing {
-                Some(env) => env.borrow_mut().set_int(name, value, d),
-                None => self
-                    .borrow()
-                    .values
-                    .borrow_mut()
-                    .insert(name.to_string(), value)
-                    .is_some(),
-            }
-        } else {
-            let d = match d {
-                Some(d) => d,
+        let local = self.locals.borrow_mut().get(&id).cloned();
+        match local {
+            Some((depth, slot)) => self.set_at(name.as_str(), value, depth, slot),
+            None => self.set_int(name.as_str(), value),
+        }
+    }
+
+    /// assigns to `name` in the closest scope that defines it
+    pub fn set_int(&self, name: &str, value: ValueType) -> bool {
+        if let Some((_, v)) = self
+            .slots
+            .borrow_mut()
+            .iter_mut()
+            .flatten()
+            .find(|(k, _)| k == name)
+        {
+            *v = value;
+            return true;
+        }
+        if let Some(v) = self.values.borrow_mut().get_mut(name) {
+            *v = value;
+            return true;
+        }
+        match &self.enclosing {
+            Some(env) => env.borrow_mut().set_int(name, value),
+            None => false,
+        }
+    }
+
+    /// assigns to the `slot` of the scope `depth` levels up, false if it isn't defined yet
+    fn set_at(&self, name: &str, value: ValueType, depth: usize, slot: usize) -> bool {
+        if depth > 0 {
+            return match &self.enclosing {
+                Some(env) => env.borrow_mut().set_at(name, value, depth - 1, slot),
                 None => {
                     self.err().throw(E0x501, 0, (0, 0), vec![]);
-                    exit(1)
+                    exit(1);
                 }
             };
-            if d <= 0 {
-                self.values.borrow_mut().remove(name);
-                true
-            } else {
-                match &self.enclosing {
-                    Some(env) => env.borrow_mut().set_int(name, value, Some(d - 1)),
-                    None => {
-                        self.err().throw(E0x502, 0, (0, 0), vec![]);
-                        exit(1);
-                    }
-                }
-            }
         }
+        match self.slots.borrow_mut().get_mut(slot) {
+            Some(Some((k, v))) if k == name => *v = value,
+            Some(Some(_)) => self.missing(name, slot),
+            _ => return false,
+        }
+        true
     }
 }
 
@@ -324,4 +468,8 @@ fn get_empty_md() -> ModEnvValueType {
     Rc::new(RefCell::new(HashMap::new()))
 }
 
+fn get_empty_sl() -> EnvSlotsType {
+    Rc::new(RefCell::new(Vec::new()))
+}
+
 // @todo better memory manager
@@ -1,19 +1,24 @@
 use super::env::{Env, ValueKind, ValueType, VarKind};
 use super::types::TypeKind;
-use crate::ast::{LiteralKind, Statement};
-use crate::bundler::parse_expr;
+use crate::ast::LiteralKind;
 use crate::errors::{Error, ErrorCode::*};
 use crate::interpreter::types::{type_check, typekind_to_literaltype};
 use crate::manifest::Project;
+use crate::profiler::{self, FrameKind};
 use crate::{
-    ast::{CallType, FuncBody, FuncImpl, LiteralType, Token, TokenType::*},
-    interpreter::run_func,
+    ast::{
+        format::FormatSpec,
+        CallType, FuncBody, FuncImpl, LiteralType, Token,
+        TokenType::{self, *},
+    },
+    interpreter::call_func,
 };
 use core::cmp::Eq;
+use serde::{Deserialize, Serialize};
 use std::process::exit;
 use std::{cell::RefCell, fmt, rc::Rc};
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum AssignKind {
     Normal,
     Plus,
@@ -22,62 +27,88 @@ pub enum AssignKind {
     Div,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Expression {
     If {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         cond: Box<Expression>,
         body: Box<Expression>,
         else_branch: Option<Box<Expression>>,
     },
     Record {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         fields: Vec<(String, Expression)>,
     },
     Assign {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         name: Token,
         value: Box<Expression>,
         kind: AssignKind,
     },
     Vec {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         items: Vec<Expression>,
     },
     Tuple {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         items: Vec<Expression>,
     },
     Var {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         name: Token,
     },
+    /// `Color::Red`, `src::helper`, `src::Color::Red`
+    Path {
+        #[serde(deserialize_with = "crate::cache::node_id")]
+        id: usize,
+        names: Vec<Token>,
+    },
     Call {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         name: Box<Expression>,
         args: Vec<Expression>,
         call_type: CallType,
     },
     Unary {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         left: Box<Expression>,
         operator: Token,
     },
     Binary {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         left: Box<Expression>,
         operator: Token,
         right: Box<Expression>,
     },
     Grouping {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         expression: Box<Expression>,
     },
     Value {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         value: LiteralType,
+        /// line of the literal, 0 for values the interpreter makes up
+        line: usize,
+    },
+    /// `"a {b:>8} c"`, the text around the placeholders is kept as string values
+    Interpolation {
+        #[serde(deserialize_with = "crate::cache::node_id")]
+        id: usize,
+        parts: Vec<(Expression, Option<FormatSpec>)>,
     },
     Func {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         name: Token,
         value_type: Token,
@@ -87,13 +118,16 @@ pub enum Expression {
         is_pub: bool,
     },
     Await {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         expr: Box<Expression>,
     },
     Range {
+        #[serde(deserialize_with = "crate::cache::node_id")]
         id: usize,
         lhs: usize,
         rhs: usize,
+        line: usize,
     },
 }
 
@@ -108,6 +142,7 @@ impl Expression {
             Expression::Range { id, .. } => *id,
             Expression::Record { id, .. } => *id,
             Expression::Var { id, .. } => *id,
+            Expression::Path { id, .. } => *id,
             Expression::Tuple { id, .. } => *id,
             Expression::Call { id, .. } => *id,
             Expression::Func { id, .. } => *id,
@@ -116,12 +151,53 @@ impl Expression {
             Expression::Binary { id, .. } => *id,
             Expression::Unary { id, .. } => *id,
             Expression::Value { id, .. } => *id,
+            Expression::Interpolation { id, .. } => *id,
             Expression::Grouping { id, .. } => *id,
             Expression::Assign { id, .. } => *id,
             Expression::If { id, .. } => *id,
         }
     }
 
+    /// line of the first token in the expression, 0 if the expression has no tokens
+    pub fn line(&self) -> usize {
+        match self {
+            Expression::Var { name, .. }
+            | Expression::Assign { name, .. }
+            | Expression::Func { name, .. } => name.line,
+            Expression::Unary { operator, left, .. } => match operator.line {
+                0 => left.line(),
+                line => line,
+            },
+            Expression::Binary { left, operator, .. } => match left.line() {
+                0 => operator.line,
+                line => line,
+            },
+            Expression::Call { name, .. } => name.line(),
+            Expression::Path { names, .. } => names.first().map_or(0, |n| n.line),
+            Expression::Grouping {
+                expression: expr, ..
+            }
+            | Expression::Await { expr, .. }
+            | Expression::If { cond: expr, .. } => expr.line(),
+            Expression::Vec { items, .. } | Expression::Tuple { items, .. } => items
+                .iter()
+                .map(|i| i.line())
+                .find(|l| *l != 0)
+                .unwrap_or(0),
+            Expression::Interpolation { parts, .. } => parts
+                .iter()
+                .map(|(p, _)| p.line())
+                .find(|l| *l != 0)
+                .unwrap_or(0),
+            Expression::Record { fields, .. } => fields
+                .iter()
+                .map(|(_, v)| v.line())
+                .find(|l| *l != 0)
+                .unwrap_or(0),
+            Expression::Value { line, .. } | Expression::Range { line, .. } => *line,
+        }
+    }
+
     pub fn to_literal(&self) -> LiteralType {
         match self {
             Expression::Value { value, .. } => value.clone(),
@@ -152,279 +228,29 @@ impl Expression {
                 LiteralType::Null
             }
             Expression::Record { fields, .. } => LiteralType::Record(fields.clone()),
-            Expression::Assign {
-                name, value, kind, ..
-            } => {
-                let mut val = (*value).eval(Rc::clone(&env));
-                let mut is_mut = false;
-                match env.borrow().get(name.lexeme.clone(), self.id()) {
-                    Some(v) => match v.clone().kind {
-                        ValueKind::Var(s) => {
-                            if !s.is_mut {
-                                self.err().throw(E0x410, name.line, name.pos, vec![]);
-                            }
-                            is_mut = true;
-                            if s.is_pub {
-                                self.err().throw(E0x411, name.line, name.pos, vec![]);
-                            }
-                            match v.value {
-                                LiteralType::Number(x) => match kind {
-                                    AssignKind::Plus => match val {
-                                        LiteralType::Number(n) => {
-                                            val = LiteralType::Number(n + x);
-                                        }
-                                        _ => {}
-                                    },
-                                    AssignKind::Minus => match val {
-                                        LiteralType::Number(n) => {
-                                            val = LiteralType::Number(n - x);
-                                        }
-                                        _ => {}
-                                    },
-                                    AssignKind::Mult => match val {
-                                        LiteralType::Number(n) => {
-                                            val = LiteralType::Number(n * x);
-                                        }
-                                        _ => {}
-                                    },
-                                    AssignKind::Div => match val {
-                                        LiteralType::Number(n) => {
-                                            val = LiteralType::Number(n / x);
-                                        }
-                                        _ => {}
-                                    },
-                                    _ => {}
-                                },
-                                _ => {
-                                    if kind.clone() != AssignKind::Normal {
-                                        self.err().throw(E0x414, name.line, name.pos, vec![]);
-                                        exit(1);
-                                    }
-                                }
-                            }
-
-                            if v.value.type_name() != val.type_name() {
-                                if let ValueKind::Var(s) = v.kind {
-                                    if let Some(LiteralKind::Type(c)) = s.value_type.value.clone() {
-                                        if let TypeKind::Either { lhs, rhs } = *c {
-                                            let left_true = if let TypeKind::Var { name } = *lhs {
-                                                type_check(&name, &val, &env)
-                                            } else {
-                                                false
-                                            };
-
-                                            let right_true = if let TypeKind::Var { name } = *rhs {
-                                                type_check(&name, &val, &env)
-                                            } else {
-                                                false
-                                            };
-
-                                            if !left_true && !right_true {
-                                                self.err().throw(
-                                                    E0x412,
-                                                    name.line,
-                                                    name.pos,
-                                                    vec![name.clone().lexeme],
-                                                );
-                                            }
-                                        } else {
-                                            let expected_type = typekind_to_literaltype(*c);
-                                            if val != expected_type {
-                                                self.err().throw(
-                                                    E0x412,
-                                                    name.line,
-                                                    name.pos,
-                                                    vec![name.clone().lexeme],
-                                                );
-                                            }
-                                        }
-                                    } else {
-                                        self.err().throw(
-                                            E0x412,
-                                            name.line,
-                                            name.pos,
-                                            vec![name.clone().lexeme],
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                        _ => {
-                            self.err().throw(E0x413, name.line, name.pos, vec![]);
-                        }
-                    },
-                    None => {}
-                }
-                let ass_val = ValueType {
-                    kind: ValueKind::Var(VarKind {
-                        is_mut,
-                        is_pub: false,
-                        is_func: false,
-                        value_type: name.clone(),
-                    }),
-                    value: val.clone(),
-                };
-                let assigned = env
-                    .borrow_mut()
-                    .assing(name.lexeme.clone(), ass_val, self.id());
-
-                if assigned {
-                    val
-                } else {
-                    self.err().throw(E0x414, name.line, name.pos, vec![]);
-                    exit(1);
-                }
+            Expression::Assign { value, .. } => {
+                let val = value.eval(Rc::clone(&env));
+                self.assign(val, env)
             }
-            Expression::Var { name, .. } => {
-                let lexeme = name.lexeme.as_str();
-                let env_borrow = env.borrow();
+            Expression::Var { name, .. } => variable(name, self.id(), &env),
 
-                if let Some(v) = env_borrow.get(name.lexeme.clone(), self.id()) {
-                    v.value.clone()
-                } else if let Some(v) = env_borrow.values.borrow().get(lexeme) {
-                    v.value.clone()
-                } else if env_borrow.enums.borrow().contains_key(lexeme) {
-                    LiteralType::Enum {
-                        parent: name.clone(),
-                        name: Token::null(),
-                        value: None,
-                    }
-                } else {
-                    LiteralType::Null
-                }
-            }
-
-            Expression::Call {
-                name,
-                args,
-                call_type,
-                ..
-            } => {
-                let call: LiteralType = name.eval(Rc::clone(&env));
-                match call {
-                    LiteralType::Enum { .. } => {
-                        if let CallType::Enum = call_type {
-                            let parent = if let Expression::Var { name, .. } = *name.clone() {
-                                name
-                            } else {
-                                Token::null()
-                            };
-                            let name = if let Expression::Var { name, .. } = args.get(0).unwrap() {
-                                name.clone()
-                            } else {
-                                Token::null()
-                            };
-
-                            let value = if let Some(v) = args.get(1) {
-                                Some(Box::new(v.eval(env)))
-                            } else {
-                                None
-                            };
-
-                            return LiteralType::Enum {
-                                parent,
-                                name,
-                                value,
-                            };
-                        }
-
-                        LiteralType::Null
-                    }
-                    LiteralType::Func(func) => run_func(func, args, env),
-                    LiteralType::DeclrFunc(func) => {
-                        let evals = args
-                            .iter()
-                            .map(|arg| Some(arg.eval(Rc::clone(&env))))
-                            .collect();
-
-                        (*func.func).call(evals)
-                    }
-                    LiteralType::Vec(res) => match args.get(0).unwrap() {
-                        Expression::Value { value, .. } => {
-                            if let LiteralType::Number(n) = value {
-                                res.get(*n as usize).unwrap().clone()
-                            } else {
-                                LiteralType::Null
-                            }
-                        }
-                        _ => LiteralType::Null,
-                    },
-                    LiteralType::Tuple(res) => match args.get(0).unwrap() {
-                        Expression::Value { value, .. } => {
-                            if let LiteralType::Number(n) = value {
-                                res.get(*n as usize).unwrap().clone()
-                            } else {
-                                LiteralType::Null
-                            }
-                        }
-                        _ => LiteralType::Null,
-                    },
-                    LiteralType::String(s) => match args.get(0).unwrap().eval(env) {
-                        LiteralType::Number(n) => {
-                            let mut res = LiteralType::Null;
-                            for (i, c) in s.chars().enumerate() {
-                                if i == n as usize {
-                                    res = LiteralType::Char(c);
-                                }
-                            }
-                            res
-                        }
-                        _ => LiteralType::Null,
-                    },
-                    LiteralType::Record(rec) => match args.get(0).unwrap() {
-                        Expression::Value { value, .. } => {
-                            if let LiteralType::String(s) = value {
-                                let mut res = LiteralType::Null;
-                                for (k, v) in rec {
-                                    if k == *s {
-                                        res = v.eval(Rc::clone(&env));
-                                    }
-                                }
-                                res
-                            } else {
-                                LiteralType::Null
-                            }
-                        }
-                        _ => LiteralType::Null,
-                    },
-                    _ => LiteralType::Null,
-                }
+            Expression::Path { names, .. } => self.eval_path(names, env),
+            Expression::Call { name, .. } => {
+                let callee = name.eval(Rc::clone(&env));
+                self.call(callee, env)
             }
             Expression::Grouping { expression, .. } => expression.eval(env),
-            Expression::Value { value, .. } => {
-                let v = match value.clone() {
-                    LiteralType::String(s) => {
-                        let mut result = String::new();
-                        let mut idx = 0;
-
-                        while let Some(start) = s[idx..].find('{') {
-                            result.push_str(&s[idx..idx + start]);
-                            let start_idx = idx + start + 1;
-
-                            if let Some(end) = s[start_idx..].find('}') {
-                                let expr = &s[start_idx..start_idx + end];
-                                let eval_result =
-                                    match parse_expr(expr, self.err()).eval(Rc::clone(&env)) {
-                                        LiteralType::String(eval_s) => eval_s,
-                                        LiteralType::Number(eval_n) => eval_n.to_string(),
-                                        LiteralType::Boolean(eval_b) => eval_b.to_string(),
-                                        _ => "null".to_string(),
-                                    };
-
-                                result.push_str(&eval_result);
-                                idx = start_idx + end + 1;
-                            } else {
-                                break;
-                            }
-                        }
-
-                        result.push_str(&s[idx..]);
-                        LiteralType::String(result)
-                    }
-                    c => c,
-                };
-                v.clone()
-            }
+            Expression::Value { value, .. } => value.clone(),
+            Expression::Interpolation { parts, .. } => LiteralType::String(
+                parts
+                    .iter()
+                    .map(|(p, spec)| {
+                        let value = p.eval(Rc::clone(&env));
+                        spec.as_ref()
+                            .map_or_else(|| value.to_string(), |s| s.apply(&value))
+                    })
+                    .collect(),
+            ),
 
             Expression::Func {
                 name,
@@ -435,47 +261,20 @@ impl Expression {
                 is_async,
                 id: _,
             } => {
+                // the body runs when it's called, in a scope inside of this one
                 let call = FuncImpl {
                     name: name.lexeme.clone(),
                     value_type: value_type.clone(),
-                    body: FuncBody::Statements(match body {
-                        FuncBody::Statements(stmts) => stmts
-                            .iter()
-                            .map(|stmt| {
-                                if let Statement::Return { expr } = stmt {
-                                    let v = &(*expr).eval(Rc::clone(&env));
-                                    if !type_check(value_type, v, &env) {
-                                        self.err().throw(
-                                            E0x301,
-                                            name.line,
-                                            name.pos,
-                                            vec![value_type.clone().lexeme, v.to_string()],
-                                        );
-                                    }
-                                }
-                                stmt.clone()
-                            })
-                            .collect(),
-                        FuncBody::Expression(e) => {
-                            let v = &(*e).eval(Rc::clone(&env));
-                            if !type_check(value_type, v, &env) {
-                                self.err().throw(
-                                    E0x301,
-                                    name.line,
-                                    name.pos,
-                                    vec![value_type.clone().lexeme, v.to_string()],
-                                );
-                            }
-                            vec![Statement::Expression { expr: *e.clone() }]
-                        }
-                    }),
+                    body: body.clone(),
                     params: params
                         .iter()
                         .map(|(name, value_type)| (name.clone(), value_type.clone()))
                         .collect(),
                     is_pub: *is_pub,
                     is_async: *is_async,
+                    project: Rc::clone(&env.borrow().project),
                     env: Rc::clone(&env),
+                    chunk: Rc::default(),
                 };
                 let func = LiteralType::Func(call);
                 func
@@ -500,172 +299,472 @@ impl Expression {
                 operator,
                 right,
                 ..
-            } => self.eval_binary(left, operator, right, env),
-            Expression::Unary { operator, left, .. } => self.eval_unary(operator, left, env),
+            } => binary(left.eval(Rc::clone(&env)), &operator.token, right.eval(env)),
+            Expression::Unary { operator, left, .. } => unary(&operator.token, left.eval(env)),
         }
     }
 
-    fn eval_unary(
-        &self,
-        operator: &Token,
-        left: &Expression,
-        env: Rc<RefCell<Env>>,
-    ) -> LiteralType {
-        let left = left.eval(Rc::clone(&env));
-        match (operator.clone().token, left.clone()) {
-            (Min, LiteralType::Number(a)) => LiteralType::Number(-a),
-            (Bang, _) => LiteralType::Boolean(!left.is_truthy()),
-            (DblBang, _) => LiteralType::Boolean(!!left.is_truthy()),
-            (Sqr, LiteralType::Number(a)) => LiteralType::Number(a * a),
-            (Decr, LiteralType::Number(a)) => LiteralType::Number(a - 1.0),
-            (Incr, LiteralType::Number(a)) => LiteralType::Number(a + 1.0),
-            _ => LiteralType::Null,
+    /// an enum item, or a member of a module reached through its exports
+    fn eval_path(&self, names: &[Token], env: Rc<RefCell<Env>>) -> LiteralType {
+        let (first, rest) = (&names[0], &names[1..]);
+        if rest.len() == 1 && env.borrow().is_enum(&first.lexeme) {
+            return LiteralType::Enum {
+                parent: first.clone(),
+                name: rest[0].clone(),
+                value: None,
+            };
         }
-    }
-
-    fn eval_binary(
-        &self,
-        left: &Expression,
-        operator: &Token,
-        right: &Expression,
-        env: Rc<RefCell<Env>>,
-    ) -> LiteralType {
-        let left = left.eval(Rc::clone(&env));
-        let right = right.eval(Rc::clone(&env));
-        match (left.clone(), operator.token.clone(), right.clone()) {
-            (_, Or, _) => {
-                if left.is_truthy() {
-                    return left;
+        let mut vals = match env.borrow().get_mod(&first.lexeme) {
+            Some(v) => v,
+            None => {
+                self.err()
+                    .throw(E0x416, first.line, first.pos, vec![first.lexeme.clone()]);
+                exit(1);
+            }
+        };
+        let mut module = first.lexeme.clone();
+        for (i, name) in rest.iter().enumerate() {
+            let value = match vals.iter().find(|(n, _)| *n == name.lexeme) {
+                Some((_, v)) => v.clone(),
+                None => {
+                    self.err().throw(
+                        E0x420,
+                        name.line,
+                        name.pos,
+                        vec![module, name.lexeme.clone()],
+                    );
+                    exit(1);
                 }
-                return right;
-            }
-            (_, DblAnd, _) => {
-                if !left.is_truthy() {
-                    return left.is_truthy_literal();
+            };
+            let last = i + 1 == rest.len();
+            match value.kind {
+                ValueKind::Private => {
+                    self.err().throw(
+                        E0x419,
+                        name.line,
+                        name.pos,
+                        vec![name.lexeme.clone(), module],
+                    );
+                    exit(1);
+                }
+                ValueKind::Mod(exports) if !last => {
+                    vals = exports;
+                    module = format!("{}::{}", module, name.lexeme);
+                }
+                // `src::Color::Red`
+                ValueKind::Enum(_) if i + 2 == rest.len() => {
+                    return LiteralType::Enum {
+                        parent: name.clone(),
+                        name: rest[i + 1].clone(),
+                        value: None,
+                    };
+                }
+                _ if last => return value.value,
+                _ => {
+                    self.err().throw(
+                        E0x420,
+                        rest[i + 1].line,
+                        rest[i + 1].pos,
+                        vec![
+                            format!("{}::{}", module, name.lexeme),
+                            rest[i + 1].lexeme.clone(),
+                        ],
+                    );
+                    exit(1);
                 }
-                return right;
-            }
-            (LiteralType::Number(a), Prcnt, LiteralType::Number(b)) => {
-                return LiteralType::Number(a % b);
-            }
-            (LiteralType::Number(a), Mul, LiteralType::Number(b)) => {
-                return LiteralType::Number(a * b);
-            }
-            (LiteralType::Number(a), Min, LiteralType::Number(b)) => {
-                return LiteralType::Number(a - b);
-            }
-            (LiteralType::Number(a), Div, LiteralType::Number(b)) => {
-                return LiteralType::Number(a / b);
-            }
-            (LiteralType::Number(a), Plus, LiteralType::Number(b)) => {
-                return LiteralType::Number(a + b);
-            }
-            (LiteralType::Number(a), Gr, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a > b);
-            }
-            (LiteralType::Number(a), GrOrEq, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a >= b);
-            }
-            (LiteralType::Number(a), Ls, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a < b);
-            }
-            (LiteralType::Number(a), LsOrEq, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a <= b);
-            }
-            (LiteralType::Number(a), Eq, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a == b);
-            }
-            (LiteralType::Number(a), BangEq, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a != b);
             }
-            (LiteralType::Number(a), Plus, LiteralType::Vec(v)) => {
-                let nums = v
-                    .iter()
-                    .map(|p| {
-                        if let LiteralType::Number(c) = p {
-                            LiteralType::Number(c.clone() + a)
-                        } else {
-                            LiteralType::Null
-                        }
-                    })
-                    .collect();
+        }
+        LiteralType::Null
+    }
 
-                return LiteralType::Vec(nums);
-            }
-            (LiteralType::Number(a), Min, LiteralType::Vec(v)) => {
-                let nums = v
-                    .iter()
-                    .map(|p| {
-                        if let LiteralType::Number(c) = p {
-                            LiteralType::Number(c.clone() - a)
-                        } else {
-                            LiteralType::Null
+    /// assigns the evaluated value to the variable of the assignment
+    pub fn assign(&self, mut val: LiteralType, env: Rc<RefCell<Env>>) -> LiteralType {
+        let (name, kind) = match self {
+            Expression::Assign { name, kind, .. } => (name, kind),
+            _ => return LiteralType::Null,
+        };
+        let mut is_mut = false;
+        match env.borrow().get(name.lexeme.clone(), self.id()) {
+            Some(v) => match v.clone().kind {
+                ValueKind::Var(s) => {
+                    if !s.is_mut {
+                        self.err().throw(E0x410, name.line, name.pos, vec![]);
+                    }
+                    is_mut = true;
+                    if s.is_pub {
+                        self.err().throw(E0x411, name.line, name.pos, vec![]);
+                    }
+                    match v.value {
+                        LiteralType::Number(x) => match kind {
+                            AssignKind::Plus => match val {
+                                LiteralType::Number(n) => {
+                                    val = LiteralType::Number(n + x);
+                                }
+                                _ => {}
+                            },
+                            AssignKind::Minus => match val {
+                                LiteralType::Number(n) => {
+                                    val = LiteralType::Number(n - x);
+                                }
+                                _ => {}
+                            },
+                            AssignKind::Mult => match val {
+                                LiteralType::Number(n) => {
+                                    val = LiteralType::Number(n * x);
+                                }
+                                _ => {}
+                            },
+                            AssignKind::Div => match val {
+                                LiteralType::Number(n) => {
+                                    val = LiteralType::Number(n / x);
+                                }
+                                _ => {}
+                            },
+                            _ => {}
+                        },
+                        _ => {
+                            if kind.clone() != AssignKind::Normal {
+                                self.err().throw(E0x414, name.line, name.pos, vec![]);
+                                exit(1);
+                            }
                         }
-                    })
-                    .collect();
+                    }
 
-                return LiteralType::Vec(nums);
-            }
-            (LiteralType::Number(a), Mul, LiteralType::Vec(v)) => {
-                let nums = v
-                    .iter()
-                    .map(|p| {
-                        if let LiteralType::Number(c) = p {
-                            LiteralType::Number(c.clone() * a)
-                        } else {
-                            LiteralType::Null
+                    if v.value.type_name() != val.type_name() {
+                        if let ValueKind::Var(s) = v.kind {
+                            if let Some(LiteralKind::Type(c)) = s.value_type.value.clone() {
+                                if let TypeKind::Either { lhs, rhs } = *c {
+                                    let left_true = if let TypeKind::Var { name } = *lhs {
+                                        type_check(&name, &val, &env)
+                                    } else {
+                                        false
+                                    };
+
+                                    let right_true = if let TypeKind::Var { name } = *rhs {
+                                        type_check(&name, &val, &env)
+                                    } else {
+                                        false
+                                    };
+
+                                    if !left_true && !right_true {
+                                        self.err().throw(
+                                            E0x412,
+                                            name.line,
+                                            name.pos,
+                                            vec![name.clone().lexeme],
+                                        );
+                                    }
+                                } else {
+                                    let expected_type = typekind_to_literaltype(*c);
+                                    if val != expected_type {
+                                        self.err().throw(
+                                            E0x412,
+                                            name.line,
+                                            name.pos,
+                                            vec![name.clone().lexeme],
+                                        );
+                                    }
+                                }
+                            } else {
+                                self.err().throw(
+                                    E0x412,
+                                    name.line,
+                                    name.pos,
+                                    vec![name.clone().lexeme],
+                                );
+                            }
                         }
-                    })
-                    .collect();
+                    }
+                }
+                _ => {
+                    self.err().throw(E0x413, name.line, name.pos, vec![]);
+                }
+            },
+            None => {}
+        }
+        let ass_val = ValueType {
+            kind: ValueKind::Var(VarKind {
+                is_mut,
+                is_pub: false,
+                is_func: false,
+                value_type: name.clone(),
+            }),
+            value: val.clone(),
+        };
+        let assigned = env
+            .borrow_mut()
+            .assing(name.lexeme.clone(), ass_val, self.id());
 
-                return LiteralType::Vec(nums);
-            }
-            (LiteralType::Number(a), Div, LiteralType::Vec(v)) => {
-                let nums = v
-                    .iter()
-                    .map(|p| {
-                        if let LiteralType::Number(c) = p {
-                            LiteralType::Number(c.clone() / a)
-                        } else {
-                            LiteralType::Null
+        if assigned {
+            val
+        } else {
+            self.err().throw(E0x414, name.line, name.pos, vec![]);
+            exit(1);
+        }
+    }
+
+    /// calls the evaluated callee of the call with its arguments
+    pub fn call(&self, callee: LiteralType, env: Rc<RefCell<Env>>) -> LiteralType {
+        let (name, args, call_type) = match self {
+            Expression::Call {
+                name,
+                args,
+                call_type,
+                ..
+            } => (name, args, call_type),
+            _ => return LiteralType::Null,
+        };
+        match callee {
+            // `Color::Red(1)`
+            LiteralType::Enum {
+                parent, name: item, ..
+            } => match (call_type, &**name) {
+                (CallType::Func, Expression::Path { .. }) => LiteralType::Enum {
+                    parent,
+                    name: item,
+                    value: args.first().map(|v| Box::new(v.eval(env))),
+                },
+                _ => LiteralType::Null,
+            },
+            LiteralType::Func(_) | LiteralType::DeclrFunc(_) => {
+                let values = args.iter().map(|arg| arg.eval(Rc::clone(&env))).collect();
+                apply(callee, values, env)
+            }
+            LiteralType::Vec(res) => match args.get(0).unwrap() {
+                Expression::Value { value, .. } => {
+                    if let LiteralType::Number(n) = value {
+                        res.get(*n as usize).unwrap().clone()
+                    } else {
+                        LiteralType::Null
+                    }
+                }
+                _ => LiteralType::Null,
+            },
+            LiteralType::Tuple(res) => match args.get(0).unwrap() {
+                Expression::Value { value, .. } => {
+                    if let LiteralType::Number(n) = value {
+                        res.get(*n as usize).unwrap().clone()
+                    } else {
+                        LiteralType::Null
+                    }
+                }
+                _ => LiteralType::Null,
+            },
+            LiteralType::String(s) => match args.get(0).unwrap().eval(env) {
+                LiteralType::Number(n) => {
+                    let mut res = LiteralType::Null;
+                    for (i, c) in s.chars().enumerate() {
+                        if i == n as usize {
+                            res = LiteralType::Char(c);
                         }
-                    })
-                    .collect();
+                    }
+                    res
+                }
+                _ => LiteralType::Null,
+            },
+            LiteralType::Record(rec) => match args.get(0).unwrap() {
+                Expression::Value { value, .. } => {
+                    if let LiteralType::String(s) = value {
+                        let mut res = LiteralType::Null;
+                        for (k, v) in rec {
+                            if k == *s {
+                                res = v.eval(Rc::clone(&env));
+                            }
+                        }
+                        res
+                    } else {
+                        LiteralType::Null
+                    }
+                }
+                _ => LiteralType::Null,
+            },
+            _ => LiteralType::Null,
+        }
+    }
+}
 
-                return LiteralType::Vec(nums);
-            }
-            (LiteralType::String(a), Eq, LiteralType::String(b)) => {
-                return LiteralType::Boolean(a == b);
-            }
-            (LiteralType::String(a), BangEq, LiteralType::String(b)) => {
-                return LiteralType::Boolean(a != b);
-            }
-            (LiteralType::Char(a), Eq, LiteralType::Char(b)) => {
-                return LiteralType::Boolean(a == b);
-            }
-            (LiteralType::Char(a), BangEq, LiteralType::Char(b)) => {
-                return LiteralType::Boolean(a != b);
-            }
-            (LiteralType::Boolean(a), Eq, LiteralType::Boolean(b)) => {
-                return LiteralType::Boolean(a == b);
-            }
-            (LiteralType::Boolean(a), BangEq, LiteralType::Boolean(b)) => {
-                return LiteralType::Boolean(a != b);
-            }
-            (LiteralType::Null, Eq, LiteralType::Null) => {
-                return LiteralType::Boolean(true);
-            }
-            (LiteralType::Null, BangEq, LiteralType::Null) => {
-                return LiteralType::Boolean(false);
-            }
-            (_, Eq, _) => {
-                return LiteralType::Boolean(false);
-            }
-            (_, BangEq, _) => {
-                return LiteralType::Boolean(false);
+/// the value of the variable, a name of an enum is the enum itself
+pub fn variable(name: &Token, id: usize, env: &Rc<RefCell<Env>>) -> LiteralType {
+    let lexeme = name.lexeme.as_str();
+    let env_borrow = env.borrow();
+
+    if let Some(v) = env_borrow.get(name.lexeme.clone(), id) {
+        v.value.clone()
+    } else if let Some(v) = env_borrow.values.borrow().get(lexeme) {
+        v.value.clone()
+    } else if env_borrow.enums.borrow().contains_key(lexeme) {
+        LiteralType::Enum {
+            parent: name.clone(),
+            name: Token::null(),
+            value: None,
+        }
+    } else {
+        LiteralType::Null
+    }
+}
+
+/// calls the function or the native function with evaluated arguments
+pub fn apply(callee: LiteralType, args: Vec<LiteralType>, env: Rc<RefCell<Env>>) -> LiteralType {
+    match callee {
+        LiteralType::Func(func) => call_func(func, args, env),
+        LiteralType::DeclrFunc(func) => {
+            profiler::enter(&func.name);
+            let val = (*func.func).call(args.into_iter().map(Some).collect());
+            profiler::exit(FrameKind::Native);
+            val
+        }
+        _ => LiteralType::Null,
+    }
+}
+
+/// applies the unary operator to the value
+pub fn unary(operator: &TokenType, left: LiteralType) -> LiteralType {
+    match (operator.clone(), left.clone()) {
+        (Min, LiteralType::Number(a)) => LiteralType::Number(-a),
+        (Bang, _) => LiteralType::Boolean(!left.is_truthy()),
+        (DblBang, _) => LiteralType::Boolean(!!left.is_truthy()),
+        (Sqr, LiteralType::Number(a)) => LiteralType::Number(a * a),
+        (Decr, LiteralType::Number(a)) => LiteralType::Number(a - 1.0),
+        (Incr, LiteralType::Number(a)) => LiteralType::Number(a + 1.0),
+        _ => LiteralType::Null,
+    }
+}
+
+/// applies the binary operator to the values, both sides are always evaluated
+pub fn binary(left: LiteralType, operator: &TokenType, right: LiteralType) -> LiteralType {
+    match (left.clone(), operator.clone(), right.clone()) {
+        (_, Or, _) => {
+            if left.is_truthy() {
+                return left;
+            }
+            return right;
+        }
+        (_, DblAnd, _) => {
+            if !left.is_truthy() {
+                return left.is_truthy_literal();
             }
-            _ => LiteralType::Null,
+            return right;
+        }
+        (LiteralType::Number(a), Prcnt, LiteralType::Number(b)) => {
+            return LiteralType::Number(a % b);
+        }
+        (LiteralType::Number(a), Mul, LiteralType::Number(b)) => {
+            return LiteralType::Number(a * b);
+        }
+        (LiteralType::Number(a), Min, LiteralType::Number(b)) => {
+            return LiteralType::Number(a - b);
+        }
+        (LiteralType::Number(a), Div, LiteralType::Number(b)) => {
+            return LiteralType::Number(a / b);
+        }
+        (LiteralType::Number(a), Plus, LiteralType::Number(b)) => {
+            return LiteralType::Number(a + b);
+        }
+        (LiteralType::Number(a), Gr, LiteralType::Number(b)) => {
+            return LiteralType::Boolean(a > b);
         }
+        (LiteralType::Number(a), GrOrEq, LiteralType::Number(b)) => {
+            return LiteralType::Boolean(a >= b);
+        }
+        (LiteralType::Number(a), Ls, LiteralType::Number(b)) => {
+            return LiteralType::Boolean(a < b);
+        }
+        (LiteralType::Number(a), LsOrEq, LiteralType::Number(b)) => {
+            return LiteralType::Boolean(a <= b);
+        }
+        (LiteralType::Number(a), Eq, LiteralType::Number(b)) => {
+            return LiteralType::Boolean(a == b);
+        }
+        (LiteralType::Number(a), BangEq, LiteralType::Number(b)) => {
+            return LiteralType::Boolean(a != b);
+        }
+        (LiteralType::Number(a), Plus, LiteralType::Vec(v)) => {
+            let nums = v
+                .iter()
+                .map(|p| {
+                    if let LiteralType::Number(c) = p {
+                        LiteralType::Number(c.clone() + a)
+                    } else {
+                        LiteralType::Null
+                    }
+                })
+                .collect();
+
+            return LiteralType::Vec(nums);
+        }
+        (LiteralType::Number(a), Min, LiteralType::Vec(v)) => {
+            let nums = v
+                .iter()
+                .map(|p| {
+                    if let LiteralType::Number(c) = p {
+                        LiteralType::Number(c.clone() - a)
+                    } else {
+                        LiteralType::Null
+                    }
+                })
+                .collect();
+
+            return LiteralType::Vec(nums);
+        }
+        (LiteralType::Number(a), Mul, LiteralType::Vec(v)) => {
+            let nums = v
+                .iter()
+                .map(|p| {
+                    if let LiteralType::Number(c) = p {
+                        LiteralType::Number(c.clone() * a)
+                    } else {
+                        LiteralType::Null
+                    }
+                })
+                .collect();
+
+            return LiteralType::Vec(nums);
+        }
+        (LiteralType::Number(a), Div, LiteralType::Vec(v)) => {
+            let nums = v
+                .iter()
+                .map(|p| {
+                    if let LiteralType::Number(c) = p {
+                        LiteralType::Number(c.clone() / a)
+                    } else {
+                        LiteralType::Null
+                    }
+                })
+                .collect();
+
+            return LiteralType::Vec(nums);
+        }
+        (LiteralType::String(a), Eq, LiteralType::String(b)) => {
+            return LiteralType::Boolean(a == b);
+        }
+        (LiteralType::String(a), BangEq, LiteralType::String(b)) => {
+            return LiteralType::Boolean(a != b);
+        }
+        (LiteralType::Char(a), Eq, LiteralType::Char(b)) => {
+            return LiteralType::Boolean(a == b);
+        }
+        (LiteralType::Char(a), BangEq, LiteralType::Char(b)) => {
+            return LiteralType::Boolean(a != b);
+        }
+        (LiteralType::Boolean(a), Eq, LiteralType::Boolean(b)) => {
+            return LiteralType::Boolean(a == b);
+        }
+        (LiteralType::Boolean(a), BangEq, LiteralType::Boolean(b)) => {
+            return LiteralType::Boolean(a != b);
+        }
+        (LiteralType::Null, Eq, LiteralType::Null) => {
+            return LiteralType::Boolean(true);
+        }
+        (LiteralType::Null, BangEq, LiteralType::Null) => {
+            return LiteralType::Boolean(false);
+        }
+        (_, Eq, _) => {
+            return LiteralType::Boolean(false);
+        }
+        (_, BangEq, _) => {
+            return LiteralType::Boolean(false);
+        }
+        _ => LiteralType::Null,
     }
 }
 
@@ -702,6 +801,10 @@ impl fmt::Display for Expression {
             }
             Expression::Assign { name, value, .. } => write!(f, "{} = {}", name.lexeme, value),
             Expression::Var { name, .. } => write!(f, "{}", name.lexeme),
+            Expression::Path { names, .. } => {
+                let names: Vec<&str> = names.iter().map(|n| n.lexeme.as_str()).collect();
+                write!(f, "{}", names.join("::"))
+            }
             Expression::Call { name, args, .. } => {
                 let mut args_str = String::new();
                 for arg in args {
@@ -711,6 +814,19 @@ impl fmt::Display for Expression {
             }
             Expression::Grouping { expression, .. } => write!(f, "({})", expression),
             Expression::Value { value, .. } => write!(f, "{}", value),
+            Expression::Interpolation { parts, .. } => {
+                parts.iter().try_for_each(|(p, spec)| match (p, spec) {
+                    (
+                        Expression::Value {
+                            value: LiteralType::String(s),
+                            ..
+                        },
+                        None,
+                    ) => write!(f, "{}", s.replace('{', "\\{")),
+                    (_, Some(spec)) => write!(f, "{{{}:{}}}", p, spec),
+                    (_, None) => write!(f, "{{{}}}", p),
+                })
+            }
             Expression::Func { name, .. } => write!(f, "{}", name.lexeme),
             Expression::Vec { items, .. } => {
                 let mut items_str = String::new();
@@ -1,5 +1,6 @@
 pub mod env;
 pub mod expr;
+mod modules;
 pub mod types;
 use crate::ast::{
     Destruct, FuncBody, FuncImpl, LiteralKind, LiteralType,
@@ -7,17 +8,21 @@ use crate::ast::{
     Token, TokenType,
 };
 use crate::bundler::interpreter_mod;
+use crate::debugger;
 use crate::errors::{raw, Error, ErrorCode::*};
 use crate::interpreter::types::type_check;
-use crate::manifest::Project;
-use crate::std::StdFunc;
+use crate::manifest::{self, Project, MANIFEST};
+use crate::package;
+use crate::profiler::{self, FrameKind};
+use crate::std::{core::io::write_line, StdFunc};
+use crate::vm;
 use env::{Env, FuncKind, ValueKind, VarKind};
 use expr::Expression;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::env::current_dir;
-use std::fs::File;
-use std::io::Read;
+use std::fs::read_to_string;
+use std::path::Path;
 use std::process::{exit, Command, Stdio};
 use std::rc::Rc;
 use types::TypeKind;
@@ -43,8 +48,10 @@ pub struct Interpreter {
 impl Interpreter {
     /// initialize the Interpreter
     pub fn new(project: Project, error: Error) -> Self {
+        let mut env = Env::new(HashMap::new());
+        env.project = Rc::new(project.clone());
         let int = Self {
-            env: Rc::new(RefCell::new(Env::new(HashMap::new()))),
+            env: Rc::new(RefCell::new(env)),
             project: project.clone(),
             specs: Rc::new(RefCell::new(HashMap::new())),
             is_mod: false,
@@ -54,12 +61,13 @@ impl Interpreter {
         };
         // load std::core::io
         if !project.clone().disable_std && project.clone().load_std {
-            let mut std_core_io = StdFunc::new(Rc::clone(&int.env), int.project.test);
+            let mut std_core_io = StdFunc::new(Rc::clone(&int.env), int.project.clone());
             std_core_io.load_core_io();
         }
         int
     }
 
+    /// an interpreter running in the `env`, with the project of its scope
     pub fn new_with_env(
         env: Rc<RefCell<Env>>,
         is_mod: bool,
@@ -67,18 +75,19 @@ impl Interpreter {
         mod_src: Option<String>,
         order: usize,
     ) -> Self {
+        let project = (*env.borrow().project).clone();
         let int = Self {
             env: Rc::clone(&env),
             specs: Rc::new(RefCell::new(HashMap::new())),
             is_mod,
             mod_src,
-            error: Error::new(src, Project::new()),
-            project: Project::new(),
+            error: Error::new(src, project.clone()),
+            project,
             order,
         };
         // load std::core::io if interpreter runs in the module
         if is_mod {
-            let mut std_core_io = StdFunc::new(env, false);
+            let mut std_core_io = StdFunc::new(env, int.project.clone());
             std_core_io.load_core_io();
         }
         int
@@ -90,6 +99,11 @@ impl Interpreter {
     pub fn interpret(&mut self, stmts: Vec<&Statement>, order: usize) -> Rc<RefCell<Env>> {
         self.order = order;
         for stmt in stmts {
+            // the rest of the body is skipped after a `break` or a `return`
+            if self.stopped() {
+                break;
+            }
+            debugger::hook(stmt, &self.env);
             match stmt {
                 Statement::Expression { expr } => {
                     expr.eval(Rc::clone(&self.env));
@@ -105,7 +119,7 @@ impl Interpreter {
                 Loop { iter, body } => self.loops(iter.clone(), body.clone()),
                 While { cond, body } => self.whiles(cond, body.clone()),
                 For { .. } => self.fors(stmt),
-                Break {} => {
+                Break { .. } => {
                     self.specs
                         .borrow_mut()
                         .insert("break".to_string(), LiteralType::Null);
@@ -126,35 +140,64 @@ impl Interpreter {
                     is_pub,
                 } => self.types(name, value, *is_pub),
                 Statement::Record { .. } => self.record(stmt),
-                Mod { src, name } => self.mods(src, name.clone()),
-                Use { src, names, all } => self.uses(src, names.clone(), *all),
-                Sh { cmd } => self.sh(cmd),
+                Mod { src, name, .. } => self.mods(src, name.clone()),
+                Use {
+                    id,
+                    src,
+                    names,
+                    all,
+                } => self.uses(*id, src, names.clone(), *all),
+                Sh { cmd, .. } => self.sh(cmd),
             }
         }
         Rc::clone(&self.env)
     }
 
+    /// runs the statements in a new scope, like the resolver does
     fn block(&mut self, stmts: Vec<Statement>) {
-        let new_env = self.env.borrow_mut().enclose();
+        let new_env = Env::enclose(&self.env);
         let prev_env = Rc::clone(&self.env);
         self.env = Rc::new(RefCell::new(new_env));
         self.interpret(stmts.iter().map(|x| x).collect(), 1);
         self.env = prev_env;
     }
 
+    /// if a `break` or a `return` leaves the running statements
+    fn stopped(&self) -> bool {
+        let specs = self.specs.borrow();
+        specs.contains_key("break") || specs.contains_key("return")
+    }
+
+    /// takes the "return" or "break" special set by the executed statements
+    pub fn signal(&self, name: &str) -> Option<LiteralType> {
+        self.specs.borrow_mut().remove(name)
+    }
+
     fn variable(&mut self, stmt: &Statement) {
+        if let Statement::Var { value, .. } = stmt {
+            let value = value.as_ref().map(|v| v.eval(Rc::clone(&self.env)));
+            self.define(stmt, value);
+        }
+    }
+
+    /// defines the names of the variable statement with its evaluated value
+    pub fn define(&mut self, stmt: &Statement, value: Option<LiteralType>) {
         if let Statement::Var {
+            id,
             names,
             destruct,
             value_type,
-            value,
             is_mut,
             is_pub,
             pub_names,
             is_func,
+            ..
         } = stmt
         {
-            if value.is_some() {
+            // the names take the slots after the first one in a local scope
+            let first = self.env.borrow().slot(*id);
+            let slot = |index: usize| first.map(|slot| slot + index);
+            if let Some(vl) = value {
                 // disable mutability in side effects
                 if is_mut.clone() && !self.project.side_effects {
                     self.error
@@ -166,7 +209,6 @@ impl Interpreter {
                         .throw(E0x415, names[0].line, names[0].pos, vec![]);
                 }
 
-                let vl = value.clone().unwrap().eval(Rc::clone(&self.env));
                 // @todo replace value_type with option<Token>
                 // don't type check during type inference
                 if value_type.token != TokenType::Null {
@@ -183,7 +225,7 @@ impl Interpreter {
                 // hande variables in modules
                 if self.is_mod && self.order == 0 && *is_pub {
                     // define variables in the module
-                    let val = value.as_ref().unwrap().eval(Rc::clone(&self.env));
+                    let val = vl.clone();
                     pub_names.iter().for_each(|name| {
                         self.env.borrow_mut().define_mod_var(
                             self.mod_src.clone().unwrap(),
@@ -198,6 +240,10 @@ impl Interpreter {
                         )
                     });
                 }
+                names
+                    .iter()
+                    .filter(|n| !*is_pub || !pub_names.iter().any(|p| p.lexeme == n.lexeme))
+                    .for_each(|n| self.private(&n.lexeme));
 
                 // handle callbacks in the normal env
                 if !self.is_mod && *is_func {
@@ -222,6 +268,7 @@ impl Interpreter {
                         .collect();
                     self.env.borrow_mut().define_func(
                         names[0].lexeme.clone(),
+                        slot(0),
                         func,
                         FuncKind {
                             params,
@@ -238,7 +285,7 @@ impl Interpreter {
                 };
                 // hande normal variable
                 if !self.is_mod {
-                    let val = value.clone().unwrap().eval(Rc::clone(&self.env));
+                    let val = vl.clone();
 
                     // handle the name based on the value type for destructuring
                     for (index, name) in names.clone().iter().enumerate() {
@@ -264,6 +311,7 @@ impl Interpreter {
                                     } else {
                                         self.env.borrow_mut().define_var(
                                             name.lexeme.clone(),
+                                            slot(index),
                                             entry,
                                             var_kind.clone(),
                                         );
@@ -290,6 +338,7 @@ impl Interpreter {
                                     } else {
                                         self.env.borrow_mut().define_var(
                                             name.lexeme.clone(),
+                                            slot(index),
                                             entry,
                                             var_kind.clone(),
                                         );
@@ -316,6 +365,7 @@ impl Interpreter {
                                         } else {
                                             self.env.borrow_mut().define_var(
                                                 name.lexeme.clone(),
+                                                slot(index),
                                                 entry,
                                                 var_kind.clone(),
                                             );
@@ -330,6 +380,7 @@ impl Interpreter {
                         if destruct.is_none() {
                             self.env.borrow_mut().define_var(
                                 name.lexeme.clone(),
+                                slot(index),
                                 val.clone(),
                                 var_kind.clone(),
                             );
@@ -357,9 +408,10 @@ impl Interpreter {
                 }
 
                 // define every name in the variable
-                names.iter().for_each(|name| {
+                names.iter().enumerate().for_each(|(index, name)| {
                     self.env.borrow_mut().define_var(
                         name.lexeme.clone(),
+                        slot(index),
                         LiteralType::Null,
                         VarKind {
                             is_pub: false,
@@ -375,8 +427,8 @@ impl Interpreter {
 
     fn func(&mut self, stmt: &Statement) {
         if let Statement::Func {
+            id,
             name,
-            body,
             params,
             is_async,
             is_pub,
@@ -386,21 +438,6 @@ impl Interpreter {
             let is_async = *is_async;
             let is_pub = *is_pub;
 
-            // evaluate return statement
-            match body {
-                FuncBody::Statements(statements) => {
-                    for statement in statements {
-                        if let Statement::Return { expr } = statement {
-                            expr.eval(Rc::clone(&self.env));
-                        }
-                    }
-                }
-                // for short functions
-                FuncBody::Expression(expression) => {
-                    expression.eval(Rc::clone(&self.env));
-                }
-            }
-
             // publicity is disabled in side effects
             if is_pub && !self.project.side_effects {
                 self.error.throw(E0x415, name.line, name.pos, vec![]);
@@ -440,9 +477,12 @@ impl Interpreter {
             } else
             // handle normal functions
             if !self.is_mod {
+                let slot = self.env.borrow().slot(*id);
                 self.env
                     .borrow_mut()
-                    .define_func(name.lexeme.clone(), func, func_kind);
+                    .define_func(name.lexeme.clone(), slot, func, func_kind);
+            } else {
+                self.private(&name.lexeme);
             }
         }
     }
@@ -459,7 +499,7 @@ impl Interpreter {
                 let val = cond.eval(Rc::clone(&self.env));
                 // if condition is true, execute the body
                 if val.is_truthy() {
-                    self.interpret(body.iter().map(|x| x).collect(), 1);
+                    self.block(body.clone());
                 } else {
                     let mut executed = false;
                     // check elif branches
@@ -467,14 +507,14 @@ impl Interpreter {
                         let val = cond.eval(Rc::clone(&self.env));
                         if val.is_truthy() {
                             executed = true;
-                            self.interpret(body.iter().map(|x| x).collect(), 1);
+                            self.block(body.clone());
                             break;
                         }
                     }
                     // if non of the elif branches were executed, execute else branch if there
                     if let Some(body) = else_branch {
                         if !executed {
-                            self.interpret(body.iter().map(|x| x).collect(), 1);
+                            self.block(body.clone());
                         }
                     }
                 }
@@ -488,8 +528,8 @@ impl Interpreter {
                 // explicit iterations
                 Some(i) => {
                     for _ in 0..i.clone() {
-                        self.interpret(body.iter().map(|x| x).collect(), 1);
-                        if self.specs.borrow_mut().get("break").is_some() {
+                        self.block(body.clone());
+                        if self.stopped() {
                             self.specs.borrow_mut().remove("break");
                             break;
                         }
@@ -497,8 +537,8 @@ impl Interpreter {
                 }
                 // infinite loop
                 None => loop {
-                    self.interpret(body.iter().map(|x| x).collect(), 1);
-                    if self.specs.borrow_mut().get("break").is_some() {
+                    self.block(body.clone());
+                    if self.stopped() {
                         self.specs.borrow_mut().remove("break");
                         break;
                     }
@@ -511,8 +551,8 @@ impl Interpreter {
         if !self.is_mod {
             // execute code while the condition is truthy
             while cond.eval(Rc::clone(&self.env)).is_truthy() {
-                self.interpret(body.iter().map(|x| x).collect(), 1);
-                if self.specs.borrow_mut().get("break").is_some() {
+                self.block(body.clone());
+                if self.stopped() {
                     self.specs.borrow_mut().remove("break");
                     break;
                 }
@@ -538,11 +578,25 @@ impl Interpreter {
                     vec![]
                 };
 
-                // iterate between values and define arguments
+                // every iteration gets its own scope with the arguments
                 for (id, iter) in values.iter().enumerate() {
+                    let prev_env = Rc::clone(&self.env);
+                    self.env = Rc::new(RefCell::new(Env::enclose(&prev_env)));
+                    self.env.borrow_mut().define_var(
+                        iterator.clone().lexeme,
+                        Some(0),
+                        iter.clone(),
+                        VarKind {
+                            is_pub: false,
+                            is_mut: false,
+                            is_func: false,
+                            value_type: iterator.clone(),
+                        },
+                    );
                     if let Some(token) = index {
                         self.env.borrow_mut().define_var(
                             token.clone().lexeme,
+                            Some(1),
                             LiteralType::Number(id as f32),
                             VarKind {
                                 is_pub: false,
@@ -553,28 +607,13 @@ impl Interpreter {
                         );
                     }
 
-                    self.env.borrow_mut().define_var(
-                        iterator.clone().lexeme,
-                        iter.clone(),
-                        VarKind {
-                            is_pub: false,
-                            is_mut: false,
-                            is_func: false,
-                            value_type: iterator.clone(),
-                        },
-                    );
-
                     self.interpret(body.iter().map(|x| x).collect(), 1);
-                    if self.specs.borrow_mut().get("break").is_some() {
+                    self.env = prev_env;
+                    if self.stopped() {
                         self.specs.borrow_mut().remove("break");
                         break;
                     }
                 }
-                // destroy arguments after the iteration is over
-                if let Some(token) = index {
-                    self.env.borrow_mut().remove(token.clone().lexeme);
-                }
-                self.env.borrow_mut().remove(iterator.clone().lexeme);
             }
         }
     }
@@ -621,7 +660,6 @@ impl Interpreter {
 
                             FuncBody::Statements(ref stmts) => stmts.clone(),
                         };
-                        let body = body.iter().collect();
                         let expr_lit = expr.eval(Rc::clone(&self.env));
                         // check if expression is enum
                         if let LiteralType::Enum { .. } = expr_lit {
@@ -629,7 +667,7 @@ impl Interpreter {
                             if self
                                 .enum_equality(expr.eval(Rc::clone(&self.env)), condition.clone())
                             {
-                                self.interpret(body, 1);
+                                self.block(body);
                                 exec = true;
                                 break;
                             }
@@ -647,9 +685,7 @@ impl Interpreter {
 
             if !exec {
                 match def_case.clone() {
-                    FuncBody::Statements(s) => {
-                        self.interpret(s.iter().map(|x| x).collect(), 1);
-                    }
+                    FuncBody::Statements(s) => self.block(s),
                     FuncBody::Expression(e) => {
                         self.interpret(vec![&Statement::Expression { expr: *e }], 1);
                     }
@@ -697,6 +733,7 @@ impl Interpreter {
         } else
         // handle normal enums
         {
+            self.private(&name.lexeme);
             self.env
                 .borrow_mut()
                 .define_enum(name.clone().lexeme, items.clone());
@@ -721,12 +758,22 @@ impl Interpreter {
         } else
         // handle normal types
         {
+            self.private(&name.lexeme);
             self.env
                 .borrow_mut()
                 .define_type(name.clone().lexeme, value.clone());
         }
     }
 
+    /// records a name of the module that isn't `pub`, it can't be reached from outside
+    fn private(&self, name: &str) {
+        if let (true, 0, Some(src)) = (self.is_mod, self.order, &self.mod_src) {
+            self.env
+                .borrow_mut()
+                .define_mod_private(src.clone(), name.to_string());
+        }
+    }
+
     fn record(&mut self, stmt: &Statement) {
         if let Statement::Record {
             name,
@@ -794,7 +841,7 @@ impl Interpreter {
 
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
-            println!("{}", stdout);
+            write_line(stdout.to_string());
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr);
             raw(format!("sh error: {}", stderr).as_str());
@@ -810,6 +857,7 @@ impl Interpreter {
             params,
             is_async,
             is_pub,
+            ..
         } = stmt
         {
             let params: Vec<(Token, Token)> = params
@@ -831,6 +879,8 @@ impl Interpreter {
                 is_async: *is_async,
                 is_pub: *is_pub,
                 env: Rc::clone(&self.env),
+                project: Rc::clone(&self.env.borrow().project),
+                chunk: Rc::default(),
             }
         } else if let Var { value, is_func, .. } = stmt {
             if !is_func.clone() {
@@ -852,19 +902,16 @@ impl Interpreter {
                     .iter()
                     .map(|(name, value_type)| (name.clone(), value_type.clone()))
                     .collect();
-                let body: Vec<Statement> = match body {
-                    FuncBody::Statements(stmts) => stmts.iter().map(|x| x.clone()).collect(),
-                    FuncBody::Expression(e) => vec![Statement::Expression { expr: *e.clone() }],
-                };
-
                 return FuncImpl {
                     name: name.lexeme.clone(),
                     value_type: value_type.clone(),
-                    body: FuncBody::Statements(body),
+                    body,
                     params,
                     is_async,
                     is_pub,
                     env: Rc::clone(&self.env),
+                    project: Rc::clone(&self.env.borrow().project),
+                    chunk: Rc::default(),
                 };
             }
             self.error.throw(E0x404, 0, (0, 0), vec![]);
@@ -875,43 +922,120 @@ impl Interpreter {
         }
     }
 
+    /// loads the module once, its path is relative to the importing file
     fn mods(&mut self, src: &String, name: Option<String>) {
         if !self.project.side_effects {
             self.error.throw(E0x415, 0, (0, 0), vec![]);
         }
-        let mut path = current_dir().expect("failed to get current directory");
+        let mut path = match self.project.file.as_ref().and_then(|f| f.parent()) {
+            Some(dir) => dir.to_path_buf(),
+            None => current_dir().expect("failed to get current directory"),
+        };
         path.push(src.trim_matches('"'));
-        let mut file = match File::open(path) {
-            Ok(f) => f,
-            Err(f) => {
-                raw(format!("failed to opan a file: {}", f).as_str());
-                exit(0);
+        let (path, contents) = match path
+            .canonicalize()
+            .and_then(|p| read_to_string(&p).map(|c| (p, c)))
+        {
+            Ok(p) => p,
+            Err(e) => {
+                self.error
+                    .throw(E0x417, 0, (0, 0), vec![src.clone(), e.to_string()]);
+                exit(1);
             }
         };
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)
-            .expect("failed to read a file");
+        let alias = name.clone();
         let name = if name.is_some() {
             format!("\"{}\"", name.clone().unwrap())
         } else {
             src.to_string()
         };
+        let exports = match modules::cached(&path) {
+            Some(exports) => {
+                let env = self.env.borrow();
+                env.mod_vals.borrow_mut().insert(name, exports.clone());
+                exports
+            }
+            None => {
+                if let Err(chain) = modules::enter(self.project.file.as_deref(), &path) {
+                    self.error.throw(E0x418, 0, (0, 0), vec![chain]);
+                    exit(1);
+                }
+                let mut project = self.project.clone();
+                project.file = Some(path.clone());
+                interpreter_mod(
+                    contents.as_str(),
+                    Some(name.clone()),
+                    Rc::clone(&self.env),
+                    project,
+                );
+                let exports = self.env.borrow().mod_vals.borrow().get(&name).cloned();
+                let exports = exports.unwrap_or_default();
+                modules::leave(&path, exports.clone());
+                exports
+            }
+        };
+        // modules loaded by a module are reachable through it, `a::b::name`
+        if let (true, 0, Some(src), Some(alias)) = (self.is_mod, self.order, &self.mod_src, alias) {
+            self.env
+                .borrow_mut()
+                .define_mod_mod(src.clone(), alias, exports);
+        }
+    }
+
+    /// runs the entry of the package in `dir`, its exports are kept under `src`
+    fn load_package(&mut self, src: &str, dir: &Path) {
+        let path = package::entry(dir);
+        let contents = match read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                raw(format!("failed to read '{}': {}", path.display(), e).as_str());
+                exit(1);
+            }
+        };
+        // imports of the package resolve from its own manifest and the shared mods/
+        let mut project = self.project.clone();
+        if dir.join(MANIFEST).is_file() {
+            project.manifest = manifest::load(dir);
+        }
+        project.workspace = project.lock_root().cloned();
+        project.root = Some(dir.to_path_buf());
+        project.file = Some(path.clone());
         interpreter_mod(
             contents.as_str(),
-            Some(name),
+            Some(src.to_string()),
             Rc::clone(&self.env),
-            self.project.clone(),
+            project,
         );
     }
 
-    fn uses(&mut self, src: &String, names: Vec<(Token, Option<Token>)>, all: bool) {
+    fn uses(&mut self, id: usize, src: &String, names: Vec<(Token, Option<Token>)>, all: bool) {
         if !self.project.side_effects {
             self.error.throw(E0x415, 0, (0, 0), vec![]);
         }
 
+        // the names take the slots after the first one in a local scope
+        let first = self.env.borrow().slot(id);
         if src.clone().contains("::") {
             self.load_std(src.trim_matches('"').to_string().clone(), names.clone());
+            if let Some(first) = first {
+                // std functions are defined by name, they move to the slots of their names
+                let env = self.env.borrow();
+                for (index, (name, alias)) in names.iter().enumerate() {
+                    let name = alias.as_ref().unwrap_or(name).lexeme.clone();
+                    let value = env.pub_vals.borrow_mut().remove(&name);
+                    if let Some(value) = value {
+                        env.define(name, Some(first + index), value);
+                    }
+                }
+            }
         } else {
+            // packages are loaded once and stay available to later imports
+            let package = package::locate(&self.project, src.trim_matches('"'));
+            if let Some(dir) = &package {
+                if !self.env.borrow().mod_vals.borrow().contains_key(src) {
+                    self.load_package(src, dir);
+                }
+            }
             let mod_vals = self.env.borrow_mut().mod_vals.borrow_mut().clone();
             let vals = match mod_vals.get(src) {
                 Some(c) => c,
@@ -921,13 +1045,20 @@ impl Interpreter {
                 }
             };
 
-            self.env.borrow_mut().mod_vals.borrow_mut().remove(src);
-
             if all {
                 for val in vals {
                     let (name, v) = val;
 
-                    if let LiteralType::Void = v.value {
+                    if let ValueKind::Private = v.kind {
+                        continue;
+                    }
+                    if let ValueKind::Mod(exports) = &v.kind {
+                        self.env
+                            .borrow_mut()
+                            .mod_vals
+                            .borrow_mut()
+                            .insert(format!("\"{}\"", name), exports.clone());
+                    } else if let LiteralType::Void = v.value {
                         if let ValueKind::Type(t) = v.kind.clone() {
                             self.env
                                 .borrow_mut()
@@ -944,14 +1075,34 @@ impl Interpreter {
                     }
                 }
             } else {
-                for (name, alias) in names {
+                for (index, (name, alias)) in names.iter().enumerate() {
                     if let Some((_, v)) = vals.iter().find(|(n, _)| n == &name.lexeme) {
                         let new_name = alias.as_ref().map_or(&name.lexeme, |t| &t.lexeme);
-                        self.env
-                            .borrow_mut()
-                            .values
-                            .borrow_mut()
-                            .insert(new_name.clone(), v.clone());
+                        match &v.kind {
+                            ValueKind::Private => {
+                                self.error.throw(
+                                    E0x419,
+                                    name.line,
+                                    name.pos,
+                                    vec![name.lexeme.clone(), src.trim_matches('"').to_string()],
+                                );
+                                exit(1);
+                            }
+                            ValueKind::Mod(exports) => {
+                                self.env
+                                    .borrow_mut()
+                                    .mod_vals
+                                    .borrow_mut()
+                                    .insert(format!("\"{}\"", new_name), exports.clone());
+                                continue;
+                            }
+                            _ => {}
+                        }
+                        self.env.borrow_mut().define(
+                            new_name.clone(),
+                            first.map(|slot| slot + index),
+                            v.clone(),
+                        );
                     }
                 }
             }
@@ -959,15 +1110,15 @@ impl Interpreter {
     }
 }
 
-pub fn run_func(func: FuncImpl, args: &[Expression], env: Rc<RefCell<Env>>) -> LiteralType {
-    let error = Error::new("", Project::new());
+/// calls the function with the arguments evaluated by the caller
+pub fn call_func(func: FuncImpl, args: Vec<LiteralType>, env: Rc<RefCell<Env>>) -> LiteralType {
+    let error = Error::new("", (*func.project).clone());
     if args.len() != func.params.len() {
         error.throw(E0x405, 0, (0, 0), vec![]);
     }
 
     let mut arg_values = vec![];
-    for (i, arg) in args.iter().enumerate() {
-        let arg_lit = arg.eval(Rc::clone(&env));
+    for (i, arg_lit) in args.into_iter().enumerate() {
         if !type_check(&func.params.iter().nth(i).unwrap().1, &arg_lit, &env) {
             error.throw(
                 E0x301,
@@ -981,7 +1132,9 @@ pub fn run_func(func: FuncImpl, args: &[Expression], env: Rc<RefCell<Env>>) -> L
         }
         arg_values.push(arg_lit);
     }
-    let func_env = func.env.borrow_mut().enclose();
+    // the body runs with the project of the file the function is defined in
+    let mut func_env = Env::enclose(&func.env);
+    func_env.project = Rc::clone(&func.project);
     let func_env = Rc::new(RefCell::new(func_env));
 
     for (i, val) in arg_values.iter().enumerate() {
@@ -1001,6 +1154,7 @@ pub fn run_func(func: FuncImpl, args: &[Expression], env: Rc<RefCell<Env>>) -> L
                 .collect();
             func_env.borrow_mut().define_func(
                 func.params[i].0.lexeme.clone(),
+                Some(i),
                 val.clone(),
                 FuncKind {
                     params,
@@ -1013,18 +1167,35 @@ pub fn run_func(func: FuncImpl, args: &[Expression], env: Rc<RefCell<Env>>) -> L
         }
     }
 
+    profiler::enter(&func.name);
+    debugger::enter(&func.name);
+    let val = run_func_body(func, func_env, env, &error);
+    debugger::exit_frame();
+    profiler::exit(FrameKind::Func);
+    val
+}
+
+/// executes function body in the prepared function environment
+fn run_func_body(
+    func: FuncImpl,
+    func_env: Rc<RefCell<Env>>,
+    env: Rc<RefCell<Env>>,
+    error: &Error,
+) -> LiteralType {
     let mut int = Interpreter::new_with_env(Rc::clone(&func_env), false, "", None, 1);
     match func.body {
+        FuncBody::Statements(body) if vm::enabled() => {
+            if let Some(v) = vm::call(&body, &func.chunk, Rc::clone(&func_env)) {
+                return v;
+            }
+        }
         FuncBody::Statements(body) => {
             for stmt in body.clone() {
                 int.interpret(vec![&stmt], 1);
-                let mut val = {
+                let val = {
                     let specs = int.specs.borrow_mut();
                     specs.get("return").cloned()
                 };
-                if let Statement::Expression { expr } = body.first().unwrap() {
-                    val = Some(expr.eval(Rc::clone(&env)));
-                }
 
                 if val.is_some() {
                     let v = val.clone().unwrap().clone();
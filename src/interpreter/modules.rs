@@ -0,0 +1,68 @@
+// modules loaded by `mod`, cached by their canonical path
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    env::current_dir,
+    path::{Path, PathBuf},
+};
+
+use super::env::ValueType;
+
+type Exports = Vec<(String, ValueType)>;
+
+#[derive(Default)]
+struct Modules {
+    /// exports of every module that finished loading
+    loaded: HashMap<PathBuf, Exports>,
+    /// modules being loaded, each one imported by the previous one
+    loading: Vec<PathBuf>,
+}
+
+thread_local! {
+    static MODULES: RefCell<Modules> = RefCell::new(Modules::default());
+}
+
+/// exports of the module, if it was loaded before
+pub fn cached(path: &Path) -> Option<Exports> {
+    MODULES.with(|m| m.borrow().loaded.get(path).cloned())
+}
+
+/// marks the module as loading, fails with the import chain if it's already loading,
+/// the `importer` starts the chain of the first module
+pub fn enter(importer: Option<&Path>, path: &Path) -> Result<(), String> {
+    MODULES.with(|m| {
+        let mut m = m.borrow_mut();
+        if m.loading.is_empty() {
+            m.loading.extend(importer.map(|i| i.to_path_buf()));
+        }
+        if m.loading.iter().any(|p| p == path) {
+            let chain: Vec<String> = m
+                .loading
+                .iter()
+                .chain([&path.to_path_buf()])
+                .map(|p| display(p))
+                .collect();
+            return Err(chain.join(" -> "));
+        }
+        m.loading.push(path.to_path_buf());
+        Ok(())
+    })
+}
+
+/// caches the exports of the module that finished loading
+pub fn leave(path: &Path, exports: Exports) {
+    MODULES.with(|m| {
+        let mut m = m.borrow_mut();
+        m.loading.retain(|p| p != path);
+        m.loaded.insert(path.to_path_buf(), exports);
+    })
+}
+
+/// the path relative to the current directory, if it's inside of it
+fn display(path: &Path) -> String {
+    let dir = current_dir().unwrap_or_default();
+    path.strip_prefix(&dir)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
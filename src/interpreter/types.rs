@@ -3,9 +3,10 @@ use crate::{
     ast::{LiteralKind, LiteralType, Token, TokenType},
     errors::raw,
 };
+use serde::{Deserialize, Serialize};
 use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum TypeKind {
     Vec {
         // Vec<type>
@@ -355,7 +356,11 @@ fn rec_to_lt(fields: Vec<(Token, TypeKind)>) -> LiteralType {
     let mut rec = vec![];
     for (k, v) in fields {
         let v = typekind_to_literaltype(v);
-        rec.push((k.lexeme, Expression::Value { id: 0, value: v }));
+        rec.push((k.lexeme, Expression::Value {
+                id: 0,
+                value: v,
+                line: 0,
+            }));
     }
     LiteralType::Record(rec)
 }
@@ -7,13 +7,24 @@ mod std;
 use cli::cli_new;
 use manifest::Project;
 mod bundler;
+mod cache;
+mod dap;
+mod debugger;
+mod dump;
 mod errors;
+mod formatter;
+mod json;
 mod manifest;
+mod optimizer;
+mod package;
+mod profiler;
+mod scaffold;
+mod vm;
+mod workspace;
 
 pub const VERSION: &str = "0.26.0";
 
 fn main() {
     let mut project = Project::new();
-    project.load();
     cli_new(&mut project);
 }
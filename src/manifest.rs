@@ -1,11 +1,22 @@
 // handles Absurd manifesto file
-use ::std::{fs::File, io::Read};
-use std::process::exit;
-use toml::{from_str, Value};
+use serde::{
+    de::{self, value::MapAccessDeserializer, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{read_dir, read_to_string, write},
+    path::{Path, PathBuf},
+    process::exit,
+};
+use toml::Spanned;
 
-use super::errors::raw;
+use super::{errors::raw, package::semver::Req};
 
-#[derive(Debug, Clone)]
+pub const MANIFEST: &str = "project.toml";
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Project {
     // # config
     pub snippet: i8,
@@ -14,6 +25,136 @@ pub struct Project {
     pub load_std: bool,
     pub log: bool,
     pub test: bool,
+    /// folds constants and drops dead code before running, set with `-O`
+    pub optimize: bool,
+    /// output file for folded stacks, if profiling is enabled
+    pub profile: Option<String>,
+    /// what executes the program, set with `--engine=<tree|vm>`
+    pub engine: Engine,
+    /// arguments passed to the script after `--`
+    pub args: Vec<String>,
+    /// the running script, `mod` paths are relative to it
+    pub file: Option<PathBuf>,
+    // # manifest
+    /// directory of the project.toml, if one was found
+    pub root: Option<PathBuf>,
+    /// directory of the workspace manifest, its absurd.lock and mods/ are shared
+    pub workspace: Option<PathBuf>,
+    pub manifest: Manifest,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub package: Option<Package>,
+    pub workspace: Option<Workspace>,
+    #[serde(default)]
+    pub config: Config,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Spanned<Dependency>>,
+    #[serde(default, rename = "dev-dependencies")]
+    pub dev_dependencies: BTreeMap<String, Spanned<Dependency>>,
+    #[serde(default)]
+    pub lints: BTreeMap<String, LintLevel>,
+    /// named shell commands
+    #[serde(default)]
+    pub scripts: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Package {
+    pub name: Spanned<String>,
+    pub version: Spanned<String>,
+    /// script executed by `absurd run` without a file
+    pub entry: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    pub license: Option<String>,
+    /// files packed by `absurd pack`, everything if it's empty
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    /// package directories, `packages/*` includes every package in `packages`
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub snippet: Option<Spanned<i8>>,
+    pub side_effects: Option<bool>,
+    pub disable_std: Option<bool>,
+    pub load_std: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dependency {
+    /// `name = "^1.0"`
+    Version(String),
+    /// `name = { path = "../lib" }`
+    Detailed(DependencyDetail),
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DependencyDetail {
+    pub version: Option<String>,
+    pub path: Option<String>,
+    pub git: Option<String>,
+    pub rev: Option<String>,
+    /// directory of a file-system registry
+    pub registry: Option<String>,
+}
+
+/// a string is a version requirement and a table holds the details, so the errors of a
+/// table, like an unknown key, point at the key instead of the whole dependency
+impl<'de> Deserialize<'de> for Dependency {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct DependencyVisitor;
+
+        impl<'de> Visitor<'de> for DependencyVisitor {
+            type Value = Dependency;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a version requirement or a table with 'version', 'path' or 'git'")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Dependency, E> {
+                Ok(Dependency::Version(v.to_string()))
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, map: M) -> Result<Dependency, M::Error> {
+                DependencyDetail::deserialize(MapAccessDeserializer::new(map))
+                    .map(Dependency::Detailed)
+            }
+        }
+
+        d.deserialize_any(DependencyVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Engine {
+    /// walks the syntax tree
+    Tree,
+    /// compiles the program to bytecode and runs it on a stack
+    Vm,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
 }
 
 impl Project {
@@ -26,53 +167,326 @@ impl Project {
             load_std: true,
             log: false,
             test: false,
+            optimize: false,
+            profile: None,
+            engine: Engine::Tree,
+            args: vec![],
+            file: None,
+            // # manifest
+            root: None,
+            workspace: None,
+            manifest: Manifest::default(),
+        }
+    }
+
+    /// finds the manifest in the `dir` or its parents and applies its config
+    pub fn load(&mut self, dir: &Path) {
+        let root = match find_root(dir) {
+            Some(r) => r,
+            None => return,
+        };
+        self.manifest = load(&root);
+        self.workspace = find_workspace(&root, &self.manifest);
+        self.root = Some(root);
+
+        let config = &self.manifest.config;
+        if let Some(snippet) = &config.snippet {
+            self.snippet = *snippet.get_ref();
+        }
+        if let Some(side_effects) = config.side_effects {
+            self.side_effects = side_effects;
+        }
+        if let Some(disable_std) = config.disable_std {
+            self.disable_std = disable_std;
+        }
+        if let Some(load_std) = config.load_std {
+            self.load_std = load_std;
         }
     }
 
-    pub fn load(&mut self) {
-        if let Ok(f) = File::open("project.toml") {
-            let mut f = f;
-            let mut contents = String::new();
-            match f.read_to_string(&mut contents) {
-                Ok(_) => {}
-                Err(_) => {
-                    raw(format!("failed to read file 'project.toml'").as_str());
+    /// directory of the absurd.lock and mods/, the workspace or the project root
+    pub fn lock_root(&self) -> Option<&PathBuf> {
+        self.workspace.as_ref().or(self.root.as_ref())
+    }
+
+    /// path of the package entry, relative to the manifest
+    pub fn entry(&self) -> Option<PathBuf> {
+        let entry = self.manifest.package.as_ref()?.entry.as_ref()?;
+        Some(match &self.root {
+            Some(root) => root.join(entry),
+            None => PathBuf::from(entry),
+        })
+    }
+}
+
+/// closest directory with a manifest, starting at the `dir`
+pub fn find_root(dir: &Path) -> Option<PathBuf> {
+    let dir = dir.canonicalize().ok()?;
+    dir.ancestors()
+        .find(|d| d.join(MANIFEST).is_file())
+        .map(|d| d.to_path_buf())
+}
+
+/// workspace of the package in `root`, the closest parent manifest listing it as a member
+fn find_workspace(root: &Path, manifest: &Manifest) -> Option<PathBuf> {
+    if manifest.workspace.is_some() {
+        return Some(root.to_path_buf());
+    }
+    let dir = root
+        .parent()?
+        .ancestors()
+        .find(|d| d.join(MANIFEST).is_file())?;
+    let workspace = load(dir).workspace?;
+    members(dir, &workspace)
+        .iter()
+        .any(|m| m == root)
+        .then(|| dir.to_path_buf())
+}
+
+/// directories of the workspace members, every member needs a manifest
+pub fn members(dir: &Path, workspace: &Workspace) -> Vec<PathBuf> {
+    let mut members = vec![];
+    for member in &workspace.members {
+        let paths = match member.strip_suffix("/*") {
+            Some(parent) => {
+                let mut dirs: Vec<PathBuf> = match read_dir(dir.join(parent)) {
+                    Ok(entries) => entries
+                        .flatten()
+                        .map(|e| e.path())
+                        .filter(|p| p.join(MANIFEST).is_file())
+                        .collect(),
+                    Err(_) => vec![],
+                };
+                dirs.sort();
+                dirs
+            }
+            None => vec![dir.join(member)],
+        };
+        for path in paths {
+            match path.canonicalize() {
+                Ok(p) if p.join(MANIFEST).is_file() => members.push(p),
+                _ => {
+                    raw(
+                        format!("workspace member '{}' has no {}", path.display(), MANIFEST)
+                            .as_str(),
+                    );
                     exit(1);
                 }
             }
-            let parsed: Value = from_str(&contents).expect("failed to parse manifest");
-            match parsed.as_table() {
-                Some(v) => {
-                    if v.contains_key("config") {
-                        let table = v.get("config").unwrap();
-                        if table.get("snippet").is_some() {
-                            self.snippet = self.get_int(table, "snippet");
-                        }
-                        if table.get("side_effects").is_some() {
-                            self.side_effects = self.get_bool(table, "side_effects");
-                        }
-                        if table.get("disable_std").is_some() {
-                            self.disable_std = self.get_bool(table, "disable_std");
-                        }
-                        if table.get("load_std").is_some() {
-                            self.load_std = self.get_bool(table, "load_std");
-                        }
+        }
+    }
+    members
+}
+
+/// reads and parses the manifest in the `dir`
+pub fn load(dir: &Path) -> Manifest {
+    let path = dir.join(MANIFEST);
+    match read_to_string(&path) {
+        Ok(src) => parse(&src, &path.display().to_string()),
+        Err(e) => {
+            raw(format!("failed to read file '{}': {}", path.display(), e).as_str());
+            exit(1);
+        }
+    }
+}
+
+/// parses and validates the manifest, errors are reported with their location
+pub fn parse(src: &str, path: &str) -> Manifest {
+    let manifest: Manifest = match toml::from_str(src) {
+        Ok(m) => m,
+        Err(e) => {
+            raw(format!("failed to parse '{}': {}", path, e).as_str());
+            exit(1);
+        }
+    };
+    if let Err((msg, offset)) = validate(&manifest) {
+        let (line, col) = position(src, offset);
+        raw(format!(
+            "invalid '{}' at line {}, column {}: {}",
+            path, line, col, msg
+        )
+        .as_str());
+        exit(1);
+    }
+    manifest
+}
+
+/// package names use letters, digits, '-' and '_'
+pub fn is_package_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn validate(manifest: &Manifest) -> Result<(), (String, usize)> {
+    if let Some(package) = &manifest.package {
+        let name = &package.name;
+        if !is_package_name(name.get_ref()) {
+            return Err((
+                format!(
+                    "invalid package name '{}', use letters, digits, '-' and '_'",
+                    name.get_ref()
+                ),
+                name.span().start,
+            ));
+        }
+        let version = &package.version;
+        if !is_version(version.get_ref()) {
+            return Err((
+                format!(
+                    "invalid version '{}', expected major.minor.patch",
+                    version.get_ref()
+                ),
+                version.span().start,
+            ));
+        }
+    }
+    if let Some(snippet) = &manifest.config.snippet {
+        if *snippet.get_ref() < -1 {
+            return Err((
+                "snippet should be -1 or a positive number".to_string(),
+                snippet.span().start,
+            ));
+        }
+    }
+    for dep in manifest
+        .dependencies
+        .values()
+        .chain(manifest.dev_dependencies.values())
+    {
+        if let Some(version) = dep.get_ref().detail().version {
+            if Req::parse(&version).is_none() {
+                return Err((
+                    format!(
+                        "invalid version requirement '{}', expected e.g. '^1.2' or '~0.3'",
+                        version
+                    ),
+                    dep.span().start,
+                ));
+            }
+        }
+        if let Dependency::Detailed(d) = dep.get_ref() {
+            if d.version.is_none() && d.path.is_none() && d.git.is_none() {
+                return Err((
+                    "dependency needs a 'version', 'path' or 'git' key".to_string(),
+                    dep.span().start,
+                ));
+            }
+            if d.registry.is_some() && (d.path.is_some() || d.git.is_some()) {
+                return Err((
+                    "'registry' can't be used with 'path' or 'git'".to_string(),
+                    dep.span().start,
+                ));
+            }
+            if d.version.is_some() && d.rev.is_some() {
+                return Err((
+                    "'version' and 'rev' can't be used together".to_string(),
+                    dep.span().start,
+                ));
+            }
+            if d.rev.is_some() && d.git.is_none() {
+                return Err((
+                    "'rev' can only be used with 'git'".to_string(),
+                    dep.span().start,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `major.minor.patch` with an optional `-pre` suffix
+pub fn is_version(version: &str) -> bool {
+    let core = version.split('-').next().unwrap_or("");
+    let parts: Vec<&str> = core.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// one-based line and column of the byte `offset`
+fn position(src: &str, offset: usize) -> (usize, usize) {
+    let before = &src[..offset.min(src.len())];
+    let line = before.matches('\n').count() + 1;
+    let col = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, col)
+}
+
+impl Dependency {
+    pub fn detail(&self) -> DependencyDetail {
+        match self {
+            Dependency::Version(v) => DependencyDetail {
+                version: Some(v.clone()),
+                ..Default::default()
+            },
+            Dependency::Detailed(d) => d.clone(),
+        }
+    }
+
+    fn to_value(&self) -> toml_edit::Value {
+        match self {
+            Dependency::Version(v) => v.as_str().into(),
+            Dependency::Detailed(d) => {
+                let mut table = toml_edit::InlineTable::new();
+                for (key, value) in [
+                    ("version", &d.version),
+                    ("path", &d.path),
+                    ("git", &d.git),
+                    ("rev", &d.rev),
+                    ("registry", &d.registry),
+                ] {
+                    if let Some(value) = value {
+                        table.insert(key, value.as_str().into());
                     }
                 }
-                None => {
-                    raw("failed to parse manifest");
-                    exit(1);
-                }
+                toml_edit::Value::InlineTable(table)
             }
         }
     }
+}
 
-    
-    fn get_bool(&self, table: &Value, name: &str) -> bool {
-        table.get(name).unwrap().as_bool().unwrap()
-    }
+/// adds or replaces a dependency in the manifest of the `root`, keeping its formatting
+pub fn set_dependency(root: &Path, table: &str, name: &str, dep: &Dependency) {
+    edit(root, |doc| {
+        let deps = doc
+            .entry(table)
+            .or_insert(toml_edit::table())
+            .as_table_like_mut();
+        match deps {
+            Some(deps) => {
+                deps.insert(name, toml_edit::Item::Value(dep.to_value()));
+            }
+            None => raw(format!("[{}] in {} should be a table", table, MANIFEST).as_str()),
+        }
+    });
+}
 
-    fn get_int(&self, table: &Value, name: &str) -> i8 {
-        table.get(name).unwrap().as_integer().unwrap() as i8
+/// removes a dependency from the manifest of the `root`, returns false if it wasn't there
+pub fn remove_dependency(root: &Path, table: &str, name: &str) -> bool {
+    edit(root, |doc| {
+        doc.get_mut(table)
+            .and_then(|t| t.as_table_like_mut())
+            .and_then(|t| t.remove(name))
+            .is_some()
+    })
+}
+
+fn edit<T>(root: &Path, f: impl FnOnce(&mut toml_edit::DocumentMut) -> T) -> T {
+    let path = root.join(MANIFEST);
+    let src = read_to_string(&path).unwrap_or_default();
+    let mut doc: toml_edit::DocumentMut = match src.parse() {
+        Ok(d) => d,
+        Err(e) => {
+            raw(format!("failed to parse '{}': {}", path.display(), e).as_str());
+            exit(1);
+        }
+    };
+    let result = f(&mut doc);
+    if let Err(e) = write(&path, doc.to_string()) {
+        raw(format!("failed to write '{}': {}", path.display(), e).as_str());
+        exit(1);
     }
+    result
 }
@@ -0,0 +1,484 @@
+// optimization pass enabled with `-O`, runs over resolved statements before they're interpreted
+// or compiled: folds constant operators, inlines immutable literals and drops dead code
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::{FuncBody, LiteralType, Statement, Token},
+    interpreter::expr::{binary, unary, Expression},
+    parser::reserve_ids,
+};
+
+/// optimizes a script, or a module whose top level skips `if`s and loops
+pub fn optimize(stmts: Vec<Statement>, is_mod: bool) -> Vec<Statement> {
+    let mut optimizer = Optimizer {
+        scopes: vec![],
+        is_mod,
+    };
+    optimizer.block(stmts)
+}
+
+struct Optimizer {
+    /// names declared in the enclosing scopes, with the values of immutable literals
+    scopes: Vec<HashMap<String, Option<LiteralType>>>,
+    /// the top level of a module, its private variables are never defined
+    is_mod: bool,
+}
+
+impl Optimizer {
+    /// the literal the name is bound to, if it can't change
+    fn lookup(&self, name: &str) -> Option<LiteralType> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(value) = scope.get(name) {
+                return value.clone();
+            }
+            // names brought in by `use * from` are only known at runtime
+            if scope.contains_key("*") {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn declare(&mut self, name: &Token, value: Option<LiteralType>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), value);
+        }
+    }
+
+    fn block(&mut self, stmts: Vec<Statement>) -> Vec<Statement> {
+        self.scopes.push(HashMap::new());
+        let stmts = self.stmts(stmts);
+        self.scopes.pop();
+        stmts
+    }
+
+    /// statements of the current scope, the ones after a `return` or `break` never run
+    fn stmts(&mut self, stmts: Vec<Statement>) -> Vec<Statement> {
+        let twice = redeclared(&stmts);
+        let glob = stmts
+            .iter()
+            .any(|s| matches!(s, Statement::Use { all: true, .. }));
+        if let (true, Some(scope)) = (glob, self.scopes.last_mut()) {
+            scope.insert("*".to_string(), None);
+        }
+        let mut out = vec![];
+        for stmt in stmts {
+            let ends = matches!(stmt, Statement::Return { .. } | Statement::Break { .. });
+            out.extend(self.stmt(stmt, &twice));
+            if ends {
+                break;
+            }
+        }
+        out
+    }
+
+    fn stmt(&mut self, stmt: Statement, twice: &HashSet<String>) -> Option<Statement> {
+        let stmt = match stmt {
+            Statement::Expression { expr } => Statement::Expression {
+                expr: self.expr(expr),
+            },
+            Statement::Block { stmts } => Statement::Block {
+                stmts: self.block(stmts),
+            },
+            Statement::Var {
+                id,
+                names,
+                destruct,
+                value_type,
+                value,
+                is_mut,
+                is_pub,
+                pub_names,
+                is_func,
+            } => {
+                let value = value.map(|v| self.expr(v));
+                let literal = match (&value, names.as_slice()) {
+                    (Some(Expression::Value { value, .. }), [name])
+                        if !is_mut
+                            && !is_func
+                            && !self.is_mod
+                            && destruct.is_none()
+                            && !twice.contains(&name.lexeme) =>
+                    {
+                        constant(value)
+                    }
+                    _ => None,
+                };
+                for name in &names {
+                    self.declare(name, literal.clone());
+                }
+                Statement::Var {
+                    id,
+                    names,
+                    destruct,
+                    value_type,
+                    value,
+                    is_mut,
+                    is_pub,
+                    pub_names,
+                    is_func,
+                }
+            }
+            Statement::Func {
+                id,
+                name,
+                value_type,
+                body,
+                params,
+                is_async,
+                is_pub,
+            } => {
+                self.declare(&name, None);
+                let body = self.func_body(body, &params);
+                Statement::Func {
+                    id,
+                    name,
+                    value_type,
+                    body,
+                    params,
+                    is_async,
+                    is_pub,
+                }
+            }
+            Statement::If {
+                cond,
+                body,
+                else_if_branches,
+                else_branch,
+            } => {
+                let cond = self.expr(cond);
+                let body = self.block(body);
+                let else_if_branches: Vec<_> = else_if_branches
+                    .into_iter()
+                    .map(|(cond, body)| (self.expr(cond), self.block(body)))
+                    .collect();
+                let else_branch = else_branch.map(|body| self.block(body));
+                if self.is_mod {
+                    Statement::If {
+                        cond,
+                        body,
+                        else_if_branches,
+                        else_branch,
+                    }
+                } else {
+                    return branches(cond, body, else_if_branches, else_branch);
+                }
+            }
+            Statement::Return { expr } => Statement::Return {
+                expr: self.expr(expr),
+            },
+            Statement::For {
+                iterator,
+                index,
+                expr,
+                body,
+            } => {
+                let expr = self.expr(expr);
+                self.scopes.push(HashMap::new());
+                self.declare(&iterator, None);
+                if let Some(index) = &index {
+                    self.declare(index, None);
+                }
+                let body = self.block(body);
+                self.scopes.pop();
+                Statement::For {
+                    iterator,
+                    index,
+                    expr,
+                    body,
+                }
+            }
+            Statement::While { cond, body } => {
+                let cond = self.expr(cond);
+                if let Expression::Value { value, .. } = &cond {
+                    if !value.is_truthy() {
+                        return None;
+                    }
+                }
+                Statement::While {
+                    cond,
+                    body: self.block(body),
+                }
+            }
+            Statement::Loop { iter, body } => Statement::Loop {
+                iter,
+                body: self.block(body),
+            },
+            Statement::Match {
+                cond,
+                cases,
+                def_case,
+            } => Statement::Match {
+                cond: self.expr(cond),
+                cases: cases
+                    .into_iter()
+                    .map(|(pattern, body)| (pattern, self.case(body)))
+                    .collect(),
+                def_case: self.case(def_case),
+            },
+            Statement::Record { ref name, .. }
+            | Statement::Enum { ref name, .. }
+            | Statement::Type { ref name, .. } => {
+                self.declare(name, None);
+                stmt
+            }
+            Statement::Use { ref names, .. } => {
+                for (name, alias) in names {
+                    self.declare(alias.as_ref().unwrap_or(name), None);
+                }
+                stmt
+            }
+            Statement::Sh { .. } | Statement::Mod { .. } | Statement::Break { .. } => stmt,
+        };
+        Some(stmt)
+    }
+
+    /// the body of a function, its params shadow the enclosing names
+    fn func_body(&mut self, body: FuncBody, params: &[(Token, Token)]) -> FuncBody {
+        let is_mod = std::mem::replace(&mut self.is_mod, false);
+        self.scopes.push(HashMap::new());
+        for (name, _) in params {
+            self.declare(name, None);
+        }
+        let body = match body {
+            FuncBody::Statements(stmts) => FuncBody::Statements(self.block(stmts)),
+            FuncBody::Expression(expr) => FuncBody::Expression(Box::new(self.expr(*expr))),
+        };
+        self.scopes.pop();
+        self.is_mod = is_mod;
+        body
+    }
+
+    fn case(&mut self, body: FuncBody) -> FuncBody {
+        match body {
+            FuncBody::Statements(stmts) => FuncBody::Statements(self.block(stmts)),
+            FuncBody::Expression(expr) => FuncBody::Expression(Box::new(self.expr(*expr))),
+        }
+    }
+
+    fn expr(&mut self, expr: Expression) -> Expression {
+        match expr {
+            Expression::Var { id, name } => match self.lookup(&name.lexeme) {
+                Some(value) => Expression::Value {
+                    id,
+                    value,
+                    line: name.line,
+                },
+                None => Expression::Var { id, name },
+            },
+            Expression::Binary {
+                id,
+                left,
+                operator,
+                right,
+            } => match (self.expr(*left), self.expr(*right)) {
+                (
+                    Expression::Value {
+                        value: l, line, ..
+                    },
+                    Expression::Value { value: r, .. },
+                ) => Expression::Value {
+                    id,
+                    value: binary(l, &operator.token, r),
+                    line: if line == 0 { operator.line } else { line },
+                },
+                (left, right) => Expression::Binary {
+                    id,
+                    left: Box::new(left),
+                    operator,
+                    right: Box::new(right),
+                },
+            },
+            Expression::Unary { id, left, operator } => match self.expr(*left) {
+                Expression::Value { value, line, .. } => Expression::Value {
+                    id,
+                    value: unary(&operator.token, value),
+                    line: if operator.line == 0 { line } else { operator.line },
+                },
+                left => Expression::Unary {
+                    id,
+                    left: Box::new(left),
+                    operator,
+                },
+            },
+            Expression::Grouping { id, expression } => match self.expr(*expression) {
+                value @ Expression::Value { .. } => value,
+                expression => Expression::Grouping {
+                    id,
+                    expression: Box::new(expression),
+                },
+            },
+            Expression::If {
+                id,
+                cond,
+                body,
+                else_branch,
+            } => match self.expr(*cond) {
+                Expression::Value { value, .. } if value.is_truthy() => self.expr(*body),
+                Expression::Value { line, .. } => match else_branch {
+                    Some(else_branch) => self.expr(*else_branch),
+                    None => Expression::Value {
+                        id,
+                        value: LiteralType::Null,
+                        line,
+                    },
+                },
+                cond => Expression::If {
+                    id,
+                    cond: Box::new(cond),
+                    body: Box::new(self.expr(*body)),
+                    else_branch: else_branch.map(|e| Box::new(self.expr(*e))),
+                },
+            },
+            Expression::Assign {
+                id,
+                name,
+                value,
+                kind,
+            } => Expression::Assign {
+                id,
+                name,
+                value: Box::new(self.expr(*value)),
+                kind,
+            },
+            Expression::Vec { id, items } => Expression::Vec {
+                id,
+                items: items.into_iter().map(|i| self.expr(i)).collect(),
+            },
+            Expression::Tuple { id, items } => Expression::Tuple {
+                id,
+                items: items.into_iter().map(|i| self.expr(i)).collect(),
+            },
+            Expression::Interpolation { id, parts } => Expression::Interpolation {
+                id,
+                parts: parts
+                    .into_iter()
+                    .map(|(part, spec)| (self.expr(part), spec))
+                    .collect(),
+            },
+            Expression::Call {
+                id,
+                name,
+                args,
+                call_type,
+            } => Expression::Call {
+                id,
+                // calls of calls, `make(1 + 1)(2)`, names are left for the runtime to look up
+                name: match *name {
+                    call @ Expression::Call { .. } => Box::new(self.expr(call)),
+                    name => Box::new(name),
+                },
+                args: args.into_iter().map(|a| self.arg(a)).collect(),
+                call_type,
+            },
+            Expression::Func {
+                id,
+                name,
+                value_type,
+                body,
+                params,
+                is_async,
+                is_pub,
+            } => {
+                let body = self.func_body(body, &params);
+                Expression::Func {
+                    id,
+                    name,
+                    value_type,
+                    body,
+                    params,
+                    is_async,
+                    is_pub,
+                }
+            }
+            Expression::Await { id, expr } => Expression::Await {
+                id,
+                expr: Box::new(self.expr(*expr)),
+            },
+            // record fields stay as written, they're evaluated when they're read
+            Expression::Value { .. }
+            | Expression::Record { .. }
+            | Expression::Path { .. }
+            | Expression::Range { .. } => expr,
+        }
+    }
+
+    /// an argument of a call, a folded one stays wrapped: calling a vec, tuple or record
+    /// indexes it only if the first argument is written as a literal, `t(two)` and `t(1 + 1)`
+    /// are null and have to stay null
+    fn arg(&mut self, arg: Expression) -> Expression {
+        if let Expression::Value { .. } = arg {
+            return arg;
+        }
+        match self.expr(arg) {
+            value @ Expression::Value { .. } => Expression::Grouping {
+                id: reserve_ids(1),
+                expression: Box::new(value),
+            },
+            arg => arg,
+        }
+    }
+}
+
+/// the literals worth inlining, the rest are shared by reference at runtime
+fn constant(value: &LiteralType) -> Option<LiteralType> {
+    match value {
+        LiteralType::Number(_)
+        | LiteralType::String(_)
+        | LiteralType::Char(_)
+        | LiteralType::Boolean(_) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// the branches of an `if` that can still run, a constant condition decides it at once
+fn branches(
+    cond: Expression,
+    body: Vec<Statement>,
+    else_if_branches: Vec<(Expression, Vec<Statement>)>,
+    else_branch: Option<Vec<Statement>>,
+) -> Option<Statement> {
+    let mut kept: Vec<(Expression, Vec<Statement>)> = vec![];
+    let mut else_branch = else_branch;
+    for (cond, body) in [(cond, body)].into_iter().chain(else_if_branches) {
+        match &cond {
+            Expression::Value { value, .. } if value.is_truthy() => {
+                else_branch = Some(body);
+                break;
+            }
+            Expression::Value { .. } => {}
+            _ => kept.push((cond, body)),
+        }
+    }
+    if kept.is_empty() {
+        return else_branch.map(|stmts| Statement::Block { stmts });
+    }
+    let (cond, body) = kept.remove(0);
+    Some(Statement::If {
+        cond,
+        body,
+        else_if_branches: kept,
+        else_branch,
+    })
+}
+
+/// names declared more than once in a scope, closures could see either value
+fn redeclared(stmts: &[Statement]) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    stmts
+        .iter()
+        .flat_map(|stmt| match stmt {
+            Statement::Var { names, .. } => names.iter().map(|n| n.lexeme.clone()).collect(),
+            Statement::Func { name, .. }
+            | Statement::Enum { name, .. }
+            | Statement::Record { name, .. }
+            | Statement::Type { name, .. } => vec![name.lexeme.clone()],
+            Statement::Use { names, .. } => names
+                .iter()
+                .map(|(name, alias)| alias.as_ref().unwrap_or(name).lexeme.clone())
+                .collect(),
+            _ => vec![],
+        })
+        .filter(|name| !seen.insert(name.clone()))
+        .collect()
+}
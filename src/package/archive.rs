@@ -0,0 +1,281 @@
+// package archives, `absurd pack` writes a `.tar` of the package with a `CHECKSUMS` manifest
+use coloredpp::Colorize;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+use super::{remove_dir, MODS};
+use crate::{
+    errors::raw,
+    manifest::{Project, MANIFEST},
+};
+
+/// directory of the archives, next to the manifest
+pub const DIST: &str = "dist";
+/// `sha256:<hex>  <path>` of every other file in the archive
+pub const CHECKSUMS: &str = "CHECKSUMS";
+const BLOCK: usize = 512;
+
+/// an archive written by `absurd pack`
+pub struct Packed {
+    pub name: String,
+    pub version: String,
+    pub path: PathBuf,
+    /// `sha256:<hex>` of the archive
+    pub checksum: String,
+}
+
+/// `absurd pack`, archives the files of the package into `dist/<name>-<version>.tar`
+pub fn pack(project: &Project) -> Packed {
+    let (root, package) = match (&project.root, &project.manifest.package) {
+        (Some(r), Some(p)) => (r, p),
+        _ => {
+            raw(format!("no [package] found in {}, nothing to pack", MANIFEST).as_str());
+            exit(1);
+        }
+    };
+    let (name, version) = (package.name.get_ref(), package.version.get_ref());
+    let files = package_files(root, &package.include, &package.exclude);
+
+    let mut entries = vec![];
+    let mut checksums = String::new();
+    for (rel, path) in &files {
+        let contents = match fs::read(path) {
+            Ok(c) => c,
+            Err(e) => {
+                raw(format!("failed to read '{}': {}", path.display(), e).as_str());
+                exit(1);
+            }
+        };
+        checksums.push_str(format!("{}  {}\n", sha256(&contents), rel).as_str());
+        entries.push((rel.clone(), contents));
+    }
+    entries.push((CHECKSUMS.to_string(), checksums.into_bytes()));
+    let tar = write_tar(&entries);
+
+    let dist = root.join(DIST);
+    let path = dist.join(format!("{}-{}.tar", name, version));
+    if let Err(e) = fs::create_dir_all(&dist).and_then(|_| fs::write(&path, &tar)) {
+        raw(format!("failed to write '{}': {}", path.display(), e).as_str());
+        exit(1);
+    }
+    let packed = Packed {
+        name: name.clone(),
+        version: version.clone(),
+        path,
+        checksum: sha256(&tar),
+    };
+    println!(
+        "{}",
+        format!(
+            "packed {} {} ({} files) into {}",
+            name,
+            version,
+            files.len(),
+            packed
+                .path
+                .strip_prefix(root)
+                .unwrap_or(&packed.path)
+                .display()
+        )
+        .green()
+    );
+    println!("{}", packed.checksum);
+    packed
+}
+
+/// `sha256:<hex>` of the `bytes`
+pub fn sha256(bytes: &[u8]) -> String {
+    let hex: String = Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256:{}", hex)
+}
+
+/// files of the package matching the `include` patterns, if there are any, and none of the `exclude` ones,
+/// hidden files, `mods/` and `dist/` are always left out and the manifest is always packed
+fn package_files(root: &Path, include: &[String], exclude: &[String]) -> Vec<(String, PathBuf)> {
+    let mut files = vec![];
+    walk(root, root, &mut files);
+    files.retain(|(rel, _)| {
+        rel == MANIFEST
+            || ((include.is_empty() || include.iter().any(|p| matches(p, rel)))
+                && !exclude.iter().any(|p| matches(p, rel)))
+    });
+    files.sort();
+    files
+}
+
+fn walk(base: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let top = dir == base;
+        if name.starts_with('.') || (top && (name == MODS || name == DIST)) {
+            continue;
+        }
+        if path.is_dir() {
+            walk(base, &path, files);
+        } else {
+            let rel: Vec<String> = path
+                .strip_prefix(base)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            files.push((rel.join("/"), path));
+        }
+    }
+}
+
+/// `*` and `?` match within a path segment, `**` matches any number of segments,
+/// a pattern matching a directory matches everything inside it
+fn matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.trim_matches('/').split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    (1..=path.len()).any(|n| segments(&pattern, &path[..n]))
+}
+
+fn segments(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            segments(&pattern[1..], path) || (!path.is_empty() && segments(pattern, &path[1..]))
+        }
+        (Some(p), Some(s)) => {
+            glob(p.as_bytes(), s.as_bytes()) && segments(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+fn glob(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            glob(&pattern[1..], name) || (!name.is_empty() && glob(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob(&pattern[1..], &name[1..]),
+        (Some(p), Some(c)) => p == c && glob(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+/// extracts the archive into the `dir` and checks every file against its `CHECKSUMS`
+pub fn unpack(tar: &[u8], dir: &Path) -> Result<(), String> {
+    let entries = read_tar(tar)?;
+    let checksums = match entries.iter().find(|(name, _)| name == CHECKSUMS) {
+        Some((_, c)) => String::from_utf8_lossy(c).to_string(),
+        None => return Err(format!("the archive has no {}", CHECKSUMS)),
+    };
+    let listed: Vec<(&str, &str)> = checksums
+        .lines()
+        .filter_map(|l| l.split_once("  "))
+        .collect();
+    if listed.len() + 1 != entries.len() {
+        return Err(format!("{} doesn't list every file", CHECKSUMS));
+    }
+    remove_dir(dir);
+    for (name, contents) in &entries {
+        if name == CHECKSUMS {
+            continue;
+        }
+        match listed.iter().find(|(_, path)| path == name) {
+            Some((sum, _)) if *sum == sha256(contents) => {}
+            _ => return Err(format!("checksum mismatch for '{}'", name)),
+        }
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, contents).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// ustar archive of regular files, with fixed owners and times so packing is reproducible
+fn write_tar(entries: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let mut tar = vec![];
+    for (name, contents) in entries {
+        let mut header = [0u8; BLOCK];
+        // names over 100 bytes are split into the prefix and the name fields
+        let (prefix, name) = match name.len() > 100 {
+            true => match name[..name.len().min(156)].rfind('/') {
+                Some(i) if name.len() - i - 1 <= 100 => (&name[..i], &name[i + 1..]),
+                _ => {
+                    raw(format!("path '{}' is too long for the archive", name).as_str());
+                    exit(1);
+                }
+            },
+            false => ("", name.as_str()),
+        };
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        field(&mut header[100..108], 0o644);
+        field(&mut header[108..116], 0);
+        field(&mut header[116..124], 0);
+        field(&mut header[124..136], contents.len() as u64);
+        field(&mut header[136..148], 0);
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+        // the checksum is computed with its own field set to spaces
+        header[148..156].fill(b' ');
+        let sum: u64 = header.iter().map(|b| *b as u64).sum();
+        field(&mut header[148..155], sum);
+        tar.extend_from_slice(&header);
+        tar.extend_from_slice(contents);
+        tar.resize(tar.len().div_ceil(BLOCK) * BLOCK, 0);
+    }
+    tar.resize(tar.len() + 2 * BLOCK, 0);
+    tar
+}
+
+/// zero padded octal number followed by a NUL
+fn field(buf: &mut [u8], value: u64) {
+    let text = format!("{:0width$o}\0", value, width = buf.len() - 1);
+    buf.copy_from_slice(text.as_bytes());
+}
+
+fn read_tar(tar: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let mut entries = vec![];
+    let mut offset = 0;
+    while offset + BLOCK <= tar.len() {
+        let header = &tar[offset..offset + BLOCK];
+        if header.iter().all(|b| *b == 0) {
+            break;
+        }
+        let text = |range: std::ops::Range<usize>| {
+            let bytes = &header[range];
+            let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end]).to_string()
+        };
+        let size = u64::from_str_radix(text(124..136).trim(), 8)
+            .map_err(|_| "invalid archive header".to_string())? as usize;
+        let (name, prefix) = (text(0..100), text(345..500));
+        let name = match prefix.is_empty() {
+            true => name,
+            false => format!("{}/{}", prefix, name),
+        };
+        let start = offset + BLOCK;
+        if start + size > tar.len() {
+            return Err("the archive is truncated".to_string());
+        }
+        if name.starts_with('/') || name.split('/').any(|s| s == "..") {
+            return Err(format!("invalid path '{}' in the archive", name));
+        }
+        if matches!(header[156], b'0' | 0) {
+            entries.push((name, tar[start..start + size].to_vec()));
+        }
+        offset = start + size.div_ceil(BLOCK) * BLOCK;
+    }
+    Ok(entries)
+}
@@ -0,0 +1,93 @@
+// absurd.lock, pins every installed dependency to an exact commit and content hash
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read_to_string, write},
+    path::Path,
+    process::exit,
+};
+
+use crate::errors::raw;
+
+pub const LOCKFILE: &str = "absurd.lock";
+const HEADER: &str = "# generated by absurd, do not edit by hand\n";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Lock {
+    pub version: u32,
+    #[serde(default, rename = "package", skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<Locked>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Locked {
+    pub name: String,
+    /// version picked from the git tags or the registry, if the dependency has a requirement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// `git+<url>` or `registry+<dir>`
+    pub source: String,
+    pub commit: Option<String>,
+    /// `sha256:<hex>` of the installed files
+    pub hash: String,
+}
+
+impl Lock {
+    /// reads the lockfile of the `root`, empty if it doesn't exist
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(LOCKFILE);
+        let src = match read_to_string(&path) {
+            Ok(s) => s,
+            Err(_) => return Lock::default(),
+        };
+        match toml::from_str(&src) {
+            Ok(l) => l,
+            Err(e) => {
+                raw(format!("failed to parse '{}': {}", path.display(), e).as_str());
+                exit(1);
+            }
+        }
+    }
+
+    pub fn save(&mut self, root: &Path) {
+        self.version = 1;
+        self.packages.sort_by(|a, b| a.name.cmp(&b.name));
+        let path = root.join(LOCKFILE);
+        let src = match toml::to_string(self) {
+            Ok(s) => s,
+            Err(e) => {
+                raw(format!("failed to serialize '{}': {}", path.display(), e).as_str());
+                exit(1);
+            }
+        };
+        if let Err(e) = write(&path, format!("{}{}", HEADER, src)) {
+            raw(format!("failed to write '{}': {}", path.display(), e).as_str());
+            exit(1);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Locked> {
+        self.packages.iter().find(|p| p.name == name)
+    }
+}
+
+impl Locked {
+    /// git url of the source
+    pub fn url(&self) -> &str {
+        let url = self.source.trim_start_matches("git+");
+        url.split("?rev=").next().unwrap_or(url)
+    }
+
+    /// directory of the registry source
+    pub fn registry(&self) -> Option<&Path> {
+        self.source.strip_prefix("registry+").map(Path::new)
+    }
+
+    /// picked version, or the short commit for dependencies without a requirement
+    pub fn label(&self) -> String {
+        match (&self.version, &self.commit) {
+            (Some(version), _) => version.clone(),
+            (None, Some(commit)) => format!("#{}", &commit[..commit.len().min(7)]),
+            (None, None) => "?".to_string(),
+        }
+    }
+}
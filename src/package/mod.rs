@@ -0,0 +1,497 @@
+// package manager, handles `absurd add`, `absurd remove` and `absurd install`
+pub mod archive;
+pub mod lock;
+pub mod registry;
+pub mod report;
+pub mod resolve;
+pub mod semver;
+
+use coloredpp::Colorize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    process::{exit, Command, Stdio},
+};
+
+use crate::{
+    cache::CACHE,
+    errors::raw,
+    manifest::{self, Dependency, DependencyDetail, Manifest, Project, MANIFEST},
+};
+use lock::{Lock, Locked};
+use registry::{Index, REGISTRY_ENV};
+use semver::Req;
+
+/// directory of the installed dependencies, next to the manifest
+pub const MODS: &str = "mods";
+
+/// a dependency and the package requiring it: `(dependent, name, dependency)`
+pub type Requirement = (String, String, DependencyDetail);
+
+/// directory of the lockfile and `mods/`
+fn root(project: &Project) -> PathBuf {
+    match project.lock_root() {
+        Some(r) => r.clone(),
+        None => {
+            raw(format!("no {} found, create one with `absurd init`", MANIFEST).as_str());
+            exit(1);
+        }
+    }
+}
+
+/// `absurd add <owner>/<repo>|<git url>|<path>|<name>[@<req>] [name] [--rev <rev>] [--registry <dir>]`
+pub fn add(
+    project: &Project,
+    spec: &str,
+    alias: Option<&str>,
+    rev: Option<&str>,
+    registry: Option<&str>,
+) {
+    let root = root(project);
+    let dir = project.root.clone().unwrap_or(root.clone());
+    let (name, mut detail) = parse_spec(&dir, spec, alias, rev, registry);
+    let resolved = absolute(&dir, &name, &detail);
+    // without a requirement the latest version is used, if there's one
+    if detail.version.is_none() && detail.rev.is_none() {
+        let latest = match (&resolved.git, &resolved.registry) {
+            (Some(git), _) => resolve::tags(git).last().map(|(v, _)| v.clone()),
+            (None, Some(registry)) => match Index::load(Path::new(registry)).versions(&name).last()
+            {
+                Some((v, _)) => Some(v.clone()),
+                None => {
+                    raw(format!("'{}' isn't published to '{}'", name, registry).as_str());
+                    exit(1);
+                }
+            },
+            _ => None,
+        };
+        if let Some(version) = latest {
+            detail.version = Some(format!("^{}", version));
+        }
+    }
+    let from = package_name(&dir, &project.manifest);
+    let mut deps = dependencies(project);
+    deps.retain(|(f, n, _)| !(*f == from && *n == name));
+    deps.push((
+        from,
+        name.clone(),
+        DependencyDetail {
+            version: detail.version.clone(),
+            ..resolved
+        },
+    ));
+    let lock = install_deps(&root, &deps);
+    // registry dependencies without a directory are written as `name = "<req>"`
+    let dep = match &detail {
+        DependencyDetail {
+            version: Some(version),
+            path: None,
+            git: None,
+            rev: None,
+            registry: None,
+        } => Dependency::Version(version.clone()),
+        _ => Dependency::Detailed(detail),
+    };
+    manifest::set_dependency(&dir, "dependencies", &name, &dep);
+    match lock.get(&name).and_then(|l| l.version.as_ref()) {
+        Some(version) => println!("{}", format!("added {} {}", name, version).green()),
+        None => println!("{}", format!("added {}", name).green()),
+    }
+}
+
+/// `absurd remove <name>`
+pub fn remove(project: &Project, name: &str) {
+    let root = root(project);
+    let dir = project.root.clone().unwrap_or(root.clone());
+    if !manifest::remove_dependency(&dir, "dependencies", name)
+        && !manifest::remove_dependency(&dir, "dev-dependencies", name)
+    {
+        raw(format!("'{}' isn't a dependency of the project", name).as_str());
+        exit(1);
+    }
+    let from = package_name(&dir, &project.manifest);
+    let mut deps = dependencies(project);
+    deps.retain(|(f, n, _)| !(*f == from && n == name));
+    install_deps(&root, &deps);
+    println!("{}", format!("removed {}", name).green());
+}
+
+/// `absurd install`, restores `mods/` from the lockfile and locks new dependencies
+pub fn install(project: &Project) {
+    let root = root(project);
+    let lock = install_deps(&root, &dependencies(project));
+    let count = match lock.packages.len() {
+        1 => "1 package".to_string(),
+        n => format!("{} packages", n),
+    };
+    println!("{}", format!("installed {}", count).green());
+}
+
+/// manifests sharing the lockfile, the workspace members or the project itself
+pub fn manifests(project: &Project) -> Vec<(PathBuf, Manifest)> {
+    let (workspace, root) = match (&project.workspace, &project.root) {
+        (Some(w), _) => (w, w),
+        (None, Some(r)) => return vec![(r.clone(), project.manifest.clone())],
+        (None, None) => return vec![],
+    };
+    let manifest = match project.root.as_ref() == Some(workspace) {
+        true => project.manifest.clone(),
+        false => manifest::load(workspace),
+    };
+    let mut manifests = vec![];
+    if let Some(w) = &manifest.workspace {
+        for member in manifest::members(workspace, w) {
+            if member != *root {
+                let m = manifest::load(&member);
+                manifests.push((member, m));
+            }
+        }
+    }
+    // the workspace manifest can be a package too
+    if manifest.package.is_some() {
+        manifests.insert(0, (root.clone(), manifest));
+    }
+    manifests
+}
+
+/// name of the package in the `dir`, used as the dependent of its dependencies
+fn package_name(dir: &Path, manifest: &Manifest) -> String {
+    match &manifest.package {
+        Some(p) => p.name.get_ref().clone(),
+        None => dir.file_name().map_or("the project".to_string(), |n| {
+            n.to_string_lossy().to_string()
+        }),
+    }
+}
+
+/// dependencies of the manifests and of the path dependencies they reach,
+/// dev-dependencies are only used from the manifests themselves
+fn dependencies(project: &Project) -> Vec<Requirement> {
+    let mut queue: Vec<(PathBuf, Manifest, bool)> = manifests(project)
+        .into_iter()
+        .map(|(dir, m)| (dir, m, true))
+        .collect();
+    let mut seen: HashSet<PathBuf> = queue.iter().map(|(dir, _, _)| dir.clone()).collect();
+    let mut deps = vec![];
+    let mut i = 0;
+    while i < queue.len() {
+        let (dir, manifest, top) = queue[i].clone();
+        let from = package_name(&dir, &manifest);
+        let dev = match top {
+            true => manifest.dev_dependencies.iter().collect(),
+            false => vec![],
+        };
+        for (name, dep) in manifest.dependencies.iter().chain(dev) {
+            let detail = absolute(&dir, name, &dep.get_ref().detail());
+            if let Some(path) = &detail.path {
+                let path = PathBuf::from(path);
+                if path.join(MANIFEST).is_file() && seen.insert(path.clone()) {
+                    let m = manifest::load(&path);
+                    queue.push((path, m, false));
+                }
+            }
+            deps.push((from.clone(), name.clone(), detail));
+        }
+        i += 1;
+    }
+    deps
+}
+
+/// the dependency with its path and registry relative to the `dir` made absolute,
+/// dependencies with only a version come from the registry of `ABSURD_REGISTRY`
+fn absolute(dir: &Path, name: &str, detail: &DependencyDetail) -> DependencyDetail {
+    let mut detail = detail.clone();
+    if let Some(path) = &detail.path {
+        match dir.join(path).canonicalize() {
+            Ok(p) => detail.path = Some(p.display().to_string()),
+            Err(_) => {
+                raw(format!("path of '{}' doesn't exist: {}", name, path).as_str());
+                exit(1);
+            }
+        }
+    }
+    if detail.path.is_none() && detail.git.is_none() && detail.registry.is_none() {
+        detail.registry = registry::directory(None).map(|r| r.display().to_string());
+    }
+    if let Some(registry) = &detail.registry {
+        match dir.join(registry).canonicalize() {
+            Ok(p) => detail.registry = Some(p.display().to_string()),
+            Err(_) => {
+                raw(format!("registry of '{}' doesn't exist: {}", name, registry).as_str());
+                exit(1);
+            }
+        }
+    }
+    detail
+}
+
+/// resolves the `deps`, saves the lockfile and removes what isn't locked from `mods/`
+fn install_deps(root: &Path, deps: &[Requirement]) -> Lock {
+    let mut lock = resolve::resolve(root, deps, &Lock::load(root));
+    if let Ok(entries) = fs::read_dir(root.join(MODS)) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if lock.get(&name).is_none() {
+                remove_dir(&entry.path());
+            }
+        }
+    }
+    lock.save(root);
+    lock
+}
+
+/// directory of the dependency `name`, used to resolve `use ... from name;`
+pub fn locate(project: &Project, name: &str) -> Option<PathBuf> {
+    let root = project.root.as_ref()?;
+    let mods = project.lock_root()?.join(MODS).join(name);
+    let manifest = &project.manifest;
+    let dep = manifest
+        .dependencies
+        .get(name)
+        .or_else(|| manifest.dev_dependencies.get(name));
+    match dep.and_then(|d| d.get_ref().detail().path) {
+        Some(path) => Some(root.join(path)),
+        // dependencies of packages are only in mods/
+        None => Some(mods).filter(|d| dep.is_some() || d.is_dir()),
+    }
+}
+
+/// script of the package loaded by `use`, the `entry` of its manifest or `lib.abs`
+pub fn entry(dir: &Path) -> PathBuf {
+    let path = dir.join(MANIFEST);
+    let entry = match fs::read_to_string(&path) {
+        Ok(src) => manifest::parse(&src, &path.display().to_string())
+            .package
+            .and_then(|p| p.entry),
+        Err(_) => None,
+    };
+    dir.join(entry.unwrap_or_else(|| "lib.abs".to_string()))
+}
+
+/// a local directory, a git url, `owner/repo` on GitHub or a registry package, with an optional `@<req>`
+fn parse_spec(
+    root: &Path,
+    spec: &str,
+    alias: Option<&str>,
+    rev: Option<&str>,
+    registry: Option<&str>,
+) -> (String, DependencyDetail) {
+    let (spec, req) = match spec.rsplit_once('@') {
+        Some((s, r)) if Req::parse(r).is_some() => (s, Some(r.to_string())),
+        _ => (spec, None),
+    };
+    let spec = spec.trim_end_matches('/');
+    let is_url = spec.contains("://") || spec.starts_with("git@") || spec.ends_with(".git");
+    let is_path = !is_url && (spec.starts_with('.') || spec.starts_with('/'));
+    let default = spec
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or("")
+        .trim_end_matches(".git");
+    let name = alias
+        .filter(|a| !a.is_empty())
+        .unwrap_or(default)
+        .to_string();
+    let mut detail = DependencyDetail::default();
+    if registry.is_some() || (!spec.contains('/') && !is_url && !is_path) {
+        if registry::directory(registry).is_none() {
+            raw(format!(
+                "no registry for '{}', use --registry <dir> or set {}",
+                spec, REGISTRY_ENV
+            )
+            .as_str());
+            exit(1);
+        }
+        if is_path || is_url || spec.contains('/') || rev.is_some() {
+            raw("registry packages are added by name, without '--rev'");
+            exit(1);
+        }
+        detail.version = req;
+        detail.registry = registry.map(|r| r.to_string());
+        return (name, detail);
+    }
+    if name.is_empty() || !spec.contains('/') {
+        raw(format!(
+            "invalid package '{}', expected <owner>/<repo>, a git url, a path or a name",
+            spec
+        )
+        .as_str());
+        exit(1);
+    }
+    if is_path {
+        if !root.join(spec).is_dir() {
+            raw(format!("'{}' isn't a directory", spec).as_str());
+            exit(1);
+        }
+        if rev.is_some() || req.is_some() {
+            raw("versions and '--rev' can only be used with git dependencies");
+            exit(1);
+        }
+        detail.path = Some(spec.to_string());
+    } else {
+        if rev.is_some() && req.is_some() {
+            raw("a dependency can't have both a version and '--rev'");
+            exit(1);
+        }
+        detail.version = req;
+        detail.git = Some(match is_url {
+            true => spec.to_string(),
+            false => format!("https://github.com/{}.git", spec),
+        });
+        detail.rev = rev.map(|r| r.to_string());
+    }
+    (name, detail)
+}
+
+/// lockfile source of a dependency
+fn source(dep: &DependencyDetail) -> String {
+    match (&dep.git, &dep.rev, &dep.registry) {
+        (Some(git), Some(rev), _) => format!("git+{}?rev={}", git, rev),
+        (Some(git), None, _) => format!("git+{}", git),
+        (None, _, Some(registry)) => format!("registry+{}", registry),
+        _ => String::new(),
+    }
+}
+
+/// installs the exact commit or archive of the lock entry, unless `mods/` already has it
+fn restore(root: &Path, locked: &Locked) {
+    let dir = root.join(MODS).join(&locked.name);
+    if dir.is_dir() && hash_dir(&dir) == locked.hash {
+        return;
+    }
+    match (locked.registry(), &locked.version) {
+        (Some(registry), Some(version)) => {
+            let index = Index::load(registry);
+            let entry = match index
+                .versions(&locked.name)
+                .into_iter()
+                .find(|(v, _)| v.to_string() == *version)
+            {
+                Some((_, e)) => e,
+                None => {
+                    raw(format!(
+                        "{} {} isn't published to '{}' anymore",
+                        locked.name,
+                        version,
+                        registry.display()
+                    )
+                    .as_str());
+                    exit(1);
+                }
+            };
+            registry::extract(registry, &entry, &dir);
+        }
+        _ => {
+            let url = locked.url();
+            println!("{}", format!("cloning {}...", url).yellow());
+            clone(url, &dir, locked.commit.as_deref());
+        }
+    }
+    let hash = hash_dir(&dir);
+    if hash != locked.hash {
+        raw(format!(
+            "hash mismatch for '{}': expected {}, found {}",
+            locked.name, locked.hash, hash
+        )
+        .as_str());
+        exit(1);
+    }
+}
+
+fn clone(url: &str, dir: &Path, rev: Option<&str>) {
+    remove_dir(dir);
+    if let Some(parent) = dir.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            raw(format!("failed to create '{}': {}", parent.display(), e).as_str());
+            exit(1);
+        }
+    }
+    git(
+        Path::new("."),
+        &["clone", "--quiet", url, dir.to_string_lossy().as_ref()],
+    );
+    if let Some(rev) = rev {
+        git(dir, &["checkout", "--quiet", rev]);
+    }
+}
+
+/// runs git in the `dir` and returns its trimmed output
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = match Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+    {
+        Ok(o) => o,
+        Err(e) => {
+            raw(format!("git error: {}", e).as_str());
+            exit(1);
+        }
+    };
+    if !output.status.success() {
+        raw(format!(
+            "git error: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .as_str());
+        exit(1);
+    }
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn remove_dir(dir: &Path) {
+    if dir.exists() {
+        if let Err(e) = fs::remove_dir_all(dir) {
+            raw(format!("failed to remove '{}': {}", dir.display(), e).as_str());
+            exit(1);
+        }
+    }
+}
+
+/// sha256 of the relative paths and contents of all files, except `.git`
+pub fn hash_dir(dir: &Path) -> String {
+    let mut files = vec![];
+    collect_files(dir, dir, &mut files);
+    files.sort();
+    let mut hasher = Sha256::new();
+    for (rel, path) in files {
+        let contents = fs::read(&path).unwrap_or_default();
+        hasher.update(rel.as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    let hex: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256:{}", hex)
+}
+
+fn collect_files(base: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name() == ".git" || entry.file_name() == CACHE {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(base, &path, files);
+        } else {
+            let rel = path.strip_prefix(base).unwrap_or(&path);
+            let rel: Vec<String> = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            files.push((rel.join("/"), path));
+        }
+    }
+}
@@ -0,0 +1,173 @@
+// file-system registries, `<registry>/index.toml` lists the archives published into `<registry>/<name>/`
+use coloredpp::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+use super::{
+    archive::{self, sha256},
+    semver::Version,
+};
+use crate::{errors::raw, manifest::Project};
+
+pub const INDEX: &str = "index.toml";
+/// registry of dependencies without a source
+pub const REGISTRY_ENV: &str = "ABSURD_REGISTRY";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Index {
+    #[serde(default, rename = "package", skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub version: String,
+    /// archive path, relative to the registry
+    pub archive: String,
+    /// `sha256:<hex>` of the archive
+    pub checksum: String,
+}
+
+impl Index {
+    /// reads the index of the `registry`, empty if it doesn't exist
+    pub fn load(registry: &Path) -> Self {
+        let path = registry.join(INDEX);
+        let src = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(_) => return Index::default(),
+        };
+        match toml::from_str(&src) {
+            Ok(i) => i,
+            Err(e) => {
+                raw(format!("failed to parse '{}': {}", path.display(), e).as_str());
+                exit(1);
+            }
+        }
+    }
+
+    fn save(&mut self, registry: &Path) {
+        self.packages.sort_by(|a, b| {
+            let version = |e: &Entry| Version::parse(&e.version);
+            a.name.cmp(&b.name).then(version(a).cmp(&version(b)))
+        });
+        let path = registry.join(INDEX);
+        let src = match toml::to_string(self) {
+            Ok(s) => s,
+            Err(e) => {
+                raw(format!("failed to serialize '{}': {}", path.display(), e).as_str());
+                exit(1);
+            }
+        };
+        if let Err(e) = fs::write(&path, src) {
+            raw(format!("failed to write '{}': {}", path.display(), e).as_str());
+            exit(1);
+        }
+    }
+
+    /// published versions of the package `name`, oldest first
+    pub fn versions(&self, name: &str) -> Vec<(Version, Entry)> {
+        let mut versions: Vec<(Version, Entry)> = self
+            .packages
+            .iter()
+            .filter(|e| e.name == name)
+            .filter_map(|e| Version::parse(&e.version).map(|v| (v, e.clone())))
+            .collect();
+        versions.sort_by(|a, b| a.0.cmp(&b.0));
+        versions
+    }
+}
+
+/// the `--registry` directory or the one of `ABSURD_REGISTRY`
+pub fn directory(registry: Option<&str>) -> Option<PathBuf> {
+    registry
+        .map(|r| r.to_string())
+        .or_else(|| std::env::var(REGISTRY_ENV).ok())
+        .filter(|r| !r.is_empty())
+        .map(PathBuf::from)
+}
+
+/// `absurd publish --registry <dir>`, packs the package and adds it to the registry index
+pub fn publish(project: &Project, registry: &Path) {
+    let packed = archive::pack(project);
+    if let Err(e) = fs::create_dir_all(registry.join(&packed.name)) {
+        raw(format!("failed to create '{}': {}", registry.display(), e).as_str());
+        exit(1);
+    }
+    let mut index = Index::load(registry);
+    if index
+        .packages
+        .iter()
+        .any(|e| e.name == packed.name && e.version == packed.version)
+    {
+        raw(format!(
+            "{} {} is already published to '{}'",
+            packed.name,
+            packed.version,
+            registry.display()
+        )
+        .as_str());
+        exit(1);
+    }
+    let archive = format!(
+        "{}/{}",
+        packed.name,
+        packed
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+    );
+    if let Err(e) = fs::copy(&packed.path, registry.join(&archive)) {
+        raw(format!("failed to copy '{}': {}", packed.path.display(), e).as_str());
+        exit(1);
+    }
+    index.packages.push(Entry {
+        name: packed.name.clone(),
+        version: packed.version.clone(),
+        archive,
+        checksum: packed.checksum,
+    });
+    index.save(registry);
+    println!(
+        "{}",
+        format!(
+            "published {} {} to {}",
+            packed.name,
+            packed.version,
+            registry.display()
+        )
+        .green()
+    );
+}
+
+/// extracts the published archive into the `dir` after checking its checksum
+pub fn extract(registry: &Path, entry: &Entry, dir: &Path) {
+    let path = registry.join(&entry.archive);
+    let tar = match fs::read(&path) {
+        Ok(t) => t,
+        Err(e) => {
+            raw(format!("failed to read '{}': {}", path.display(), e).as_str());
+            exit(1);
+        }
+    };
+    let checksum = sha256(&tar);
+    if checksum != entry.checksum {
+        raw(format!(
+            "checksum mismatch for '{}': expected {}, found {}",
+            path.display(),
+            entry.checksum,
+            checksum
+        )
+        .as_str());
+        exit(1);
+    }
+    if let Err(e) = archive::unpack(&tar, dir) {
+        raw(format!("failed to extract '{}': {}", path.display(), e).as_str());
+        exit(1);
+    }
+}
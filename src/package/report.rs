@@ -0,0 +1,214 @@
+// dependency reports, `absurd tree` and `absurd outdated`
+use coloredpp::Colorize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::read_to_string,
+    path::Path,
+};
+
+use super::{
+    dependencies, git,
+    lock::{Lock, Locked},
+    manifests,
+    registry::Index,
+    resolve, root,
+    semver::Version,
+    MODS,
+};
+use crate::manifest::{self, Dependency, DependencyDetail, Project, MANIFEST};
+use toml::Spanned;
+
+/// `absurd tree`, prints the dependency graph with the locked versions,
+/// every member is printed at the root of a workspace
+pub fn tree(project: &Project) {
+    let root = root(project);
+    let lock = Lock::load(&root);
+    let packages = match (&project.root, project.workspace == project.root) {
+        (_, true) => manifests(project),
+        (Some(dir), false) => vec![(dir.clone(), project.manifest.clone())],
+        (None, false) => vec![],
+    };
+    for (i, (dir, manifest)) in packages.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        match &manifest.package {
+            Some(p) => println!("{} {}", p.name.get_ref(), p.version.get_ref().blue()),
+            None => println!("{}", dir.display()),
+        }
+        let mut seen = HashSet::new();
+        branches(
+            &root,
+            dir,
+            &lock,
+            &details(&manifest.dependencies),
+            "",
+            &mut seen,
+        );
+        if !manifest.dev_dependencies.is_empty() {
+            println!("{}", "[dev-dependencies]".yellow());
+            branches(
+                &root,
+                dir,
+                &lock,
+                &details(&manifest.dev_dependencies),
+                "",
+                &mut seen,
+            );
+        }
+    }
+}
+
+/// `absurd outdated`, compares the locked versions with the tags or published versions of each source
+pub fn outdated(project: &Project) {
+    let root = root(project);
+    let lock = Lock::load(&root);
+    if lock.packages.is_empty() {
+        println!("nothing is locked, run `absurd install` first");
+        return;
+    }
+    let chosen: BTreeMap<String, Locked> = lock
+        .packages
+        .iter()
+        .map(|p| (p.name.clone(), p.clone()))
+        .collect();
+    let nodes = resolve::requirements(&root, &dependencies(project), &chosen);
+
+    let mut rows = vec![];
+    for locked in &lock.packages {
+        let url = locked.url();
+        match &locked.version {
+            Some(version) => {
+                let versions: Vec<Version> = match locked.registry() {
+                    Some(registry) => Index::load(registry)
+                        .versions(&locked.name)
+                        .into_iter()
+                        .map(|(v, _)| v)
+                        .collect(),
+                    None => resolve::tags(url).into_iter().map(|(v, _)| v).collect(),
+                };
+                let reqs = nodes.get(&locked.name).map_or(&[][..], |n| &n.reqs[..]);
+                let wanted = versions
+                    .iter()
+                    .rev()
+                    .find(|v| reqs.iter().all(|(_, r)| r.matches(v)))
+                    .map_or(version.clone(), |v| v.to_string());
+                let latest = versions.last().map_or(version.clone(), |v| v.to_string());
+                if wanted != *version || latest != *version {
+                    rows.push([locked.name.clone(), version.clone(), wanted, latest]);
+                }
+            }
+            None => {
+                // dependencies without a requirement follow their branch
+                let rev = nodes
+                    .get(&locked.name)
+                    .and_then(|n| n.detail.rev.clone())
+                    .unwrap_or("HEAD".to_string());
+                let output = git(Path::new("."), &["ls-remote", url, &rev]);
+                let head = match output.split_whitespace().next() {
+                    Some(h) => h,
+                    // a pinned commit can't be outdated
+                    None => continue,
+                };
+                if locked.commit.as_deref() != Some(head) {
+                    let latest = format!("#{}", &head[..head.len().min(7)]);
+                    rows.push([locked.name.clone(), locked.label(), latest.clone(), latest]);
+                }
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        println!("{}", "all dependencies are up to date".green());
+        return;
+    }
+    let header = ["name", "locked", "wanted", "latest"].map(|h| h.to_string());
+    let mut widths = header.clone().map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |row: &[String; 4]| {
+        row.iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", line(&header).yellow());
+    for row in &rows {
+        println!("{}", line(row));
+    }
+}
+
+fn details(deps: &BTreeMap<String, Spanned<Dependency>>) -> Vec<(String, DependencyDetail)> {
+    deps.iter()
+        .map(|(name, dep)| (name.clone(), dep.get_ref().detail()))
+        .collect()
+}
+
+/// dependencies declared by the manifest of an installed package
+fn package_dependencies(dir: &Path) -> Vec<(String, DependencyDetail)> {
+    let path = dir.join(MANIFEST);
+    match read_to_string(&path) {
+        Ok(src) => details(&manifest::parse(&src, &path.display().to_string()).dependencies),
+        Err(_) => vec![],
+    }
+}
+
+/// prints the `deps` under the `prefix`, packages printed before are marked with `(*)`,
+/// paths are relative to the `base`
+fn branches(
+    root: &Path,
+    base: &Path,
+    lock: &Lock,
+    deps: &[(String, DependencyDetail)],
+    prefix: &str,
+    seen: &mut HashSet<String>,
+) {
+    for (i, (name, detail)) in deps.iter().enumerate() {
+        let (branch, indent) = match i + 1 == deps.len() {
+            true => ("└── ", "    "),
+            false => ("├── ", "│   "),
+        };
+        let locked = lock.get(name);
+        let chosen = match (&detail.path, locked) {
+            (Some(path), _) => format!("(path {})", path),
+            (None, Some(locked)) => locked.label(),
+            (None, None) => "(not installed)".to_string(),
+        };
+        let duplicate = !seen.insert(name.clone());
+        println!(
+            "{}{}{} {}{}",
+            prefix,
+            branch,
+            name,
+            chosen.blue(),
+            if duplicate {
+                " (*)".yellow()
+            } else {
+                String::new()
+            }
+        );
+        let dir = match (&detail.path, locked) {
+            (Some(path), _) => base.join(path),
+            (None, Some(_)) => root.join(MODS).join(name),
+            (None, None) => continue,
+        };
+        if duplicate {
+            continue;
+        }
+        let children = package_dependencies(&dir);
+        branches(
+            root,
+            &dir,
+            lock,
+            &children,
+            &format!("{}{}", prefix, indent),
+            seen,
+        );
+    }
+}
@@ -0,0 +1,371 @@
+// dependency resolution, picks the git tags or registry versions matching the version requirements of the whole graph
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::read_to_string,
+    path::Path,
+    process::exit,
+};
+
+use super::{
+    clone, git, hash_dir,
+    lock::{Lock, Locked},
+    registry::{self, Index},
+    restore,
+    semver::{Req, Version},
+    source, Requirement, MODS,
+};
+use crate::{
+    errors::raw,
+    manifest::{self, DependencyDetail, MANIFEST},
+};
+
+/// a package of the graph and everything that requires it
+#[derive(Clone)]
+pub struct Node {
+    pub detail: DependencyDetail,
+    /// the first dependent, used when sources differ
+    pub from: String,
+    /// dependents and their requirements
+    pub reqs: Vec<(String, Req)>,
+}
+
+/// a package no version can be picked for, with the versions it has
+struct Conflict {
+    name: String,
+    node: Node,
+    available: Vec<(Version, String)>,
+}
+
+/// `(name, version)` of the picked packages that led to a conflict
+type Excluded = HashSet<(String, String)>;
+
+/// how many times the graph is resolved again without a version before giving up
+const RETRIES: usize = 32;
+
+/// installs the `deps` and their dependencies into `mods/`,
+/// versions of the `lock` are kept while they match the requirements
+pub fn resolve(root: &Path, deps: &[Requirement], lock: &Lock) -> Lock {
+    let mut tags = HashMap::new();
+    let mut retries = RETRIES;
+    match search(root, deps, lock, &mut tags, &Excluded::new(), &mut retries) {
+        Ok(chosen) => Lock {
+            version: 1,
+            packages: chosen.into_values().collect(),
+        },
+        Err(c) => conflict(&c.name, &c.node, &c.available),
+    }
+}
+
+/// resolves the graph, when the requirements of a picked package conflict with others
+/// the graph is resolved again without that version so an older one can be picked
+fn search(
+    root: &Path,
+    deps: &[Requirement],
+    lock: &Lock,
+    tags: &mut HashMap<String, Vec<(Version, String)>>,
+    excluded: &Excluded,
+    retries: &mut usize,
+) -> Result<BTreeMap<String, Locked>, Box<Conflict>> {
+    let conflict = match walk(root, deps, lock, tags, excluded) {
+        Ok(chosen) => return Ok(chosen),
+        Err(c) => c,
+    };
+    // requirements of picked packages come from `name version`, the others can't change
+    for (from, _) in &conflict.node.reqs {
+        let Some((name, version)) = from.split_once(' ') else {
+            continue;
+        };
+        let mut without = excluded.clone();
+        if *retries == 0 || !without.insert((name.to_string(), version.to_string())) {
+            continue;
+        }
+        *retries -= 1;
+        if let Ok(chosen) = search(root, deps, lock, tags, &without, retries) {
+            return Ok(chosen);
+        }
+    }
+    Err(conflict)
+}
+
+/// picks a version of every package of the graph, except the `excluded` ones
+fn walk(
+    root: &Path,
+    deps: &[Requirement],
+    lock: &Lock,
+    tags: &mut HashMap<String, Vec<(Version, String)>>,
+    excluded: &Excluded,
+) -> Result<BTreeMap<String, Locked>, Box<Conflict>> {
+    let mut chosen: BTreeMap<String, Locked> = BTreeMap::new();
+    // picking a version changes the requirements of its dependencies,
+    // so the graph is walked again until nothing changes
+    for _ in 0..64 {
+        let nodes = requirements(root, deps, &chosen);
+        let mut next = BTreeMap::new();
+        for (name, node) in &nodes {
+            if let Some(path) = &node.detail.path {
+                if !root.join(path).is_dir() {
+                    raw(format!("path of '{}' doesn't exist: {}", name, path).as_str());
+                    exit(1);
+                }
+                continue;
+            }
+            let locked = match chosen.get(name) {
+                Some(c) if satisfies(c, node) => c.clone(),
+                _ => pick(root, name, node, lock, tags, excluded).map_err(|available| {
+                    Box::new(Conflict {
+                        name: name.clone(),
+                        node: node.clone(),
+                        available,
+                    })
+                })?,
+            };
+            next.insert(name.clone(), locked);
+        }
+        if next == chosen {
+            return Ok(chosen);
+        }
+        chosen = next;
+    }
+    raw("failed to resolve the dependencies, the requirements keep changing");
+    exit(1);
+}
+
+/// available versions of the git repository, from its `v1.2.3` or `1.2.3` tags
+pub fn tags(url: &str) -> Vec<(Version, String)> {
+    let output = git(Path::new("."), &["ls-remote", "--tags", url]);
+    let mut tags: Vec<(Version, String)> = output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .filter_map(|r| r.strip_prefix("refs/tags/"))
+        .filter(|t| !t.ends_with("^{}"))
+        .filter_map(|t| Version::from_tag(t).map(|v| (v, t.to_string())))
+        .collect();
+    tags.sort();
+    tags
+}
+
+/// the requirements of the `deps` and of the `chosen` packages
+pub fn requirements(
+    root: &Path,
+    deps: &[Requirement],
+    chosen: &BTreeMap<String, Locked>,
+) -> BTreeMap<String, Node> {
+    let mut nodes = BTreeMap::new();
+    for (from, name, detail) in deps {
+        require(&mut nodes, from, name, detail.clone());
+    }
+    for (name, locked) in chosen {
+        let path = root.join(MODS).join(name).join(MANIFEST);
+        let src = match read_to_string(&path) {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+        let package = manifest::parse(&src, &path.display().to_string());
+        let from = match &locked.version {
+            Some(v) => format!("{} {}", name, v),
+            None => name.clone(),
+        };
+        for (dep, detail) in &package.dependencies {
+            let mut detail = detail.get_ref().detail();
+            // dependencies without a source come from the registry of the package
+            if detail.git.is_none() && detail.path.is_none() && detail.registry.is_none() {
+                detail.registry = locked
+                    .registry()
+                    .map(|r| r.display().to_string())
+                    .or_else(|| registry::directory(None).map(|r| r.display().to_string()));
+            }
+            if detail.path.is_some() {
+                raw(format!(
+                    "'{}' depends on '{}' by path, packages can only depend on git and registry sources",
+                    name, dep
+                )
+                .as_str());
+                exit(1);
+            }
+            require(&mut nodes, &from, dep, detail);
+        }
+    }
+    nodes
+}
+
+fn require(nodes: &mut BTreeMap<String, Node>, from: &str, name: &str, detail: DependencyDetail) {
+    let req = detail.version.as_ref().map(|v| match Req::parse(v) {
+        Some(r) => r,
+        None => {
+            raw(format!(
+                "invalid version requirement '{}' of '{}' in {}",
+                v, name, from
+            )
+            .as_str());
+            exit(1);
+        }
+    });
+    let node = nodes.entry(name.to_string()).or_insert_with(|| Node {
+        detail: detail.clone(),
+        from: from.to_string(),
+        reqs: vec![],
+    });
+    if node.detail.git != detail.git
+        || node.detail.path != detail.path
+        || node.detail.registry != detail.registry
+    {
+        raw(format!(
+            "'{}' is required from different sources:\n  {} uses {}\n  {} uses {}",
+            name,
+            node.from,
+            describe(&node.detail),
+            from,
+            describe(&detail)
+        )
+        .as_str());
+        exit(1);
+    }
+    if let Some(req) = req {
+        node.reqs.push((from.to_string(), req));
+    }
+}
+
+fn describe(detail: &DependencyDetail) -> String {
+    match (&detail.git, &detail.path, &detail.registry) {
+        (Some(git), _, _) => git.clone(),
+        (None, Some(path), _) => path.clone(),
+        (None, None, Some(registry)) => format!("registry {}", registry),
+        _ => "no source".to_string(),
+    }
+}
+
+fn satisfies(locked: &Locked, node: &Node) -> bool {
+    if locked.source != source(&node.detail) {
+        return false;
+    }
+    match locked.version.as_ref().and_then(|v| Version::parse(v)) {
+        Some(v) => !node.reqs.is_empty() && node.reqs.iter().all(|(_, r)| r.matches(&v)),
+        None => node.reqs.is_empty(),
+    }
+}
+
+/// installs the package into `mods/`, from the lock if it still matches,
+/// the available versions if none of them matches
+fn pick(
+    root: &Path,
+    name: &str,
+    node: &Node,
+    lock: &Lock,
+    tags_of: &mut HashMap<String, Vec<(Version, String)>>,
+    excluded: &Excluded,
+) -> Result<Locked, Vec<(Version, String)>> {
+    let allowed = |v: &Version| !excluded.contains(&(name.to_string(), v.to_string()));
+    let usable = |l: &&Locked| {
+        satisfies(l, node)
+            && l.version
+                .as_ref()
+                .and_then(|v| Version::parse(v))
+                .is_none_or(|v| allowed(&v))
+    };
+    if let Some(locked) = lock.get(name).filter(usable) {
+        restore(root, locked);
+        return Ok(locked.clone());
+    }
+    let dir = root.join(MODS).join(name);
+    let url = match (&node.detail.git, &node.detail.registry) {
+        (Some(url), _) => url,
+        (None, Some(registry)) => return published(name, node, Path::new(registry), &dir, allowed),
+        (None, None) => {
+            raw(format!(
+                "'{}' has no source, add a 'git', 'path' or 'registry' key or set {}",
+                name,
+                registry::REGISTRY_ENV
+            )
+            .as_str());
+            exit(1);
+        }
+    };
+    if node.reqs.is_empty() {
+        clone(url, &dir, node.detail.rev.as_deref());
+        return Ok(locked(name, &node.detail, None, &dir));
+    }
+
+    let available = tags_of.entry(url.clone()).or_insert_with(|| tags(url));
+    let (version, tag) = match available
+        .iter()
+        .rev()
+        .find(|(v, _)| allowed(v) && node.reqs.iter().all(|(_, r)| r.matches(v)))
+    {
+        Some(t) => t.clone(),
+        None => return Err(available.clone()),
+    };
+    clone(url, &dir, Some(&tag));
+    // the tag should agree with the manifest of the package
+    let path = dir.join(MANIFEST);
+    if let Ok(src) = read_to_string(&path) {
+        let package = manifest::parse(&src, &path.display().to_string());
+        if let Some(declared) = package.package.map(|p| p.version.into_inner()) {
+            if declared != version.to_string() {
+                raw(format!(
+                    "tag '{}' of '{}' declares version {} in its {}",
+                    tag, name, declared, MANIFEST
+                )
+                .as_str());
+                exit(1);
+            }
+        }
+    }
+    Ok(locked(name, &node.detail, Some(version.to_string()), &dir))
+}
+
+/// installs the newest published version matching the requirements
+fn published(
+    name: &str,
+    node: &Node,
+    registry: &Path,
+    dir: &Path,
+    allowed: impl Fn(&Version) -> bool,
+) -> Result<Locked, Vec<(Version, String)>> {
+    let available = Index::load(registry).versions(name);
+    let (version, entry) = match available
+        .iter()
+        .rev()
+        .find(|(v, _)| allowed(v) && node.reqs.iter().all(|(_, r)| r.matches(v)))
+    {
+        Some(p) => p.clone(),
+        None => return Err(available.into_iter().map(|(v, e)| (v, e.archive)).collect()),
+    };
+    registry::extract(registry, &entry, dir);
+    Ok(Locked {
+        name: name.to_string(),
+        version: Some(version.to_string()),
+        source: source(&node.detail),
+        commit: None,
+        hash: hash_dir(dir),
+    })
+}
+
+fn locked(name: &str, detail: &DependencyDetail, version: Option<String>, dir: &Path) -> Locked {
+    Locked {
+        name: name.to_string(),
+        version,
+        source: source(detail),
+        commit: Some(git(dir, &["rev-parse", "HEAD"])),
+        hash: hash_dir(dir),
+    }
+}
+
+/// explains why no version of the package can be used
+fn conflict(name: &str, node: &Node, available: &[(Version, String)]) -> ! {
+    let mut msg = format!("no version of '{}' satisfies all requirements:", name);
+    for (from, req) in &node.reqs {
+        msg.push_str(format!("\n  {} requires {} {}", from, name, req).as_str());
+    }
+    if available.is_empty() {
+        let missing = match node.detail.registry {
+            Some(_) => "published versions",
+            None => "version tags",
+        };
+        msg.push_str(format!("\n  {} has no {}", describe(&node.detail), missing).as_str());
+    } else {
+        let versions: Vec<String> = available.iter().map(|(v, _)| v.to_string()).collect();
+        msg.push_str(format!("\n  available versions: {}", versions.join(", ")).as_str());
+    }
+    raw(msg.as_str());
+    exit(1);
+}
@@ -0,0 +1,210 @@
+// semantic versions and requirements: `^1.2`, `~0.3`, `=1.2.3`, `>=1.0, <2.0`, `*`
+use std::{cmp::Ordering, fmt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Caret,
+    Tilde,
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Comparator {
+    op: Op,
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Option<String>,
+}
+
+/// comma separated comparators, all of them have to match
+#[derive(Debug, Clone, PartialEq)]
+pub struct Req {
+    comparators: Vec<Comparator>,
+    text: String,
+}
+
+type Bound = (u64, u64, u64);
+const MAX: Bound = (u64::MAX, u64::MAX, u64::MAX);
+
+impl Version {
+    /// `major.minor.patch` with an optional `-pre` suffix
+    pub fn parse(src: &str) -> Option<Self> {
+        let (core, pre) = match src.split_once('-') {
+            Some((c, p)) => (c, Some(p.to_string())),
+            None => (src, None),
+        };
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        let version = Version {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+            pre,
+        };
+        match parts.next() {
+            Some(_) => None,
+            None => Some(version),
+        }
+    }
+
+    /// version of a git tag, `v1.2.3` or `1.2.3`
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        Self::parse(tag.strip_prefix('v').unwrap_or(tag))
+    }
+
+    fn bound(&self) -> Bound {
+        (self.major, self.minor, self.patch)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // pre-releases come before their release
+        self.bound()
+            .cmp(&other.bound())
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+impl Req {
+    pub fn parse(src: &str) -> Option<Self> {
+        let comparators = src
+            .split(',')
+            .map(|c| Comparator::parse(c.trim()))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Req {
+            comparators,
+            text: src.trim().to_string(),
+        })
+    }
+
+    /// pre-releases only match an exact requirement
+    pub fn matches(&self, version: &Version) -> bool {
+        if version.pre.is_some() {
+            return self.comparators.iter().any(|c| {
+                c.op == Op::Exact
+                    && c.pre == version.pre
+                    && (c.major, c.minor, c.patch)
+                        == (version.major, Some(version.minor), Some(version.patch))
+            });
+        }
+        self.comparators.iter().all(|c| {
+            let (low, high) = c.range();
+            low <= version.bound() && version.bound() < high
+        })
+    }
+}
+
+impl fmt::Display for Req {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl Comparator {
+    fn parse(src: &str) -> Option<Self> {
+        if src == "*" {
+            return Some(Comparator {
+                op: Op::Any,
+                major: 0,
+                minor: None,
+                patch: None,
+                pre: None,
+            });
+        }
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("^", Op::Caret),
+            ("~", Op::Tilde),
+        ]
+        .iter()
+        .find_map(|(p, op)| src.strip_prefix(p).map(|r| (*op, r.trim())))
+        .unwrap_or((Op::Caret, src));
+        let (core, pre) = match rest.split_once('-') {
+            Some((c, p)) => (c, Some(p.to_string())),
+            None => (rest, None),
+        };
+        let mut parts = core.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().map(|p| p.parse()).transpose().ok()?;
+        let patch = parts.next().map(|p| p.parse()).transpose().ok()?;
+        if parts.next().is_some() || (patch.is_none() && pre.is_some()) {
+            return None;
+        }
+        Some(Comparator {
+            op,
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+
+    /// versions from the first bound up to the second, excluded
+    fn range(&self) -> (Bound, Bound) {
+        let (major, minor, patch) = (self.major, self.minor, self.patch);
+        let low = (major, minor.unwrap_or(0), patch.unwrap_or(0));
+        // first version after the written parts, `1.2` -> `1.3.0`
+        let next = match (minor, patch) {
+            (Some(minor), Some(patch)) => (major, minor, patch + 1),
+            (Some(minor), None) => (major, minor + 1, 0),
+            _ => (major + 1, 0, 0),
+        };
+        match self.op {
+            Op::Any => ((0, 0, 0), MAX),
+            Op::Exact => (low, next),
+            Op::GreaterEq => (low, MAX),
+            Op::Greater => (next, MAX),
+            Op::Less => ((0, 0, 0), low),
+            Op::LessEq => ((0, 0, 0), next),
+            Op::Tilde => match minor {
+                Some(minor) => (low, (major, minor + 1, 0)),
+                None => (low, (major + 1, 0, 0)),
+            },
+            // the first non-zero part can't change
+            Op::Caret => match (major, minor, patch) {
+                (0, Some(0), Some(_)) => (low, next),
+                (0, Some(minor), _) => (low, (0, minor + 1, 0)),
+                _ => (low, (major + 1, 0, 0)),
+            },
+        }
+    }
+}
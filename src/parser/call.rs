@@ -21,7 +21,7 @@ impl Parser {
             Some(self.prev(1).token)
         } {
             match token {
-                DblColon => expr = self.enum_call(),
+                DblColon => expr = self.path(),
                 Dot => expr = self.obj_call(),
                 LParen => expr = self.func_call(),
                 LBracket => expr = self.vector_call(),
@@ -36,27 +36,32 @@ impl Parser {
         expr
     }
 
-    pub fn enum_call(&mut self) -> Expression {
-        let name = self.prev(2).clone();
-        let e = self.consume(Ident);
-        let mut args = vec![Expression::Var {
+    /// `Color::Red(1)`, `src::helper(1)` or `src::Color::Red`, enum items and module members
+    pub fn path(&mut self) -> Expression {
+        let mut names = vec![self.prev(2).clone(), self.consume(Ident)];
+        while self.if_token_consume(DblColon) {
+            names.push(self.consume(Ident));
+        }
+        let path = Expression::Path {
             id: self.id(),
-            name: e,
-        }];
-        if self.if_token_consume(LParen) {
-            let expr = self.expr();
-            self.consume(RParen);
-            args.push(expr);
+            names,
         };
-
+        if !self.if_token_consume(LParen) {
+            return path;
+        }
+        let mut args = vec![];
+        while !self.is_token(RParen) {
+            args.push(self.expr());
+            if !self.if_token_consume(Comma) && !self.is_token(RParen) {
+                self.throw_error(E0x103, vec![self.peek().lexeme.clone()]);
+            }
+        }
+        self.consume(RParen);
         Expression::Call {
             id: self.id(),
-            name: Box::new(Expression::Var {
-                id: self.id(),
-                name,
-            }),
+            name: Box::new(path),
             args,
-            call_type: CallType::Enum,
+            call_type: CallType::Func,
         }
     }
 
@@ -77,6 +82,7 @@ impl Parser {
     }
 
     pub fn range(&mut self) -> Expression {
+        let line = self.prev(2).line;
         let lhs = if let Some(LiteralKind::Number { value, .. }) = self.prev(2).clone().value {
             value as usize
         } else {
@@ -95,6 +101,7 @@ impl Parser {
             id: self.id(),
             lhs,
             rhs,
+            line,
         }
     }
 
@@ -104,6 +111,7 @@ impl Parser {
         let args = vec![Expression::Value {
             id: self.id(),
             value: LiteralType::String(e.lexeme),
+            line: e.line,
         }];
         Expression::Call {
             id: self.id(),
@@ -1,6 +1,6 @@
 // parses expressions
 use super::Parser;
-use crate::ast::{FuncBody, Statement, Token, TokenType::*};
+use crate::ast::{format::FormatSpec, FuncBody, LiteralType, Statement, Token, TokenType::*};
 use crate::errors::ErrorCode::{E0x103, E0x107};
 use crate::interpreter::expr::{AssignKind, Expression};
 
@@ -63,11 +63,24 @@ impl Parser {
             Pipe => self.func_expr(),
             Await => self.await_expr(),
             If => self.if_expr(),
+            StrLit => {
+                self.advance();
+                let value = Expression::Value {
+                    id: self.id(),
+                    line: token.line,
+                    value: self.to_value_type(token),
+                };
+                match self.is_token(LParse) {
+                    true => self.interpolation(value),
+                    false => value,
+                }
+            }
             _ => {
                 if self.is_literal() {
                     self.advance();
                     Expression::Value {
                         id: self.id(),
+                        line: token.line,
                         value: self.to_value_type(token),
                     }
                 } else {
@@ -77,6 +90,33 @@ impl Parser {
         }
     }
 
+    /// `"a {b:>8} c"`, scanned into strings with the placeholders between `LParse` and `RParse`,
+    /// the lexeme of `RParse` keeps the format spec
+    fn interpolation(&mut self, head: Expression) -> Expression {
+        let mut parts = vec![(head, None)];
+        while self.if_token_consume(LParse) {
+            let expr = self.expr();
+            let close = self.consume(RParse).lexeme;
+            let spec = close
+                .strip_prefix(':')
+                .and_then(|s| s.strip_suffix('}'))
+                .and_then(FormatSpec::parse);
+            parts.push((expr, spec));
+            let token = self.consume(StrLit);
+            let text = Expression::Value {
+                id: self.id(),
+                line: token.line,
+                value: self.to_value_type(token),
+            };
+            parts.push((text, None));
+        }
+        parts.retain(|(p, _)| p.to_literal() != LiteralType::String(String::new()));
+        Expression::Interpolation {
+            id: self.id(),
+            parts,
+        }
+    }
+
     fn if_expr(&mut self) -> Expression {
         self.consume(If);
         let cond = self.expr();
@@ -6,7 +6,23 @@ use crate::ast::{
 };
 use crate::errors::ErrorCode::{self, E0x104, E0x106};
 use coloredpp::Colorize;
-use std::process::exit;
+use std::{
+    process::exit,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// modules are resolved into the same `Env::locals`, so their node ids can't overlap
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// the id the next parsed node gets
+pub fn next_id() -> usize {
+    NEXT_ID.load(Ordering::Relaxed)
+}
+
+/// reserves `n` node ids for a program that was parsed before, returns the first one
+pub fn reserve_ids(n: usize) -> usize {
+    NEXT_ID.fetch_add(n, Ordering::Relaxed)
+}
 
 impl Parser {
     /// extracts values from token
@@ -166,10 +182,9 @@ impl Parser {
     }
 
     #[inline]
-    /// gets id and increases previous
+    /// gets the next node id, unique across every parser of the program
     pub fn id(&mut self) -> usize {
-        self.id += 1;
-        self.id - 1
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
     }
 
     #[inline]
@@ -8,6 +8,7 @@ use coloredpp::Colorize;
 mod call;
 pub mod expr;
 mod helpers;
+pub use helpers::{next_id, reserve_ids};
 pub mod scanner;
 mod types;
 
@@ -17,7 +18,6 @@ pub struct Parser {
     err: Error,
     crnt: usize,
     log: bool,
-    id: usize,
 }
 
 impl Parser {
@@ -27,7 +27,6 @@ impl Parser {
             err,
             log,
             crnt: 0,
-            id: 0,
         }
     }
 
@@ -191,8 +190,8 @@ impl Parser {
         let mut destruct = None;
 
         // checks if variable is immutable and consumes `pub` keyword, if its there
+        let mut is_pub = !is_mut && self.is_token(Pub);
         let mut pub_names = self.var_is_pub(is_mut);
-        let mut is_pub = !pub_names.is_empty();
         if self.if_token_consume(LBracket) {
             names = self.var_vec_dest();
             destruct = Some(Destruct::Vector)
@@ -225,15 +224,19 @@ impl Parser {
         // publish by their local names
         if pub_names.is_empty() {
             pub_names = names.clone();
-            is_pub = false;
+            // destructured names are only published with `pub(...)`
+            is_pub = is_pub && destruct.is_none();
         }
 
+        let id = self.id();
         let null_var = Statement::Var {
+            id,
             names: names.clone(),
             value_type: Token::null(),
             value: Some(Expression::Value {
                 id: self.id(),
                 value: LiteralType::Null,
+                line: names.first().map_or(0, |n| n.line),
             }),
             is_mut,
             is_pub,
@@ -272,11 +275,13 @@ impl Parser {
             self.log("variable statement");
             // differes from normal `null_var` with dynamic `value_type`
             return Statement::Var {
+                id,
                 names: names.clone(),
                 value_type,
                 value: Some(Expression::Value {
                     id: self.id(),
                     value: LiteralType::Null,
+                    line: names.first().map_or(0, |n| n.line),
                 }),
                 is_mut,
                 is_pub,
@@ -297,6 +302,7 @@ impl Parser {
 
         self.log("variable statement");
         Statement::Var {
+            id,
             names,
             value_type,
             value: Some(value),
@@ -327,6 +333,7 @@ impl Parser {
             }
         }
 
+        let id = self.id();
         let name = self.consume(Ident);
 
         // handles parameters, `...(i: T, i: T)...`
@@ -353,6 +360,7 @@ impl Parser {
             self.consume(Semi);
             self.log("function statement");
             return Statement::Func {
+                id,
                 name,
                 value_type,
                 body: FuncBody::Expression(Box::new(body)),
@@ -367,6 +375,7 @@ impl Parser {
         let body = self.block_stmts();
         self.log("function statement");
         Statement::Func {
+            id,
             name,
             value_type,
             body: FuncBody::Statements(body),
@@ -382,6 +391,7 @@ impl Parser {
             Expression::Value {
                 id: self.id(),
                 value: LiteralType::Null,
+                line: self.prev(1).line,
             }
         } else {
             self.expr()
@@ -470,9 +480,10 @@ impl Parser {
 
     fn breaks(&mut self) -> Statement {
         self.start("break statement");
+        let line = self.prev(1).line;
         self.consume(Semi);
         self.log("break statement");
-        Statement::Break {}
+        Statement::Break { line }
     }
 
     fn enums(&mut self) -> Statement {
@@ -558,14 +569,16 @@ impl Parser {
     // very simple syntax
     fn shs(&mut self) -> Statement {
         self.start("sh statement");
+        let line = self.prev(1).line;
         let cmd = self.consume(StrLit).lexeme;
         self.consume(Semi);
         self.log("sh statement");
-        Statement::Sh { cmd }
+        Statement::Sh { cmd, line }
     }
 
     fn mods(&mut self) -> Statement {
         self.start("mod statement");
+        let line = self.prev(1).line;
         let src = self.consume(StrLit).lexeme;
         let name = if self.if_token_consume(As) {
             Some(self.consume(Ident).lexeme)
@@ -574,7 +587,7 @@ impl Parser {
         };
         self.consume(Semi);
         self.log("mod statement");
-        Statement::Mod { src, name }
+        Statement::Mod { src, name, line }
     }
 
     fn uses(&mut self) -> Statement {
@@ -615,7 +628,12 @@ impl Parser {
         };
         self.consume(Semi);
         self.log("use statement");
-        Statement::Use { src, names, all }
+        Statement::Use {
+            id: self.id(),
+            src,
+            names,
+            all,
+        }
     }
 
     fn types(&mut self) -> Statement {
@@ -1,5 +1,6 @@
 // Absurd Scanner, collects tokens from the source.
 use crate::ast::{
+    format::{slot, FormatSpec},
     Base, LiteralKind, Token,
     TokenType::{self, *},
 };
@@ -19,6 +20,8 @@ pub struct Scanner<'a> {
     crnt: usize,
     err: Error,
     log: bool,
+    /// braces opened in the placeholders of the strings being scanned, innermost last
+    interps: Vec<usize>,
 }
 
 impl<'a> Scanner<'a> {
@@ -74,6 +77,7 @@ impl<'a> Scanner<'a> {
             start: 0,
             crnt: 0,
             log,
+            interps: vec![],
         }
     }
 
@@ -82,11 +86,19 @@ impl<'a> Scanner<'a> {
         if self.log {
             println!("  {}", "collecting tokens...".yellow())
         }
+        // skip the shebang line of executable scripts
+        if self.src.starts_with("#!") {
+            self.line_comment();
+        }
         // advance until the end of the file
         while !self.is_eof() {
             self.start = self.crnt;
             self.advance_token();
         }
+        if !self.interps.is_empty() {
+            self.err
+                .throw(E0x102, self.line, (self.pos - 1, self.pos), vec![]);
+        }
         // push 'end of file' token, after reaching it
         self.tokens.push(Token {
             token: Eof,
@@ -118,15 +130,24 @@ impl<'a> Scanner<'a> {
             '%' => self.push(Prcnt, None),
             '(' => self.push(LParen, None),
             ')' => self.push(RParen, None),
-            '{' => self.push(LBrace, None),
-            '}' => self.push(RBrace, None),
+            '{' => {
+                if let Some(open) = self.interps.last_mut() {
+                    *open += 1;
+                }
+                self.push(LBrace, None)
+            }
+            '}' => self.rbrace(),
             '[' => self.push(LBracket, None),
             ']' => self.push(RBracket, None),
             ';' => self.push(Semi, None),
             ',' => self.push(Comma, None),
             '?' => self.push(Qstn, None),
             // double character tokens
-            ':' => self.dbl_char(':', Colon, DblColon),
+            ':' => {
+                if !self.format_spec() {
+                    self.dbl_char(':', Colon, DblColon)
+                }
+            }
             '!' => self.mult_char(Bang, &[('=', BangEq), ('!', DblBang)]),
             '&' => self.dbl_char('&', And, DblAnd),
             '+' => self.mult_char(Plus, &[('+', Incr), ('=', PlusEq)]),
@@ -304,12 +325,14 @@ impl<'a> Scanner<'a> {
         );
     }
 
-    /// handles "strings"
+    /// handles "strings", `"a {b} c"` is pushed as `StrLit LParse .. RParse StrLit`
     fn strlit(&mut self) {
         let mut value = String::new();
         let mut in_escape = false;
         let mut unicode_escape = false;
         let mut unicode_buffer = String::new();
+        // commands and paths of `sh`, `mod` and `use ... from` aren't expressions, their braces stay
+        let raw = matches!(self.tokens.last().map(|t| &t.token), Some(Sh | Mod | From));
 
         while self.peek() != '"' && !self.is_eof() {
             if self.peek() == '\n' {
@@ -348,6 +371,8 @@ impl<'a> Scanner<'a> {
                         't' => value.push('\t'),
                         '\\' => value.push('\\'),
                         '"' => value.push('"'),
+                        '{' => value.push('{'),
+                        '}' => value.push('}'),
                         'r' => value.push('\r'),
                         'u' => {
                             unicode_escape = true;
@@ -358,6 +383,25 @@ impl<'a> Scanner<'a> {
                 }
             } else if self.peek() == '\\' {
                 in_escape = true;
+            } else if raw {
+                value.push(self.peek());
+            } else if let Some((len, spec)) = slot(&self.src[self.crnt..]) {
+                if let Err(spec) = spec {
+                    let offset = self.src[self.start..self.crnt].chars().count();
+                    self.invalid_spec(spec, offset + 2);
+                }
+                // `{}` and `{:spec}` are kept for the templates of `format`
+                value.push_str(&self.src[self.crnt..self.crnt + len]);
+                self.crnt += len;
+                continue;
+            } else if self.peek() == '{' {
+                // the placeholder is scanned as code, `rbrace` continues the string after it
+                self.push(StrLit, Some(LiteralKind::String { value }));
+                self.start = self.crnt;
+                self.advance();
+                self.push(LParse, None);
+                self.interps.push(0);
+                return;
             } else {
                 value.push(self.peek());
             }
@@ -374,6 +418,54 @@ impl<'a> Scanner<'a> {
         self.push(StrLit, Some(LiteralKind::String { value }));
     }
 
+    /// `}`, closing a placeholder continues its string
+    fn rbrace(&mut self) {
+        match self.interps.last_mut() {
+            Some(0) => self.close_placeholder(),
+            Some(open) => {
+                *open -= 1;
+                self.push(RBrace, None);
+            }
+            None => self.push(RBrace, None),
+        }
+    }
+
+    /// `:spec}` after the expression of a placeholder closes it, the spec is kept in the lexeme
+    fn format_spec(&mut self) -> bool {
+        let rest = &self.src[self.crnt..];
+        let spec = match rest.find('}') {
+            Some(end) if self.interps.last() == Some(&0) && !rest.starts_with(':') => &rest[..end],
+            _ => return false,
+        };
+        if FormatSpec::parse(spec).is_none() {
+            // text after the `:` of an `if` has spaces or literals in it, a lone word is a spec
+            if !spec.is_empty()
+                && !spec.contains(|c: char| c.is_whitespace() || "\"'([".contains(c))
+            {
+                self.invalid_spec(spec, 1);
+            }
+            return false;
+        }
+        self.crnt += spec.len() + 1;
+        self.close_placeholder();
+        true
+    }
+
+    /// reports the `spec`, which starts `offset` chars after the current token
+    fn invalid_spec(&self, spec: &str, offset: usize) {
+        let start = self.pos + offset;
+        let end = start + spec.chars().count();
+        self.err
+            .throw(E0x108, self.line, (start, end), vec![spec.to_string()]);
+    }
+
+    fn close_placeholder(&mut self) {
+        self.interps.pop();
+        self.push(RParse, None);
+        self.start = self.crnt;
+        self.strlit();
+    }
+
     /// handles identifier, Unicode emoji, '_' or alphanumeric
     fn ident(&mut self) {
         while UnicodeXID::is_xid_continue(self.peek()) || self.peek() == '_' {
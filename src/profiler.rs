@@ -0,0 +1,151 @@
+// function-level profiler, enabled with `absurd run <file> --profile`
+use coloredpp::Colorize;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs::File,
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use crate::errors::raw;
+
+thread_local! {
+    static PROFILER: RefCell<Option<Profiler>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameKind {
+    /// user function, executed by `call_func`
+    Func,
+    /// standard library function (`DeclrFunc`)
+    Native,
+}
+
+#[derive(Debug)]
+struct Frame {
+    name: String,
+    start: Instant,
+    /// time spent in the callees of the frame
+    children: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    calls: usize,
+    inclusive: Duration,
+    exclusive: Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct Profiler {
+    stack: Vec<Frame>,
+    stats: HashMap<(String, bool), Stats>,
+    /// exclusive time of every unique call stack
+    folded: HashMap<String, Duration>,
+}
+
+/// starts recording function calls
+pub fn start() {
+    PROFILER.with(|p| *p.borrow_mut() = Some(Profiler::default()));
+}
+
+/// pushes a new frame, call it right before the function body is executed
+pub fn enter(name: &str) {
+    PROFILER.with(|p| {
+        if let Some(p) = p.borrow_mut().as_mut() {
+            p.stack.push(Frame {
+                name: name.to_string(),
+                start: Instant::now(),
+                children: Duration::ZERO,
+            });
+        }
+    });
+}
+
+/// pops the last frame and records its timings
+pub fn exit(kind: FrameKind) {
+    PROFILER.with(|p| {
+        if let Some(p) = p.borrow_mut().as_mut() {
+            p.pop(kind);
+        }
+    });
+}
+
+impl Profiler {
+    fn pop(&mut self, kind: FrameKind) {
+        let frame = match self.stack.pop() {
+            Some(f) => f,
+            None => return,
+        };
+        let inclusive = frame.start.elapsed();
+        let exclusive = inclusive.saturating_sub(frame.children);
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children += inclusive;
+        }
+
+        // recursive calls are already counted by the outermost frame
+        let is_recursive = self.stack.iter().any(|f| f.name == frame.name);
+        let stats = self
+            .stats
+            .entry((frame.name.clone(), kind == FrameKind::Native))
+            .or_default();
+        stats.calls += 1;
+        stats.exclusive += exclusive;
+        if !is_recursive {
+            stats.inclusive += inclusive;
+        }
+
+        let mut path: Vec<&str> = vec!["main"];
+        path.extend(self.stack.iter().map(|f| f.name.as_str()));
+        path.push(frame.name.as_str());
+        *self.folded.entry(path.join(";")).or_default() += exclusive;
+    }
+}
+
+/// stops recording, prints the report and writes folded stacks to the `path`
+pub fn finish(path: &str) {
+    let profiler = match PROFILER.with(|p| p.borrow_mut().take()) {
+        Some(p) => p,
+        None => return,
+    };
+
+    let mut rows: Vec<(&(String, bool), &Stats)> = profiler.stats.iter().collect();
+    rows.sort_by(|a, b| b.1.exclusive.cmp(&a.1.exclusive).then(a.0.cmp(b.0)));
+
+    println!("{}", "profile:".yellow());
+    println!(
+        "{}",
+        format!(
+            "  {:>8}  {:>12}  {:>12}  {:<6}  name",
+            "calls", "incl (ms)", "excl (ms)", "kind"
+        )
+        .blue()
+    );
+    for ((name, is_native), stats) in rows {
+        println!(
+            "  {:>8}  {:>12.3}  {:>12.3}  {:<6}  {}",
+            stats.calls,
+            stats.inclusive.as_secs_f64() * 1000.0,
+            stats.exclusive.as_secs_f64() * 1000.0,
+            if *is_native { "native" } else { "func" },
+            name
+        );
+    }
+
+    // folded stacks format: `main;a;b <microseconds>`
+    let mut stacks: Vec<(&String, &Duration)> = profiler.folded.iter().collect();
+    stacks.sort();
+    let mut out = String::new();
+    for (stack, time) in stacks {
+        out.push_str(&format!("{} {}\n", stack, time.as_micros()));
+    }
+    match File::create(path).and_then(|mut f| f.write_all(out.as_bytes())) {
+        Ok(_) => println!(
+            "{} {}",
+            "folded stacks written to".green(),
+            path.to_string().blue()
+        ),
+        Err(e) => raw(format!("failed to write '{}': {}", path, e).as_str()),
+    }
+}
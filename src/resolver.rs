@@ -1,7 +1,7 @@
 // Absurd resolver, it resolves statements and returns locals
 use crate::ast::{FuncBody, Statement, Token};
 use crate::errors::{Error, ErrorCode::*};
-use crate::interpreter::env::Env;
+use crate::interpreter::env::{Env, Locals};
 use crate::interpreter::expr::Expression;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -9,8 +9,9 @@ use std::rc::Rc;
 
 #[derive(Debug, Clone)]
 pub struct Resolver {
-    locals: HashMap<usize, usize>,
-    scopes: Vec<HashMap<String, bool>>,
+    locals: Locals,
+    /// names of every local scope with their slot and whether they are defined yet
+    scopes: Vec<HashMap<String, (usize, bool)>>,
     is_crnt_fnc: bool,
     is_crnt_loop: bool,
     err: Error,
@@ -28,11 +29,7 @@ impl Resolver {
     }
 
     /// entry method
-    pub fn resolve(
-        &mut self,
-        stmts: &[Statement],
-        env: &Rc<RefCell<Env>>,
-    ) -> HashMap<usize, usize> {
+    pub fn resolve(&mut self, stmts: &[Statement], env: &Rc<RefCell<Env>>) -> Locals {
         stmts.iter().for_each(|stmt| self.resolve_stmt(stmt, env));
         self.locals.clone()
     }
@@ -53,9 +50,20 @@ impl Resolver {
                 cond,
             } => self.ifs(cond, body, else_if_branches, else_branch, env),
             Statement::Block { stmts } => self.block(stmts, env),
-            Statement::Break {} => self.breaks(),
+            Statement::Break { .. } => self.breaks(),
             Statement::Expression { expr } => self.expr(expr, env),
-            Statement::Func { body, params, .. } => self.func(body, params.as_slice(), env),
+            Statement::Func {
+                id,
+                name,
+                body,
+                params,
+                ..
+            } => {
+                self.declare(name);
+                self.define(name);
+                self.slot(*id, name);
+                self.func(body, params.as_slice(), env)
+            }
             Statement::Loop { body, .. } => self.loops(body, env),
             Statement::Match {
                 cond,
@@ -63,8 +71,10 @@ impl Resolver {
                 def_case,
             } => self.matchs(cond, def_case, cases, env),
             Statement::Return { expr } => self.returns(expr, env),
-            Statement::Use { names, .. } => self.uses(names),
-            Statement::Var { names, value, .. } => self.var(names, value, env),
+            Statement::Use { id, names, .. } => self.uses(*id, names),
+            Statement::Var {
+                id, names, value, ..
+            } => self.var(*id, names, value, env),
             Statement::While { body, cond } => self.whiles(body, cond, env),
             _ => {}
         }
@@ -78,7 +88,6 @@ impl Resolver {
         expr: &Expression,
         env: &Rc<RefCell<Env>>,
     ) {
-        
         self.expr(expr, env);
         let encl_loop = self.is_crnt_loop;
         self.is_crnt_loop = true;
@@ -94,7 +103,7 @@ impl Resolver {
         self.is_crnt_loop = encl_loop;
     }
 
-    fn uses(&mut self, names: &Vec<(Token, Option<Token>)>) {
+    fn uses(&mut self, id: usize, names: &Vec<(Token, Option<Token>)>) {
         for (old, new) in names {
             if let Some(new_name) = new {
                 self.declare(new_name);
@@ -104,9 +113,18 @@ impl Resolver {
                 self.define(old);
             }
         }
+        if let Some((old, new)) = names.first() {
+            self.slot(id, new.as_ref().unwrap_or(old));
+        }
     }
 
-    fn var(&mut self, names: &Vec<Token>, value: &Option<Expression>, env: &Rc<RefCell<Env>>) {
+    fn var(
+        &mut self,
+        id: usize,
+        names: &Vec<Token>,
+        value: &Option<Expression>,
+        env: &Rc<RefCell<Env>>,
+    ) {
         for name in names {
             self.declare(name);
             if let Some(value) = value {
@@ -114,6 +132,9 @@ impl Resolver {
             }
             self.define(name);
         }
+        if let Some(name) = names.first() {
+            self.slot(id, name);
+        }
     }
 
     fn whiles(&mut self, body: &Vec<Statement>, cond: &Expression, env: &Rc<RefCell<Env>>) {
@@ -261,10 +282,18 @@ impl Resolver {
                     self.expr(&branch, env);
                 }
             }
-            Expression::Assign { value, .. } => self.expr(value, env),
+            Expression::Assign {
+                id, name, value, ..
+            } => {
+                self.expr(value, env);
+                self.resolve_local(name, *id);
+            }
             Expression::Vec { items, .. } => {
                 items.iter().for_each(|item| self.expr(item, env));
             }
+            Expression::Interpolation { parts, .. } => {
+                parts.iter().for_each(|(part, _)| self.expr(part, env));
+            }
             Expression::Tuple { items, .. } => {
                 items.iter().for_each(|item| self.expr(item, env));
             }
@@ -313,8 +342,8 @@ impl Resolver {
     }
 
     fn varexpr(&mut self, expr: &Expression) {
-        if let Expression::Var { name, .. } = expr {
-            if let Some(false) = self.scopes.last().and_then(|scope| scope.get(&name.lexeme)) {
+        if let Expression::Var { id, name } = expr {
+            if let Some((_, false)) = self.scopes.last().and_then(|scope| scope.get(&name.lexeme)) {
                 self.err.throw(
                     E0x304,
                     name.line,
@@ -322,6 +351,7 @@ impl Resolver {
                     vec!["a local variable".to_string()],
                 );
             }
+            self.resolve_local(name, *id);
         } else if let Expression::Call { name, .. } = expr {
             if let Expression::Var { name, .. } = name.as_ref() {
                 self.resolve_local(name, expr.id());
@@ -341,20 +371,35 @@ impl Resolver {
                 self.err
                     .throw(E0x305, name.line, name.pos, vec![name.lexeme.clone()]);
             }
-            scope.insert(name.lexeme.clone(), false);
+            // a redefined name keeps its slot, like it does at runtime
+            let slot = scope
+                .get(&name.lexeme)
+                .map_or(scope.len(), |(slot, _)| *slot);
+            scope.insert(name.lexeme.clone(), (slot, false));
         }
     }
 
     fn define(&mut self, name: &Token) {
         if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name.lexeme.clone(), true);
+            let slot = scope.len();
+            scope.entry(name.lexeme.clone()).or_insert((slot, true)).1 = true;
         }
     }
 
+    /// records the slot of the first name declared by the statement `id` in a local scope,
+    /// the names after it take the next slots
+    fn slot(&mut self, id: usize, name: &Token) {
+        if let Some((slot, _)) = self.scopes.last().and_then(|scope| scope.get(&name.lexeme)) {
+            self.locals.insert(id, (0, *slot));
+        }
+    }
+
+    /// records the depth of the scope defining the name and its slot in it,
+    /// names that aren't found are global
     fn resolve_local(&mut self, name: &Token, id: usize) {
         for (i, scope) in self.scopes.iter().rev().enumerate() {
-            if scope.contains_key(&name.lexeme) {
-                self.locals.insert(id, i);
+            if let Some((slot, _)) = scope.get(&name.lexeme) {
+                self.locals.insert(id, (i, *slot));
                 return;
             }
         }
@@ -0,0 +1,81 @@
+// creates new projects, used by `absurd new <name>` and `absurd init`
+use coloredpp::Colorize;
+use std::{fs, path::Path, process::exit};
+
+use crate::manifest::is_package_name;
+
+const MAIN: &str = "print(\"Hello, world!\");\n";
+
+const TEST: &str = "// run with: absurd run tests/main.abs -t
+use assert from std::core::test;
+
+assert(1 + 1 == 2, \"addition\");
+";
+
+const GITIGNORE: &str = "mods/\ndist/\n.absurd-cache/\n";
+
+fn manifest(name: &str) -> String {
+    format!(
+        "[package]
+name = \"{name}\"
+version = \"0.1.0\"
+entry = \"src/main.abs\"
+
+[config]
+# number of lines to show before and after the error line
+# set to -1 to disable snippets
+snippet = 1
+# allow side effects
+# if false: disables: mutability, modality, publicity and other sources of side effects
+side_effects = true
+# disable the standard library
+disable_std = false
+# disable the standard library prelude
+load_std = true
+"
+    )
+}
+
+/// prints the error and exits with a failure
+fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg.red());
+    exit(1);
+}
+
+/// creates a project in the `dir`, fails if the name isn't a valid package name
+/// or the directory already contains a manifest
+pub fn create(dir: &Path, name: &str) {
+    if !is_package_name(name) {
+        fail(&format!(
+            "invalid package name '{}', use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    if dir.join("project.toml").exists() {
+        fail(&format!(
+            "'{}' already contains a project.toml",
+            dir.display()
+        ));
+    }
+    let files = [
+        ("project.toml", manifest(name)),
+        ("src/main.abs", MAIN.to_string()),
+        ("tests/main.abs", TEST.to_string()),
+        (".gitignore", GITIGNORE.to_string()),
+    ];
+    for (path, contents) in files {
+        let path = dir.join(path);
+        // keep existing sources when initializing in a non-empty directory
+        if path.exists() {
+            continue;
+        }
+        let written = match path.parent() {
+            Some(parent) => fs::create_dir_all(parent).and_then(|_| fs::write(&path, contents)),
+            None => fs::write(&path, contents),
+        };
+        if let Err(e) = written {
+            fail(&format!("failed to create '{}': {}", path.display(), e));
+        }
+    }
+    println!("{} {}", "created project".green(), name.to_string().blue());
+}
@@ -0,0 +1,149 @@
+use std::{env, rc::Rc};
+
+use crate::{
+    ast::{LiteralType, Token, Wrapper},
+    errors::raw,
+    interpreter::expr::Expression,
+    std::{func, StdFunc},
+};
+
+impl StdFunc {
+    pub fn load_core_env(&mut self) {
+        self.load_args(None);
+        self.load_var(None);
+        self.load_vars(None);
+        self.load_cwd(None);
+        self.load_set_var(None);
+    }
+
+    /// args() - arguments passed to the script after `--`
+    pub fn load_args(&mut self, name: Option<Token>) {
+        let name = match name {
+            Some(n) => n.lexeme.clone(),
+            None => "args".to_string(),
+        };
+        let args = self.project.args.clone();
+        func(
+            name.as_str(),
+            0,
+            &mut self.env,
+            Rc::new(Wrapper(Box::new(move |_args: &[Option<LiteralType>]| {
+                LiteralType::Vec(
+                    args.iter()
+                        .map(|a| LiteralType::String(a.clone()))
+                        .collect(),
+                )
+            }))),
+        );
+    }
+
+    /// var(name) - value of the environment variable, null if it isn't set
+    pub fn load_var(&mut self, name: Option<Token>) {
+        let name = match name {
+            Some(n) => n.lexeme.clone(),
+            None => "var".to_string(),
+        };
+        func(
+            name.as_str(),
+            1,
+            &mut self.env,
+            Rc::new(Wrapper(Box::new(|args: &[Option<LiteralType>]| {
+                if args.len() != 1 {
+                    raw("expected an argument");
+                }
+                match args[0].clone() {
+                    Some(LiteralType::String(key)) => match env::var(key) {
+                        Ok(v) => LiteralType::String(v),
+                        Err(_) => LiteralType::Null,
+                    },
+                    _ => {
+                        raw("expected a string");
+                        LiteralType::Void
+                    }
+                }
+            }))),
+        );
+    }
+
+    /// vars() - record of all environment variables
+    pub fn load_vars(&mut self, name: Option<Token>) {
+        let name = match name {
+            Some(n) => n.lexeme.clone(),
+            None => "vars".to_string(),
+        };
+        func(
+            name.as_str(),
+            0,
+            &mut self.env,
+            Rc::new(Wrapper(Box::new(|_args: &[Option<LiteralType>]| {
+                let mut vars: Vec<(String, String)> = env::vars().collect();
+                vars.sort();
+                LiteralType::Record(
+                    vars.into_iter()
+                        .map(|(k, v)| {
+                            (
+                                k,
+                                Expression::Value {
+                                    id: 0,
+                                    value: LiteralType::String(v),
+                                    line: 0,
+                                },
+                            )
+                        })
+                        .collect(),
+                )
+            }))),
+        );
+    }
+
+    /// cwd() - current working directory
+    pub fn load_cwd(&mut self, name: Option<Token>) {
+        let name = match name {
+            Some(n) => n.lexeme.clone(),
+            None => "cwd".to_string(),
+        };
+        func(
+            name.as_str(),
+            0,
+            &mut self.env,
+            Rc::new(Wrapper(Box::new(
+                |_args: &[Option<LiteralType>]| match env::current_dir() {
+                    Ok(dir) => LiteralType::String(dir.to_string_lossy().to_string()),
+                    Err(e) => {
+                        raw(format!("failed to get the current directory: {}", e).as_str());
+                        LiteralType::Void
+                    }
+                },
+            ))),
+        );
+    }
+
+    /// set_var(name, value) - sets the environment variable, disabled without side effects
+    pub fn load_set_var(&mut self, name: Option<Token>) {
+        let name = match name {
+            Some(n) => n.lexeme.clone(),
+            None => "set_var".to_string(),
+        };
+        let side_effects = self.project.side_effects;
+        func(
+            name.as_str(),
+            2,
+            &mut self.env,
+            Rc::new(Wrapper(Box::new(move |args: &[Option<LiteralType>]| {
+                if args.len() != 2 {
+                    raw("expected 2 arguments");
+                }
+                if !side_effects {
+                    raw("side effects are disabled, 'set_var' can't modify the environment");
+                }
+                match (args[0].clone(), args[1].clone()) {
+                    (Some(LiteralType::String(key)), Some(value)) => {
+                        env::set_var(key, value.to_string())
+                    }
+                    _ => raw("expected a string name"),
+                }
+                LiteralType::Void
+            }))),
+        );
+    }
+}
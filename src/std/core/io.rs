@@ -1,11 +1,21 @@
 use crate::{
     ast::{LiteralType, Token, Wrapper},
+    dap,
     errors::raw,
     std::{func, StdFunc},
 };
 use coloredpp::Colorize;
 use std::{process::exit, rc::Rc};
 
+/// prints a line to the terminal, or sends it to the debug adapter client
+pub fn write_line(text: String) {
+    if dap::is_active() {
+        dap::output("stdout", &format!("{}\n", text));
+    } else {
+        println!("{}", text);
+    }
+}
+
 impl StdFunc {
     pub fn load_core_io(&mut self) {
         self.load_print(None);
@@ -34,7 +44,7 @@ impl StdFunc {
                     if args.len() != 1 {
                         raw("expected an argument");
                     }
-                    println!("{}", args[0].clone().unwrap().to_string());
+                    write_line(args[0].clone().unwrap().to_string());
                     LiteralType::Void
                 }),
             }),
@@ -56,7 +66,7 @@ impl StdFunc {
                     if args.len() != 1 {
                         raw("expected an argument");
                     }
-                    println!("{}", args[0].clone().unwrap().to_string().red());
+                    write_line(args[0].clone().unwrap().to_string().red());
                     LiteralType::Void
                 }),
             }),
@@ -77,7 +87,7 @@ impl StdFunc {
                     if args.len() != 1 {
                         raw("expected an argument");
                     }
-                    println!("{}", args[0].clone().unwrap().to_string().yellow());
+                    write_line(args[0].clone().unwrap().to_string().yellow());
                     LiteralType::Void
                 }),
             }),
@@ -98,7 +108,7 @@ impl StdFunc {
                     if args.len() != 1 {
                         raw("expected an argument");
                     }
-                    println!("{}", args[0].clone().unwrap().to_string().red());
+                    write_line(args[0].clone().unwrap().to_string().red());
                     exit(0);
                 }),
             }),
@@ -1,2 +1,3 @@
+pub mod env;
 pub mod io;
 pub mod test;
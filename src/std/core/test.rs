@@ -3,7 +3,7 @@ use std::{cell::RefCell, rc::Rc};
 use crate::{
     ast::{LiteralType, Token, Wrapper},
     errors::raw,
-    std::{func, StdFunc},
+    std::{core::io::write_line, func, StdFunc},
 };
 use coloredpp::Colorize;
 
@@ -18,7 +18,7 @@ impl StdFunc {
             None => "assert".to_string(),
         };
 
-        if self.is_test {
+        if self.project.test {
             let mut env = Rc::clone(&self.env);
             let test_instance = Rc::new(RefCell::new(self.clone()));
 
@@ -60,12 +60,12 @@ impl StdFunc {
     
     #[inline]
     fn success(&self, name: String) {
-        println!("  {}", format!("success: test '{}'", name).green());
+        write_line(format!("  {}", format!("success: test '{}'", name).green()));
     }
 
    
     #[inline]
     fn fail(&self, name: String) {
-        println!("  {}", format!("fail: test '{}'", name).red());
+        write_line(format!("  {}", format!("fail: test '{}'", name).red()));
     }
 }
@@ -1,7 +1,7 @@
 use std::rc::Rc;
 
 use crate::{
-    ast::{LiteralType, Token, Wrapper},
+    ast::{format::fill, LiteralType, Token, Wrapper},
     errors::raw,
     std::{func, StdFunc},
 };
@@ -22,6 +22,7 @@ impl StdFunc {
         self.load_to_uppercase(None);
         // self.load_parse(None);
         self.load_replace(None);
+        self.load_format(None);
         // self.load_split(None);
         // self.load_split_once(None);
         // self.load_split_whitespace(None);
@@ -375,6 +376,31 @@ impl StdFunc {
         );
     }
 
+    /// format(template, values..) - fills the `{}` and `{:spec}` slots of the template in order
+    pub fn load_format(&mut self, name: Option<Token>) {
+        let name = match name {
+            Some(n) => n.lexeme.clone(),
+            None => "format".to_string(),
+        };
+        func(
+            name.as_str(),
+            1,
+            &mut self.env,
+            Rc::new(Wrapper(Box::new(|args: &[Option<LiteralType>]| {
+                if args.is_empty() {
+                    raw("expected a template");
+                }
+                let values: Vec<LiteralType> = args.iter().flatten().cloned().collect();
+                match values.split_first() {
+                    Some((LiteralType::String(template), values)) => {
+                        LiteralType::String(fill(template, values))
+                    }
+                    _ => LiteralType::Null,
+                }
+            }))),
+        );
+    }
+
     // pub fn load_split(&mut self, name: Option<Token>) {
     //     let name = match name {
     //         Some(n) => n.lexeme.clone(),
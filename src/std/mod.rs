@@ -9,6 +9,7 @@ use crate::{
         env::{Env, FuncKind},
         Interpreter,
     },
+    manifest::Project,
 };
 
 pub fn func(name: &str, arity: usize, env: &mut Rc<RefCell<Env>>, func: Rc<dyn FuncValType>) {
@@ -31,12 +32,12 @@ pub fn func(name: &str, arity: usize, env: &mut Rc<RefCell<Env>>, func: Rc<dyn F
 #[derive(Clone)]
 pub struct StdFunc {
     env: Rc<RefCell<Env>>,
-    is_test: bool,
+    project: Project,
 }
 
 impl StdFunc {
-    pub fn new(env: Rc<RefCell<Env>>, is_test: bool) -> Self {
-        Self { env, is_test }
+    pub fn new(env: Rc<RefCell<Env>>, project: Project) -> Self {
+        Self { env, project }
     }
 }
 
@@ -44,7 +45,7 @@ impl Interpreter {
     pub fn std_map(
         &mut self,
     ) -> HashMap<&str, Vec<(&str, HashMap<&str, Box<dyn FnMut(&Option<Token>) + '_>>)>> {
-        let std = StdFunc::new(Rc::clone(&self.env), self.project.test);
+        let std = StdFunc::new(Rc::clone(&self.env), self.project.clone());
         HashMap::from([
             (
                 "core",
@@ -156,6 +157,61 @@ impl Interpreter {
                             }) as Box<dyn FnMut(&Option<Token>)>,
                         )]),
                     ),
+                    (
+                        "env",
+                        HashMap::from([
+                            (
+                                "args",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_args(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "var",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_var(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "vars",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_vars(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "cwd",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_cwd(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "set_var",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_set_var(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                        ]),
+                    ),
                 ],
             ),
             (
@@ -609,6 +665,16 @@ impl Interpreter {
                                 })
                                     as Box<dyn FnMut(&Option<Token>)>,
                             ),
+                            (
+                                "format",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_format(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
                         ]),
                     ),
                     (
@@ -743,7 +809,7 @@ impl Interpreter {
 
     pub fn load_std(&mut self, src: String, names: Vec<(Token, Option<Token>)>) {
         let parts: Vec<&str> = src.split("::").collect();
-        let mut std = StdFunc::new(Rc::clone(&self.env), self.project.test);
+        let mut std = StdFunc::new(Rc::clone(&self.env), self.project.clone());
 
         if parts[0] != "std" {
             raw(format!("standard library '{src}' doesn't exist").as_str());
@@ -763,6 +829,7 @@ impl Interpreter {
                             "core" => match parts[2] {
                                 "io" => std.load_core_io(),
                                 "test" => std.load_core_test(),
+                                "env" => std.load_core_env(),
                                 _ => raw(format!(
                                     "std module '{}::{}' doesn't exist",
                                     parts[1], parts[2]
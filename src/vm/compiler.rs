@@ -0,0 +1,373 @@
+// compiles resolved statements into bytecode, constructs without an instruction run on the tree walker
+use crate::{
+    ast::{format::FormatSpec, LiteralType, Statement, TokenType},
+    interpreter::expr::Expression,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    /// pushes the constant
+    Const(usize),
+    /// pushes the value of the variable expression
+    Get(usize),
+    /// assigns the value on the stack by the assignment expression, keeping it on the stack
+    Assign(usize),
+    /// defines the variable statement with the value on the stack
+    Define(usize),
+    Binary(TokenType),
+    Unary(TokenType),
+    /// collects the values on the stack into a vector
+    Vec(usize),
+    /// collects the values on the stack into a tuple
+    Tuple(usize),
+    /// joins the displayed values on the stack into a string
+    Concat(usize),
+    /// replaces the value on the stack with its text in the format
+    Format(FormatSpec),
+    /// checks the callee on the stack, callees that aren't functions or enums are called
+    /// by the tree walker with the arguments of the call expression, jumping to `end`
+    Callee {
+        expr: usize,
+        end: usize,
+    },
+    /// calls the callee with the arguments on the stack
+    Call(usize),
+    Pop,
+    Jump(usize),
+    /// pops the condition and jumps if it isn't truthy
+    JumpIfFalse(usize),
+    EnterScope,
+    ExitScope,
+    /// leaves the scopes entered by the chunk until that many are left
+    Unwind(usize),
+    /// decrements the counter of `loop n` on the stack, jumps once it reaches zero
+    Countdown(usize),
+    /// replaces the value on the stack with the state of a `for` loop
+    Iter,
+    /// enters the scope of the next iteration of the `for` statement, jumps once it's done
+    Next {
+        stmt: usize,
+        end: usize,
+    },
+    Return,
+    /// evaluates the expression on the tree walker
+    Eval(usize),
+    /// executes the statement on the tree walker, a `break` in it leaves
+    /// the innermost loop, given as its scopes and its end
+    Exec {
+        stmt: usize,
+        exit: Option<(usize, usize)>,
+    },
+}
+
+/// compiled statements with the pools their instructions refer to
+#[derive(Debug, Default, PartialEq)]
+pub struct Chunk {
+    pub code: Vec<Op>,
+    pub constants: Vec<LiteralType>,
+    pub stmts: Vec<Statement>,
+    pub exprs: Vec<Expression>,
+}
+
+impl Chunk {
+    /// one line per instruction, with the pool entry it refers to
+    pub fn disassemble(&self) -> Vec<String> {
+        self.code
+            .iter()
+            .enumerate()
+            .map(|(i, op)| {
+                let entry = match op {
+                    Op::Const(c) => self.constants[*c].to_string(),
+                    Op::Get(e) | Op::Assign(e) | Op::Eval(e) | Op::Callee { expr: e, .. } => {
+                        self.exprs[*e].to_string()
+                    }
+                    Op::Define(s) | Op::Next { stmt: s, .. } | Op::Exec { stmt: s, .. } => {
+                        describe(&self.stmts[*s])
+                    }
+                    _ => String::new(),
+                };
+                format!("{:04} {:<32} {}", i, format!("{:?}", op), entry)
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+}
+
+/// the kind of the statement and the names it declares
+fn describe(stmt: &Statement) -> String {
+    match stmt {
+        Statement::Var { names, .. } => {
+            let names: Vec<&str> = names.iter().map(|n| n.lexeme.as_str()).collect();
+            format!("let {}", names.join(", "))
+        }
+        Statement::Func { name, .. } => format!("func {}", name.lexeme),
+        Statement::For { iterator, .. } => format!("for {}", iterator.lexeme),
+        _ => format!("{:?}", stmt)
+            .split([' ', '{', '('])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase(),
+    }
+}
+
+pub struct Compiler {
+    chunk: Chunk,
+    /// scopes entered since the start of the chunk
+    depth: usize,
+    /// scopes of the enclosing loops and the jumps of their `break`s
+    loops: Vec<(usize, Vec<usize>)>,
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self {
+            chunk: Chunk::default(),
+            depth: 0,
+            loops: vec![],
+        }
+    }
+
+    pub fn compile(mut self, stmts: &[Statement]) -> Chunk {
+        stmts.iter().for_each(|s| self.stmt(s));
+        self.chunk
+    }
+
+    fn emit(&mut self, op: Op) -> usize {
+        self.chunk.code.push(op);
+        self.chunk.code.len() - 1
+    }
+
+    /// points the jump at the instruction to the next instruction
+    fn patch(&mut self, at: usize) {
+        let to = self.chunk.code.len();
+        match &mut self.chunk.code[at] {
+            Op::Jump(end)
+            | Op::JumpIfFalse(end)
+            | Op::Countdown(end)
+            | Op::Callee { end, .. }
+            | Op::Next { end, .. }
+            | Op::Exec {
+                exit: Some((_, end)),
+                ..
+            } => *end = to,
+            _ => {}
+        }
+    }
+
+    fn stmt_entry(&mut self, stmt: &Statement) -> usize {
+        self.chunk.stmts.push(stmt.clone());
+        self.chunk.stmts.len() - 1
+    }
+
+    fn expr_entry(&mut self, expr: &Expression) -> usize {
+        self.chunk.exprs.push(expr.clone());
+        self.chunk.exprs.len() - 1
+    }
+
+    fn constant(&mut self, value: &LiteralType) -> usize {
+        match self.chunk.constants.iter().position(|c| c == value) {
+            Some(c) => c,
+            None => {
+                self.chunk.constants.push(value.clone());
+                self.chunk.constants.len() - 1
+            }
+        }
+    }
+
+    /// statements in a new scope, like the resolver does
+    fn scoped(&mut self, stmts: &[Statement]) {
+        self.emit(Op::EnterScope);
+        self.depth += 1;
+        stmts.iter().for_each(|s| self.stmt(s));
+        self.depth -= 1;
+        self.emit(Op::ExitScope);
+    }
+
+    /// body of a loop starting at `start`, `break`s jump to the end of the loop
+    fn loop_body(&mut self, start: usize, body: impl FnOnce(&mut Self)) -> Vec<usize> {
+        self.loops.push((self.depth, vec![]));
+        body(self);
+        self.emit(Op::Jump(start));
+        self.loops.pop().map_or(vec![], |(_, breaks)| breaks)
+    }
+
+    fn stmt(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Expression { expr } => {
+                self.expr(expr);
+                self.emit(Op::Pop);
+            }
+            Statement::Block { stmts } => self.scoped(stmts),
+            Statement::Var {
+                value: Some(value), ..
+            } => {
+                self.expr(value);
+                let s = self.stmt_entry(stmt);
+                self.emit(Op::Define(s));
+            }
+            Statement::Return { expr } => {
+                self.expr(expr);
+                self.emit(Op::Return);
+            }
+            Statement::If {
+                cond,
+                body,
+                else_if_branches,
+                else_branch,
+            } => {
+                let mut ends = vec![];
+                let branches = [(cond, body)]
+                    .into_iter()
+                    .chain(else_if_branches.iter().map(|(c, b)| (c, b)));
+                for (cond, body) in branches {
+                    self.expr(cond);
+                    let next = self.emit(Op::JumpIfFalse(0));
+                    self.scoped(body);
+                    ends.push(self.emit(Op::Jump(0)));
+                    self.patch(next);
+                }
+                if let Some(body) = else_branch {
+                    self.scoped(body);
+                }
+                ends.into_iter().for_each(|e| self.patch(e));
+            }
+            Statement::Loop { iter, body } => {
+                if let Some(n) = iter {
+                    let c = self.constant(&LiteralType::Number(*n as f32));
+                    self.emit(Op::Const(c));
+                }
+                let start = self.chunk.code.len();
+                let done = iter.map(|_| self.emit(Op::Countdown(0)));
+                let breaks = self.loop_body(start, |c| c.scoped(body));
+                breaks.into_iter().chain(done).for_each(|b| self.patch(b));
+                if iter.is_some() {
+                    self.emit(Op::Pop);
+                }
+            }
+            Statement::While { cond, body } => {
+                let start = self.chunk.code.len();
+                self.expr(cond);
+                let done = self.emit(Op::JumpIfFalse(0));
+                let breaks = self.loop_body(start, |c| c.scoped(body));
+                breaks.into_iter().chain([done]).for_each(|b| self.patch(b));
+            }
+            Statement::For { expr, body, .. } => {
+                self.expr(expr);
+                self.emit(Op::Iter);
+                let s = self.stmt_entry(stmt);
+                let start = self.emit(Op::Next { stmt: s, end: 0 });
+                // the iteration scope is entered by `Next`
+                let breaks = self.loop_body(start, |c| {
+                    c.depth += 1;
+                    body.iter().for_each(|s| c.stmt(s));
+                    c.depth -= 1;
+                    c.emit(Op::ExitScope);
+                });
+                breaks
+                    .into_iter()
+                    .chain([start])
+                    .for_each(|b| self.patch(b));
+                self.emit(Op::Pop);
+                self.emit(Op::Pop);
+            }
+            Statement::Break { .. } => {
+                if let Some((depth, _)) = self.loops.last() {
+                    self.emit(Op::Unwind(*depth));
+                    let jump = self.emit(Op::Jump(0));
+                    self.loops.last_mut().unwrap().1.push(jump);
+                }
+            }
+            _ => {
+                let s = self.stmt_entry(stmt);
+                let exit = self.loops.last().map(|(depth, _)| (*depth, 0));
+                let exec = self.emit(Op::Exec { stmt: s, exit });
+                if let Some((_, breaks)) = self.loops.last_mut() {
+                    breaks.push(exec);
+                }
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Value { value, .. } => {
+                let c = self.constant(value);
+                self.emit(Op::Const(c));
+            }
+            Expression::Var { .. } => {
+                let e = self.expr_entry(expr);
+                self.emit(Op::Get(e));
+            }
+            Expression::Assign { value, .. } => {
+                self.expr(value);
+                let e = self.expr_entry(expr);
+                self.emit(Op::Assign(e));
+            }
+            Expression::Grouping { expression, .. } => self.expr(expression),
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                self.expr(left);
+                self.expr(right);
+                self.emit(Op::Binary(operator.token.clone()));
+            }
+            Expression::Unary { operator, left, .. } => {
+                self.expr(left);
+                self.emit(Op::Unary(operator.token.clone()));
+            }
+            Expression::Vec { items, .. } => {
+                items.iter().for_each(|i| self.expr(i));
+                self.emit(Op::Vec(items.len()));
+            }
+            Expression::Tuple { items, .. } => {
+                items.iter().for_each(|i| self.expr(i));
+                self.emit(Op::Tuple(items.len()));
+            }
+            Expression::Interpolation { parts, .. } => {
+                for (part, spec) in parts {
+                    self.expr(part);
+                    if let Some(spec) = spec {
+                        self.emit(Op::Format(spec.clone()));
+                    }
+                }
+                self.emit(Op::Concat(parts.len()));
+            }
+            Expression::If {
+                cond,
+                body,
+                else_branch,
+                ..
+            } => {
+                self.expr(cond);
+                let next = self.emit(Op::JumpIfFalse(0));
+                self.expr(body);
+                let end = self.emit(Op::Jump(0));
+                self.patch(next);
+                match else_branch {
+                    Some(e) => self.expr(e),
+                    None => {
+                        let c = self.constant(&LiteralType::Null);
+                        self.emit(Op::Const(c));
+                    }
+                }
+                self.patch(end);
+            }
+            Expression::Call { name, args, .. } => {
+                self.expr(name);
+                let e = self.expr_entry(expr);
+                let callee = self.emit(Op::Callee { expr: e, end: 0 });
+                args.iter().for_each(|a| self.expr(a));
+                self.emit(Op::Call(args.len()));
+                self.patch(callee);
+            }
+            _ => {
+                let e = self.expr_entry(expr);
+                self.emit(Op::Eval(e));
+            }
+        }
+    }
+}
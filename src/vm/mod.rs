@@ -0,0 +1,280 @@
+// runs compiled bytecode on a stack, selected with `--engine=vm`, scopes are the environments of the tree walker
+mod compiler;
+
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    mem::replace,
+    rc::Rc,
+};
+
+use crate::{
+    ast::{CallType, LiteralType, Statement},
+    interpreter::{
+        env::{Env, VarKind},
+        expr::{apply, binary, unary, variable, Expression},
+        Interpreter,
+    },
+};
+use compiler::Op;
+pub use compiler::{Chunk, Compiler};
+
+thread_local! {
+    /// if the bodies of called functions run on the vm too
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// runs the program on the vm, with the env of the interpreter as the global scope
+pub fn run(stmts: &[Statement], int: &mut Interpreter) {
+    ENABLED.with(|e| e.set(true));
+    let chunk = Compiler::new().compile(stmts);
+    Vm::new(&chunk, int, 0).run();
+}
+
+pub fn enabled() -> bool {
+    ENABLED.with(|e| e.get())
+}
+
+/// runs the body of a function in its prepared environment, returns what it returned,
+/// the body is compiled into the `chunk` of the function once
+pub fn call(
+    body: &[Statement],
+    chunk: &OnceCell<Chunk>,
+    env: Rc<RefCell<Env>>,
+) -> Option<LiteralType> {
+    let chunk = chunk.get_or_init(|| Compiler::new().compile(body));
+    let mut int = Interpreter::new_with_env(env, false, "", None, 1);
+    Vm::new(chunk, &mut int, 1).run()
+}
+
+struct Vm<'a> {
+    chunk: &'a Chunk,
+    /// executes what has no instructions, its env is the current scope
+    int: &'a mut Interpreter,
+    /// order of the statements executed by the interpreter
+    order: usize,
+    stack: Vec<LiteralType>,
+    /// environments the scopes entered by the chunk were entered from
+    scopes: Vec<Rc<RefCell<Env>>>,
+}
+
+impl<'a> Vm<'a> {
+    fn new(chunk: &'a Chunk, int: &'a mut Interpreter, order: usize) -> Self {
+        Self {
+            chunk,
+            int,
+            order,
+            stack: vec![],
+            scopes: vec![],
+        }
+    }
+
+    fn env(&self) -> Rc<RefCell<Env>> {
+        Rc::clone(&self.int.env)
+    }
+
+    fn pop(&mut self) -> LiteralType {
+        self.stack.pop().unwrap_or(LiteralType::Null)
+    }
+
+    fn pop_n(&mut self, n: usize) -> Vec<LiteralType> {
+        self.stack.split_off(self.stack.len().saturating_sub(n))
+    }
+
+    fn enter(&mut self) {
+        let env = Rc::new(RefCell::new(Env::enclose(&self.int.env)));
+        let prev = replace(&mut self.int.env, env);
+        self.scopes.push(prev);
+    }
+
+    /// leaves scopes until `depth` of them are left
+    fn unwind(&mut self, depth: usize) {
+        while self.scopes.len() > depth {
+            if let Some(env) = self.scopes.pop() {
+                self.int.env = env;
+            }
+        }
+    }
+
+    /// executes the chunk, returns the value of its `return`
+    fn run(&mut self) -> Option<LiteralType> {
+        let chunk = self.chunk;
+        let mut ip = 0;
+        while let Some(op) = chunk.code.get(ip) {
+            ip += 1;
+            match op {
+                Op::Const(c) => self.stack.push(chunk.constants[*c].clone()),
+                Op::Get(e) => {
+                    let value = match &chunk.exprs[*e] {
+                        expr @ Expression::Var { name, .. } => {
+                            variable(name, expr.id(), &self.int.env)
+                        }
+                        _ => LiteralType::Null,
+                    };
+                    self.stack.push(value);
+                }
+                Op::Assign(e) => {
+                    let value = self.pop();
+                    let value = chunk.exprs[*e].assign(value, self.env());
+                    self.stack.push(value);
+                }
+                Op::Define(s) => {
+                    let value = self.pop();
+                    self.int.define(&chunk.stmts[*s], Some(value));
+                }
+                Op::Binary(operator) => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.stack.push(binary(left, operator, right));
+                }
+                Op::Unary(operator) => {
+                    let left = self.pop();
+                    self.stack.push(unary(operator, left));
+                }
+                Op::Vec(n) => {
+                    let items = self.pop_n(*n);
+                    self.stack.push(LiteralType::Vec(items));
+                }
+                Op::Tuple(n) => {
+                    let items = self.pop_n(*n);
+                    self.stack.push(LiteralType::Tuple(items));
+                }
+                Op::Format(spec) => {
+                    let value = self.pop();
+                    self.stack.push(LiteralType::String(spec.apply(&value)));
+                }
+                Op::Concat(n) => {
+                    let parts = self.pop_n(*n);
+                    let value = parts.iter().map(|p| p.to_string()).collect();
+                    self.stack.push(LiteralType::String(value));
+                }
+                Op::Callee { expr, end } => {
+                    let expr = &chunk.exprs[*expr];
+                    let native = match (self.stack.last(), expr) {
+                        (Some(LiteralType::Func(_) | LiteralType::DeclrFunc(_)), _) => true,
+                        // `Color::Red(1)`
+                        (
+                            Some(LiteralType::Enum { .. }),
+                            Expression::Call {
+                                name,
+                                call_type: CallType::Func,
+                                ..
+                            },
+                        ) => matches!(**name, Expression::Path { .. }),
+                        _ => false,
+                    };
+                    if !native {
+                        let callee = self.pop();
+                        let value = expr.call(callee, self.env());
+                        self.stack.push(value);
+                        ip = *end;
+                    }
+                }
+                Op::Call(n) => {
+                    let args = self.pop_n(*n);
+                    let value = match self.pop() {
+                        LiteralType::Enum { parent, name, .. } => LiteralType::Enum {
+                            parent,
+                            name,
+                            value: args.into_iter().next().map(Box::new),
+                        },
+                        callee => apply(callee, args, self.env()),
+                    };
+                    self.stack.push(value);
+                }
+                Op::Pop => {
+                    self.pop();
+                }
+                Op::Jump(to) => ip = *to,
+                Op::JumpIfFalse(to) => {
+                    if !self.pop().is_truthy() {
+                        ip = *to;
+                    }
+                }
+                Op::EnterScope => self.enter(),
+                Op::ExitScope => {
+                    let depth = self.scopes.len().saturating_sub(1);
+                    self.unwind(depth);
+                }
+                Op::Unwind(depth) => self.unwind(*depth),
+                Op::Countdown(end) => match self.stack.last_mut() {
+                    Some(LiteralType::Number(n)) if *n > 0.0 => *n -= 1.0,
+                    _ => ip = *end,
+                },
+                Op::Iter => {
+                    let items = match self.pop() {
+                        LiteralType::Vec(items) => items,
+                        _ => vec![],
+                    };
+                    self.stack.push(LiteralType::Vec(items));
+                    self.stack.push(LiteralType::Number(0.0));
+                }
+                Op::Next { stmt, end } => {
+                    let index = match self.stack.last() {
+                        Some(LiteralType::Number(i)) => *i as usize,
+                        _ => 0,
+                    };
+                    let item = match self.stack.iter().nth_back(1) {
+                        Some(LiteralType::Vec(items)) => items.get(index).cloned(),
+                        _ => None,
+                    };
+                    match (item, &chunk.stmts[*stmt]) {
+                        (
+                            Some(item),
+                            Statement::For {
+                                iterator,
+                                index: name,
+                                ..
+                            },
+                        ) => {
+                            self.stack.pop();
+                            self.stack.push(LiteralType::Number((index + 1) as f32));
+                            // every iteration gets its own scope with the arguments
+                            self.enter();
+                            let index = LiteralType::Number(index as f32);
+                            let args = [(Some(iterator), item), (name.as_ref(), index)];
+                            for (slot, (token, value)) in args.into_iter().enumerate() {
+                                if let Some(token) = token {
+                                    self.int.env.borrow_mut().define_var(
+                                        token.lexeme.clone(),
+                                        Some(slot),
+                                        value,
+                                        VarKind {
+                                            is_pub: false,
+                                            is_mut: false,
+                                            is_func: false,
+                                            value_type: token.clone(),
+                                        },
+                                    );
+                                }
+                            }
+                        }
+                        _ => ip = *end,
+                    }
+                }
+                Op::Return => {
+                    let value = self.pop();
+                    self.unwind(0);
+                    return Some(value);
+                }
+                Op::Eval(e) => {
+                    let value = chunk.exprs[*e].eval(self.env());
+                    self.stack.push(value);
+                }
+                Op::Exec { stmt, exit } => {
+                    self.int.interpret(vec![&chunk.stmts[*stmt]], self.order);
+                    if let Some(value) = self.int.signal("return") {
+                        self.unwind(0);
+                        return Some(value);
+                    }
+                    if let Some((depth, end)) = exit {
+                        if self.int.signal("break").is_some() {
+                            self.unwind(*depth);
+                            ip = *end;
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,177 @@
+// commands running over every package of a workspace: `absurd test`, `absurd check` and `absurd fmt`
+use coloredpp::Colorize;
+use std::{
+    env::{current_dir, current_exe},
+    fs,
+    path::{Path, PathBuf},
+    process::{exit, Command, Output},
+};
+
+use crate::{
+    errors::raw,
+    formatter,
+    manifest::{Project, MANIFEST},
+    package::{self, MODS},
+};
+
+/// the members at the root of a workspace, otherwise the current package
+fn packages(project: &Project) -> Vec<PathBuf> {
+    match (&project.root, project.workspace == project.root) {
+        (None, _) => vec![current_dir().expect("failed to get current directory")],
+        (Some(_), true) => package::manifests(project)
+            .into_iter()
+            .map(|(dir, _)| dir)
+            .collect(),
+        (Some(root), false) => vec![root.clone()],
+    }
+}
+
+/// `.abs` files of the package in `dir`, without `mods/`, hidden directories and nested packages
+fn sources(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    collect(dir, &mut files);
+    files.sort();
+    files
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != MODS && !path.join(MANIFEST).is_file() {
+                collect(&path, files);
+            }
+        } else if name.ends_with(".abs") {
+            files.push(path);
+        }
+    }
+}
+
+fn relative(path: &Path, dir: &Path) -> String {
+    path.strip_prefix(dir).unwrap_or(path).display().to_string()
+}
+
+/// runs this executable in the `dir`, the interpreter exits on errors
+fn absurd(dir: &Path, args: &[&str]) -> Output {
+    let exe = current_exe().expect("failed to find the absurd executable");
+    match Command::new(exe).args(args).current_dir(dir).output() {
+        Ok(o) => o,
+        Err(e) => {
+            raw(format!("failed to run absurd: {}", e).as_str());
+            exit(1);
+        }
+    }
+}
+
+fn header(action: &str, dir: &Path) {
+    let name = dir.file_name().map_or(dir.display().to_string(), |n| {
+        n.to_string_lossy().to_string()
+    });
+    println!("{} {}", action.yellow(), name.blue());
+}
+
+/// `absurd test`, runs the files in `tests/` of every package in test mode
+pub fn test(project: &Project) {
+    let (mut passed, mut failed, mut errors) = (0, 0, 0);
+    for dir in packages(project) {
+        let files = sources(&dir.join("tests"));
+        if files.is_empty() {
+            continue;
+        }
+        header("testing", &dir);
+        for file in files {
+            let path = relative(&file, &dir);
+            println!(" {}", path);
+            let output = absurd(&dir, &["run", &path, "-t"]);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            print!("{}", stdout);
+            passed += stdout.matches("success: test").count();
+            failed += stdout.matches("fail: test").count();
+            if !stderr.trim().is_empty() {
+                eprint!("{}", stderr);
+                errors += 1;
+            }
+        }
+    }
+    let summary = format!("{} passed, {} failed", passed, failed);
+    if failed > 0 || errors > 0 {
+        match errors {
+            0 => println!("{}", summary.red()),
+            _ => println!(
+                "{}",
+                format!("{}, {} files with errors", summary, errors).red()
+            ),
+        }
+        exit(1);
+    }
+    println!("{}", summary.green());
+}
+
+/// `absurd check`, parses and resolves every file without running it
+pub fn check(project: &Project) {
+    let (mut checked, mut errors) = (0, 0);
+    for dir in packages(project) {
+        header("checking", &dir);
+        for file in sources(&dir) {
+            let path = relative(&file, &dir);
+            let output = absurd(&dir, &["dump", &path, "resolved"]);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            checked += 1;
+            if !stderr.trim().is_empty() {
+                println!(" {}", path.red());
+                eprint!("{}", stderr);
+                errors += 1;
+            }
+        }
+    }
+    if errors > 0 {
+        println!(
+            "{}",
+            format!("{} of {} files have errors", errors, checked).red()
+        );
+        exit(1);
+    }
+    println!("{}", format!("checked {} files", checked).green());
+}
+
+/// `absurd fmt [--check]`, formats every file or lists the unformatted ones
+pub fn fmt(project: &Project, check: bool) {
+    let mut changed = 0;
+    for dir in packages(project) {
+        for file in sources(&dir) {
+            let src = match fs::read_to_string(&file) {
+                Ok(s) => s,
+                Err(e) => {
+                    raw(format!("failed to read '{}': {}", file.display(), e).as_str());
+                    exit(1);
+                }
+            };
+            let formatted = formatter::format(&src);
+            if formatted == src {
+                continue;
+            }
+            changed += 1;
+            let path = relative(&file, &dir);
+            if check {
+                println!("{} {}", "unformatted".yellow(), path);
+            } else if let Err(e) = fs::write(&file, formatted) {
+                raw(format!("failed to write '{}': {}", file.display(), e).as_str());
+                exit(1);
+            } else {
+                println!("{} {}", "formatted".green(), path);
+            }
+        }
+    }
+    if check && changed > 0 {
+        exit(1);
+    }
+    if changed == 0 {
+        println!("{}", "all files are formatted".green());
+    }
+}
@@ -0,0 +1,90 @@
+// runs scripts twice, the second run loads them from `.absurd-cache`
+mod common;
+
+use common::{engines, scripts};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// the cache files in the `dir`
+fn cached(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir.join(".absurd-cache"))
+        .map(|files| files.map(|f| f.unwrap().path()).collect())
+        .unwrap_or_default()
+}
+
+const SCALE: &str = "func pub scale(n: number) -> number {
+    let twice = n * 2;
+    if twice > 4 {
+        let more = twice + 1;
+        return more;
+    }
+    return twice;
+}
+";
+
+#[test]
+fn caches_scripts_and_their_modules() {
+    let dir = scripts(
+        "modules",
+        &[
+            (
+                "main.abs",
+                "mod \"scale.abs\" as c;\nuse scale from c;\nlet mut total = 0;\nfor n in [1, 2, 3] {\n    total = total + scale(n);\n}\nprint(total);\n",
+            ),
+            ("scale.abs", SCALE),
+        ],
+    );
+    let first = engines(&dir, &["main.abs"]);
+    assert_eq!(first, "13\n");
+    let files = cached(&dir);
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().all(|f| f.extension().unwrap() == "absc"));
+    assert_eq!(engines(&dir, &["main.abs"]), first);
+    assert_eq!(cached(&dir).len(), 2);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn parses_changed_scripts_next_to_cached_modules() {
+    let dir = scripts(
+        "changed",
+        &[
+            (
+                "main.abs",
+                "mod \"scale.abs\" as c;\nuse scale from c;\nprint(scale(1));\n",
+            ),
+            ("scale.abs", SCALE),
+        ],
+    );
+    assert_eq!(engines(&dir, &["main.abs"]), "2\n");
+    fs::write(
+        dir.join("main.abs"),
+        "mod \"scale.abs\" as c;\nuse scale from c;\nlet first = scale(2);\nif true {\n    let second = scale(3);\n    print(first);\n    print(second);\n}\n",
+    )
+    .unwrap();
+    assert_eq!(engines(&dir, &["main.abs"]), "4\n7\n");
+    assert_eq!(engines(&dir, &["main.abs"]), "4\n7\n");
+    assert_eq!(cached(&dir).len(), 3);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn ignores_broken_cache_files() {
+    let dir = scripts("broken", &[("main.abs", "let x = [1, 2];\nprint(x);\n")]);
+    let first = engines(&dir, &["main.abs"]);
+    // the lengths of the last one are too large to add to the position
+    let huge = [&[0xff; 9][..], &[0x01]].concat();
+    let broken = [
+        b"ABSC\xff\xff".to_vec(),
+        [&b"ABSC\x00\x01"[..], &huge, &huge].concat(),
+    ];
+    for bytes in broken {
+        for file in cached(&dir) {
+            fs::write(file, &bytes).unwrap();
+        }
+        assert_eq!(engines(&dir, &["main.abs"]), first);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,54 @@
+// starts scripts from a file with a shebang, from stdin and as an expression
+mod common;
+
+use common::{absurd, scripts};
+use std::{
+    fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+#[test]
+fn runs_scripts_with_a_shebang() {
+    let dir = scripts(
+        "shebang",
+        &[(
+            "script.abs",
+            "#!/usr/bin/env absurd\nuse args from \"std::core::env\";\nprint(args());\n",
+        )],
+    );
+    assert_eq!(absurd(&dir, &["script.abs", "a", "-x"]), "[a, -x]\n");
+    assert_eq!(absurd(&dir, &["script.abs", "--", "info"]), "[info]\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn runs_code_from_stdin() {
+    let dir = scripts("stdin", &[]);
+    let mut child = Command::new(env!("CARGO_BIN_EXE_absurd"))
+        .args(["run", "-"])
+        .current_dir(&dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"let x = 2;\nprint(x * 3);\n")
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "6\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn evaluates_expressions() {
+    let dir = scripts("eval", &[]);
+    assert_eq!(absurd(&dir, &["eval", "1 + 2"]), "3\n");
+    let out = absurd(&dir, &["eval"]);
+    assert!(out.contains("expected an expression"), "{}", out);
+    assert!(!out.contains("panicked"), "{}", out);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,58 @@
+// helpers shared by the integration tests, each test file includes them with `mod common;`
+#![allow(dead_code)]
+use std::{
+    env::temp_dir,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// runs absurd with the `args` in the `dir` and returns what it printed
+pub fn absurd(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_absurd"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    )
+}
+
+/// runs absurd on both engines, they have to print the same
+pub fn engines(dir: &Path, args: &[&str]) -> String {
+    let outputs: Vec<String> = ["--engine=tree", "--engine=vm"]
+        .iter()
+        .map(|engine| absurd(dir, &[&[*engine], args].concat()))
+        .collect();
+    assert_eq!(outputs[0], outputs[1], "the engines disagree");
+    outputs[0].clone()
+}
+
+/// a fresh directory with the `files`, as `(path, contents)`
+pub fn scripts(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = temp_dir().join(format!(
+        "absurd-{}-{}-{}",
+        env!("CARGO_CRATE_NAME"),
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (path, contents) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+/// runs the `src` as `main.abs` on both engines and returns what it printed
+pub fn run(name: &str, src: &str) -> String {
+    let dir = scripts(name, &[("main.abs", src)]);
+    let out = engines(&dir, &["main.abs"]);
+    fs::remove_dir_all(&dir).unwrap();
+    out
+}
@@ -0,0 +1,236 @@
+// drives `absurd dap` with a scripted client
+use std::{
+    env::temp_dir,
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+const PROGRAM: &str = "let x = 5;
+func add(a: number, b: number) -> number {
+    let c = a + b;
+    return c;
+}
+let y = add(x, 2);
+print(y);
+";
+
+struct Client {
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    seq: usize,
+}
+
+impl Client {
+    fn send(&mut self, command: &str, arguments: &str) {
+        self.seq += 1;
+        let msg = format!(
+            r#"{{"seq":{},"type":"request","command":"{}","arguments":{}}}"#,
+            self.seq, command, arguments
+        );
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", msg.len(), msg).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn read(&mut self) -> String {
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            assert_ne!(
+                self.stdout.read_line(&mut line).unwrap(),
+                0,
+                "adapter exited"
+            );
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(l) = line.strip_prefix("Content-Length:") {
+                len = l.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; len];
+        self.stdout.read_exact(&mut body).unwrap();
+        String::from_utf8(body).unwrap()
+    }
+
+    /// reads messages until one contains `needle`, returns it
+    fn expect(&mut self, needle: &str) -> String {
+        for _ in 0..50 {
+            let msg = self.read();
+            if msg.contains(needle) {
+                return msg;
+            }
+        }
+        panic!("no message containing {}", needle);
+    }
+}
+
+/// starts the adapter and launches PROGRAM, written to a file named after the `test`
+fn launch(test: &str) -> (Child, Client, PathBuf, String) {
+    let file = temp_dir().join(format!("absurd_dap_{}_{}.abs", test, std::process::id()));
+    fs::write(&file, PROGRAM).unwrap();
+    let path = file.to_string_lossy().replace('\\', "/");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_absurd"))
+        .arg("dap")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut client = Client {
+        stdin: child.stdin.take().unwrap(),
+        stdout: BufReader::new(child.stdout.take().unwrap()),
+        seq: 0,
+    };
+
+    client.send("initialize", r#"{"adapterID":"absurd"}"#);
+    client.expect(r#""command":"initialize""#);
+    client.expect(r#""event":"initialized""#);
+    client.send("launch", &format!(r#"{{"program":"{}"}}"#, path));
+    client.expect(r#""command":"launch""#);
+    (child, client, file, path)
+}
+
+#[test]
+fn breakpoints_stack_and_variables() {
+    let (mut child, mut client, file, path) = launch("main");
+    client.send(
+        "setBreakpoints",
+        &format!(
+            r#"{{"source":{{"path":"{}"}},"breakpoints":[{{"line":3}}]}}"#,
+            path
+        ),
+    );
+    let msg = client.expect(r#""command":"setBreakpoints""#);
+    assert!(msg.contains(r#""verified":true"#), "{}", msg);
+    client.send("configurationDone", "{}");
+
+    let msg = client.expect(r#""event":"stopped""#);
+    assert!(msg.contains(r#""reason":"breakpoint""#), "{}", msg);
+
+    client.send("stackTrace", r#"{"threadId":1}"#);
+    let msg = client.expect(r#""command":"stackTrace""#);
+    assert!(msg.contains(r#""name":"add""#), "{}", msg);
+    assert!(msg.contains(r#""line":3"#), "{}", msg);
+    assert!(msg.contains(r#""name":"main""#), "{}", msg);
+
+    client.send("scopes", r#"{"frameId":0}"#);
+    let msg = client.expect(r#""command":"scopes""#);
+    assert!(msg.contains(r#""name":"Locals""#), "{}", msg);
+    assert!(msg.contains(r#""name":"Globals""#), "{}", msg);
+
+    client.send("variables", r#"{"variablesReference":1}"#);
+    let msg = client.expect(r#""command":"variables""#);
+    assert!(msg.contains(r#""name":"a","value":"5""#), "{}", msg);
+    assert!(msg.contains(r#""name":"b","value":"2""#), "{}", msg);
+
+    client.send("evaluate", r#"{"expression":"a * b","frameId":0}"#);
+    let msg = client.expect(r#""command":"evaluate""#);
+    assert!(msg.contains(r#""result":"10""#), "{}", msg);
+
+    client.send("next", r#"{"threadId":1}"#);
+    let msg = client.expect(r#""event":"stopped""#);
+    assert!(msg.contains(r#""reason":"step""#), "{}", msg);
+
+    // continue until the program ends, collecting its output
+    let mut output = String::new();
+    client.send("continue", r#"{"threadId":1}"#);
+    loop {
+        let msg = client.read();
+        if msg.contains(r#""event":"output""#) {
+            output.push_str(&msg);
+        } else if msg.contains(r#""event":"stopped""#) {
+            client.send("continue", r#"{"threadId":1}"#);
+        } else if msg.contains(r#""event":"terminated""#) {
+            break;
+        }
+    }
+    assert!(output.contains(r#""output":"7\n""#), "{}", output);
+    client.send("disconnect", "{}");
+    client.expect(r#""command":"disconnect""#);
+
+    assert!(child.wait().unwrap().success());
+    fs::remove_file(file).ok();
+}
+
+#[test]
+fn ignores_breakpoints_of_other_sources() {
+    let (mut child, mut client, file, path) = launch("sources");
+    let other = temp_dir().join(format!("absurd_dap_other_{}.abs", std::process::id()));
+    fs::write(&other, PROGRAM).unwrap();
+    let other_path = other.to_string_lossy().replace('\\', "/");
+
+    client.send(
+        "setBreakpoints",
+        &format!(
+            r#"{{"source":{{"path":"{}"}},"breakpoints":[{{"line":3}}]}}"#,
+            other_path
+        ),
+    );
+    let msg = client.expect(r#""command":"setBreakpoints""#);
+    assert!(msg.contains(r#""verified":false"#), "{}", msg);
+    client.send(
+        "setBreakpoints",
+        &format!(
+            r#"{{"source":{{"path":"{}"}},"breakpoints":[{{"line":7}}]}}"#,
+            path
+        ),
+    );
+    let msg = client.expect(r#""command":"setBreakpoints""#);
+    assert!(msg.contains(r#""verified":true"#), "{}", msg);
+    client.send("configurationDone", "{}");
+
+    client.expect(r#""event":"stopped""#);
+    client.send("stackTrace", r#"{"threadId":1}"#);
+    let msg = client.expect(r#""command":"stackTrace""#);
+    assert!(msg.contains(r#""line":7"#), "{}", msg);
+
+    // breakpoints set while paused are only taken from the program too
+    client.send(
+        "setBreakpoints",
+        &format!(
+            r#"{{"source":{{"path":"{}"}},"breakpoints":[{{"line":3}}]}}"#,
+            other_path
+        ),
+    );
+    let msg = client.expect(r#""command":"setBreakpoints""#);
+    assert!(msg.contains(r#""verified":false"#), "{}", msg);
+    client.send("disconnect", "{}");
+    client.expect(r#""command":"disconnect""#);
+
+    assert!(child.wait().unwrap().success());
+    fs::remove_file(file).ok();
+    fs::remove_file(other).ok();
+}
+
+#[test]
+fn answers_bad_expressions_with_errors() {
+    let (mut child, mut client, file, path) = launch("evaluate");
+    client.send(
+        "setBreakpoints",
+        &format!(
+            r#"{{"source":{{"path":"{}"}},"breakpoints":[{{"line":7}}]}}"#,
+            path
+        ),
+    );
+    client.expect(r#""command":"setBreakpoints""#);
+    client.send("configurationDone", "{}");
+    client.expect(r#""event":"stopped""#);
+
+    client.send("evaluate", r#"{"expression":"y +","frameId":0}"#);
+    let msg = client.expect(r#""command":"evaluate""#);
+    assert!(msg.contains(r#""success":false"#), "{}", msg);
+    assert!(msg.contains("syntax error"), "{}", msg);
+
+    // the adapter is still serving the same session
+    client.send("evaluate", r#"{"expression":"y * 2","frameId":0}"#);
+    let msg = client.expect(r#""command":"evaluate""#);
+    assert!(msg.contains(r#""result":"14""#), "{}", msg);
+    client.send("disconnect", "{}");
+    client.expect(r#""command":"disconnect""#);
+
+    assert!(child.wait().unwrap().success());
+    fs::remove_file(file).ok();
+}
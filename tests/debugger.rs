@@ -0,0 +1,137 @@
+// drives `absurd debug` with commands on stdin and checks where it stops
+mod common;
+
+use common::scripts;
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// runs absurd with the `args` in the `dir`, typing the `commands`, returns what it printed
+fn debug(dir: &Path, args: &[&str], commands: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_absurd"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(commands.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    String::from_utf8_lossy(&out.stdout).to_string()
+}
+
+/// debugs `main.abs` in the `dir` with the `commands`, returns the lines it stopped at
+fn stops(dir: &Path, commands: &str) -> Vec<String> {
+    let out = debug(dir, &["debug", "main.abs"], commands);
+    // every stop prints `main.abs:<line> | <source>`
+    out.split("main.abs:")
+        .skip(1)
+        .map(|l| {
+            l.split(|c: char| !c.is_ascii_digit())
+                .next()
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn stops_at_every_iteration_of_a_loop() {
+    let dir = scripts(
+        "loop",
+        &[(
+            "main.abs",
+            "let mut total = 0;\nfor n in [1, 2, 3] {\n    total = total + n;\n}\nprint(total);\n",
+        )],
+    );
+    assert_eq!(stops(&dir, "b 3\nc\nc\nc\nc\n"), ["1", "3", "3", "3"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn stops_at_mod_sh_break_and_literal_statements() {
+    let dir = scripts(
+        "statements",
+        &[
+            (
+                "main.abs",
+                "let a = 1;
+mod \"lib.abs\" as lib;
+sh \"echo\";
+let mut i = 0;
+loop {
+    i = i + 1;
+    if i > 1 {
+        break;
+    }
+}
+\"done\";
+",
+            ),
+            ("lib.abs", "let pub b = 2;\n"),
+        ],
+    );
+    let commands = "b 2\nb 3\nb 8\nb 11\nc\nc\nc\nc\nc\n";
+    assert_eq!(stops(&dir, commands), ["1", "2", "3", "8", "11"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn keeps_breakpoints_in_the_debugged_file() {
+    let dir = scripts(
+        "files",
+        &[
+            (
+                "main.abs",
+                "mod \"lib.abs\" as lib;\nuse f from lib;\nprint(f(1));\n",
+            ),
+            (
+                "lib.abs",
+                "func pub f(n: any) -> any {\n    let m = n + 1;\n\n\n    return m;\n}\n",
+            ),
+        ],
+    );
+    assert_eq!(stops(&dir, "b 5\nc\nc\n"), ["1"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn recovers_from_bad_expressions() {
+    let dir = scripts(
+        "expressions",
+        &[("main.abs", "let a = 2;\nprint(a + 1);\n")],
+    );
+    let out = debug(&dir, &["debug", "main.abs"], "b 2\nc\np 1 +\np a * 3\nc\n");
+    assert!(out.contains("syntax error"), "{}", out);
+    assert!(out.contains("6\n"), "{}", out);
+    assert!(out.contains("3\n"), "{}", out);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn debugs_on_the_tree_walker() {
+    let dir = scripts(
+        "engine",
+        &[(
+            "main.abs",
+            "let mut total = 0;\nfor n in [1, 2] {\n    total = total + n;\n}\nprint(total);\n",
+        )],
+    );
+    let out = debug(
+        &dir,
+        &["--engine=vm", "debug", "main.abs"],
+        "b 3\nc\nc\nc\n",
+    );
+    assert!(out.contains("--engine=vm is ignored"), "{}", out);
+    assert_eq!(out.matches("main.abs:3").count(), 2, "{}", out);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,115 @@
+// dumps the tokens, syntax tree and resolved locals of scripts with `absurd dump`
+mod common;
+
+use common::{absurd, scripts};
+use std::fs;
+
+const SCRIPT: &str = "let x = 1;
+func add(a: number) -> number {
+    let y = a + x;
+    return y;
+}
+print(add(2));
+";
+
+#[test]
+fn dumps_tokens() {
+    let dir = scripts("tokens", &[("main.abs", SCRIPT)]);
+    let out = absurd(&dir, &["dump", "main.abs", "tokens"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "1:1-4      Let          \"let\"");
+    assert_eq!(lines[3], "1:9-10     NumLit       \"1\"  1");
+    assert!(
+        lines.contains(&"2:6-9      Ident        \"add\""),
+        "{}",
+        out
+    );
+
+    let out = absurd(&dir, &["--json", "dump", "main.abs", "tokens"]);
+    assert!(
+        out.starts_with("[{\"kind\":\"Let\",\"lexeme\":\"let\",\"value\":null,\"line\":1,\"start\":1,\"end\":4},"),
+        "{}",
+        out
+    );
+    assert!(
+        out.contains("{\"kind\":\"NumLit\",\"lexeme\":\"1\",\"value\":1,"),
+        "{}",
+        out
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dumps_the_syntax_tree() {
+    let dir = scripts("ast", &[("main.abs", SCRIPT)]);
+    // the syntax tree is the default stage
+    let out = absurd(&dir, &["dump", "main.abs"]);
+    assert_eq!(out, absurd(&dir, &["dump", "main.abs", "ast"]));
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(
+        lines[0].starts_with("Let id=") && lines[0].contains("names=[\"x\"]"),
+        "{}",
+        out
+    );
+    let func = lines
+        .iter()
+        .position(|l| l.starts_with("FuncStmt"))
+        .unwrap();
+    assert!(
+        lines[func].contains("name=\"add\" params=[\"a: number\"] type=\"number\""),
+        "{}",
+        out
+    );
+    assert_eq!(lines[func + 1], "  body:");
+    assert!(lines[func + 2].starts_with("    Let id="), "{}", out);
+    assert!(out.contains("Binary id="), "{}", out);
+
+    let out = absurd(&dir, &["--json", "dump", "main.abs"]);
+    assert!(out.starts_with("[{\"kind\":\"Let\",\"id\":"), "{}", out);
+    assert!(out.contains("\"kind\":\"FuncStmt\""), "{}", out);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dumps_resolved_locals() {
+    let dir = scripts("resolved", &[("main.abs", SCRIPT)]);
+    let out = absurd(&dir, &["dump", "main.abs", "resolved"]);
+    let locals: Vec<&str> = out
+        .lines()
+        .map(|l| l.split_once(" name=").map_or(l, |(_, rest)| rest))
+        .collect();
+    // `x` and `add` are global, so only the names inside of the function are resolved
+    assert_eq!(
+        locals,
+        [
+            "\"y\" depth=0 slot=1",
+            "\"a\" depth=0 slot=0",
+            "\"y\" depth=0 slot=1"
+        ],
+        "{}",
+        out
+    );
+
+    let out = absurd(&dir, &["--json", "dump", "main.abs", "resolved"]);
+    assert!(
+        out.contains("\"kind\":\"Local\"")
+            && out.contains("\"name\":\"a\",\"depth\":0,\"slot\":0}"),
+        "{}",
+        out
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn reports_unknown_stages_and_missing_files() {
+    let dir = scripts("dump-errors", &[("main.abs", SCRIPT)]);
+    let out = absurd(&dir, &["dump", "main.abs", "bytes"]);
+    assert!(
+        out.contains("unknown dump stage 'bytes', expected tokens, ast, resolved or bytecode"),
+        "{}",
+        out
+    );
+    let out = absurd(&dir, &["dump", "missing.abs", "tokens"]);
+    assert!(out.contains("failed to open file 'missing.abs'"), "{}", out);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,94 @@
+// runs scripts on the tree walker and on the bytecode vm
+mod common;
+
+use common::{absurd, run, scripts};
+use std::fs;
+
+#[test]
+fn runs_loops_the_same_on_both_engines() {
+    let src = "let mut i = 0;
+loop 5 {
+    i = i + 1;
+    if i == 3 {
+        break;
+    }
+}
+print(i);
+let mut w = 10;
+while w > 0 {
+    w = w - 3;
+}
+print(w);
+for x, idx in [4, 5, 6] {
+    print(x + idx);
+}
+for x in [1, 2, 3, 4] {
+    if x == 3 {
+        break;
+    }
+    print(x);
+}
+enum Color { Red, Green }
+loop {
+    match Color::Green {
+        Color::Green => {
+            print(\"matched\");
+            break;
+        }
+    }
+}
+func first(xs: any) -> any {
+    for x in xs {
+        if x > 2 {
+            return x;
+        }
+    }
+    return 0;
+}
+print(first([1, 5, 3]));
+";
+    let out = run("loops", src);
+    assert_eq!(out, "3\n-2\n4\n6\n8\n1\n2\nmatched\n5\n");
+}
+
+#[test]
+fn dumps_the_bytecode() {
+    let dir = scripts("bytecode", &[("main.abs", "let x = 1 + 2;\nprint(x);\n")]);
+    let out = absurd(&dir, &["dump", "main.abs", "bytecode"]);
+    fs::remove_dir_all(&dir).unwrap();
+    let ops: Vec<&str> = out
+        .lines()
+        .map(|l| l.split_whitespace().nth(1).unwrap_or(""))
+        .collect();
+    assert_eq!(
+        ops,
+        [
+            "Const(0)",
+            "Const(1)",
+            "Binary(Plus)",
+            "Define(0)",
+            "Get(0)",
+            "Callee",
+            "Get(2)",
+            "Call(1)",
+            "Pop"
+        ]
+    );
+}
+
+#[test]
+fn takes_the_engine_before_or_after_run() {
+    let dir = scripts("flags", &[("main.abs", "print(1 + 1);\n")]);
+    for engine in ["--engine=tree", "--engine=vm"] {
+        for args in [
+            [engine, "run", "main.abs"],
+            ["run", engine, "main.abs"],
+            ["run", "main.abs", engine],
+        ] {
+            assert_eq!(absurd(&dir, &args), "2\n", "{:?}", args);
+        }
+    }
+    let out = absurd(&dir, &["run", "--engine=nope", "main.abs"]);
+    assert!(out.contains("unknown engine 'nope'"), "{}", out);
+    fs::remove_dir_all(&dir).unwrap();
+}
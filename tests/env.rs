@@ -0,0 +1,35 @@
+// runs scripts reading their arguments and the environment of the process
+mod common;
+
+use common::{engines, scripts};
+use std::fs;
+
+const SHOW: &str = "func show() -> void {
+    use args, set_var, var from \"std::core::env\";
+    print(args());
+    set_var(\"ABSURD_ENV_TEST\", \"set\");
+    print(var(\"ABSURD_ENV_TEST\"));
+    let all = |n: number| any: args();
+    print(all(0));
+}
+show();
+";
+
+#[test]
+fn reads_script_args_inside_functions() {
+    let dir = scripts("args", &[("main.abs", SHOW)]);
+    let out = engines(&dir, &["run", "main.abs", "--", "-x", "--verbose"]);
+    assert_eq!(out, "[-x, --verbose]\nset\n[-x, --verbose]\n");
+    let out = engines(&dir, &["run", "main.abs", "--", "info"]);
+    assert_eq!(out, "[info]\nset\n[info]\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn disables_side_effects_inside_functions() {
+    let dir = scripts("side-effects", &[("main.abs", SHOW)]);
+    let out = engines(&dir, &["-s", "run", "main.abs", "--", "a"]);
+    assert!(out.contains("side effects are disabled"), "{}", out);
+    assert!(!out.contains("set"), "{}", out);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,118 @@
+// reports invalid manifests with the location of what is wrong in them
+mod common;
+
+use common::{absurd, scripts};
+use std::fs;
+
+/// runs a script of a project with the `manifest` and returns what it printed
+fn load(name: &str, manifest: &str) -> String {
+    let dir = scripts(
+        name,
+        &[("project.toml", manifest), ("main.abs", "print(1);\n")],
+    );
+    let out = absurd(&dir, &["run", "main.abs"]);
+    fs::remove_dir_all(&dir).unwrap();
+    out
+}
+
+/// a manifest of the `app` package with the `deps`
+fn with_deps(deps: &str) -> String {
+    format!(
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\n{}\n",
+        deps
+    )
+}
+
+#[test]
+fn reports_unknown_dependency_keys() {
+    let out = load(
+        "unknown-key",
+        &with_deps("foo = { git = \"https://example.com/foo\", tagg = \"v1\" }"),
+    );
+    assert!(out.contains("line 6, column 42"), "{}", out);
+    assert!(
+        out.contains(
+            "unknown field `tagg`, expected one of `version`, `path`, `git`, `rev`, `registry`"
+        ),
+        "{}",
+        out
+    );
+    assert!(!out.contains("did not match any variant"), "{}", out);
+
+    // keys of dependency tables are reported on their own line
+    let out = load(
+        "unknown-table-key",
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dev-dependencies.bar]\ngit = \"https://example.com/bar\"\nbranch = \"main\"\n",
+    );
+    assert!(
+        out.contains("line 7, column 1") && out.contains("unknown field `branch`"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn reports_dependencies_of_the_wrong_type() {
+    let out = load("wrong-type", &with_deps("foo = 1"));
+    assert!(out.contains("line 6, column 7"), "{}", out);
+    assert!(
+        out.contains("expected a version requirement or a table with 'version', 'path' or 'git'"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn reports_invalid_dependencies() {
+    for (dep, msg) in [
+        (
+            "foo = \"nope\"",
+            "invalid version requirement 'nope', expected e.g. '^1.2' or '~0.3'",
+        ),
+        (
+            "foo = { rev = \"abc\" }",
+            "dependency needs a 'version', 'path' or 'git' key",
+        ),
+        (
+            "foo = { registry = \"reg\", path = \"../foo\" }",
+            "'registry' can't be used with 'path' or 'git'",
+        ),
+        (
+            "foo = { git = \"https://example.com/foo\", version = \"^1\", rev = \"abc\" }",
+            "'version' and 'rev' can't be used together",
+        ),
+        (
+            "foo = { path = \"../foo\", rev = \"abc\" }",
+            "'rev' can only be used with 'git'",
+        ),
+    ] {
+        let out = load("invalid-dep", &with_deps(dep));
+        assert!(
+            out.contains(&format!("at line 6, column 7: {}", msg)),
+            "{}",
+            out
+        );
+    }
+}
+
+#[test]
+fn reports_invalid_packages() {
+    let out = load(
+        "invalid-name",
+        "[package]\nname = \"my app\"\nversion = \"0.1.0\"\n",
+    );
+    assert!(
+        out.contains("at line 2, column 8: invalid package name 'my app'"),
+        "{}",
+        out
+    );
+    let out = load(
+        "invalid-version",
+        "[package]\nname = \"app\"\nversion = \"1.0\"\n",
+    );
+    assert!(
+        out.contains("at line 3, column 11: invalid version '1.0', expected major.minor.patch"),
+        "{}",
+        out
+    );
+}
@@ -0,0 +1,169 @@
+// runs scripts importing each other with `mod`
+mod common;
+
+use common::{engines, scripts};
+use std::fs;
+
+#[test]
+fn resolves_paths_from_the_importing_file() {
+    let dir = scripts(
+        "relative",
+        &[
+            (
+                "app/main.abs",
+                "mod \"lib/a.abs\" as a;\nuse x from a;\nprint(x);\n",
+            ),
+            (
+                "app/lib/a.abs",
+                "mod \"b.abs\" as b;\nuse y from b;\nlet pub x = y;\n",
+            ),
+            ("app/lib/b.abs", "let pub y = \"from b\";\n"),
+        ],
+    );
+    for (cwd, file) in [
+        ("app", "main.abs"),
+        (".", "app/main.abs"),
+        ("app/lib", "../main.abs"),
+    ] {
+        let out = engines(&dir.join(cwd), &[file]);
+        assert_eq!(out, "from b\n", "from {}", cwd);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn resolves_paths_of_mods_inside_functions() {
+    let dir = scripts(
+        "functions",
+        &[
+            (
+                "app/main.abs",
+                "mod \"lib/b.abs\" as b;
+use show from b;
+func load() -> void {
+    mod \"lib/c.abs\" as c;
+    use value from c;
+    print(value);
+}
+load();
+show();
+",
+            ),
+            (
+                "app/lib/b.abs",
+                "func pub show() -> void {
+    mod \"c.abs\" as c2;
+    use value from c2;
+    print(value + 1);
+}
+",
+            ),
+            ("app/lib/c.abs", "let pub value = 7;\n"),
+        ],
+    );
+    for (cwd, file) in [("app", "main.abs"), (".", "app/main.abs")] {
+        let out = engines(&dir.join(cwd), &[file]);
+        assert_eq!(out, "7\n8\n", "from {}", cwd);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn loads_each_module_once() {
+    let dir = scripts(
+        "cache",
+        &[
+            (
+                "main.abs",
+                "mod \"a.abs\" as a;\nmod \"./a.abs\" as b;\nuse x from a;\nuse x as y from b;\nprint(x);\nprint(y);\n",
+            ),
+            ("a.abs", "print(\"loading a\");\nlet pub x = 1;\n"),
+        ],
+    );
+    let out = engines(&dir, &["main.abs"]);
+    assert_eq!(out, "loading a\n1\n1\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn reports_import_cycles() {
+    let dir = scripts(
+        "cycle",
+        &[
+            ("main.abs", "mod \"lib/a.abs\" as a;\n"),
+            ("lib/a.abs", "mod \"b.abs\" as b;\n"),
+            ("lib/b.abs", "mod \"a.abs\" as a;\n"),
+        ],
+    );
+    let out = engines(&dir, &["main.abs"]);
+    assert!(
+        out.contains("circular module import: main.abs -> lib/a.abs -> lib/b.abs -> lib/a.abs"),
+        "{}",
+        out
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn reaches_module_members_by_path() {
+    let dir = scripts(
+        "path",
+        &[
+            (
+                "main.abs",
+                "mod \"lib.abs\" as lib;\nprint(lib::add(1, 2));\nprint(lib::Color::Red);\nprint(lib::inner::deep);\n",
+            ),
+            (
+                "lib.abs",
+                "mod \"inner.abs\" as inner;\nfunc pub add(a: number, b: number) -> number {\n    return a + b;\n}\nenum Color pub { Red, Green }\n",
+            ),
+            ("inner.abs", "let pub deep = \"deep\";\n"),
+        ],
+    );
+    let out = engines(&dir, &["main.abs"]);
+    assert_eq!(out, "3\nColor::Red\ndeep\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rejects_private_module_members() {
+    let dir = scripts(
+        "private",
+        &[
+            ("main.abs", "mod \"lib.abs\" as lib;\nprint(lib::secret);\n"),
+            ("lib.abs", "let secret = 1;\nlet pub open = 2;\n"),
+        ],
+    );
+    let out = engines(&dir, &["main.abs"]);
+    assert!(out.contains("'secret' is private to module lib"), "{}", out);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn keeps_node_ids_apart_across_modules() {
+    // parsed from 0, the `t = v` in the loop of the module would share its id with the `f`
+    // of `f(x)`, and its lookup would go a scope above the global one
+    let dir = scripts(
+        "ids",
+        &[
+            (
+                "main.abs",
+                "mod \"a.abs\" as a;\nuse f from a;\nlet x = 10;\nprint(f(x));\nprint(x);\n",
+            ),
+            (
+                "a.abs",
+                "func pub f(v: any) -> any {
+    let mut t = 0;
+    for i in [1] {
+        t = v;
+    }
+    return t;
+}
+",
+            ),
+        ],
+    );
+    let out = engines(&dir, &["main.abs"]);
+    assert_eq!(out, "10\n10\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,207 @@
+// runs scripts with and without `-O`, the optimized programs must print the same
+mod common;
+
+use common::{absurd, scripts};
+use std::fs;
+
+/// runs `main.abs` on both engines, optimized or not, and returns what it printed
+fn run(name: &str, files: &[(&str, &str)]) -> String {
+    let dir = scripts(name, files);
+    let mut outputs = vec![];
+    for engine in ["--engine=tree", "--engine=vm"] {
+        outputs.push(absurd(&dir, &[engine, "main.abs"]));
+        outputs.push(absurd(&dir, &["-O", engine, "main.abs"]));
+        outputs.push(absurd(&dir, &[engine, "run", "-O", "main.abs"]));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(
+        outputs.iter().all(|out| *out == outputs[0]),
+        "the outputs differ: {:?}",
+        outputs
+    );
+    outputs[0].clone()
+}
+
+#[test]
+fn folds_constants_and_inlines_immutable_literals() {
+    let out = run(
+        "folds",
+        &[(
+            "main.abs",
+            "let a = 2;
+let b = a * 3 + 1;
+let msg = \"hi\";
+print(b);
+print(-a);
+print(!true);
+print(\"{msg} {b:>4}\");
+let x = 1;
+let x = 5;
+func get() -> any {
+    return x;
+}
+print(get());
+func shadow(a: any) -> any {
+    return a + 1;
+}
+print(shadow(10));
+let mut m = a;
+m = m + 1;
+print(m);
+for a in [7, 8] {
+    print(a);
+}
+let add = |n: number| number: n + a;
+print(add(1));
+print(a);
+",
+        )],
+    );
+    assert_eq!(out, "7\n-2\nfalse\nhi    7\n5\n11\n3\n7\n8\n3\n2\n");
+}
+
+#[test]
+fn drops_dead_branches_and_unreachable_statements() {
+    let out = run(
+        "branches",
+        &[(
+            "main.abs",
+            "let k = 3;
+let mut i = 0;
+if k > 5 {
+    print(\"a\");
+} elif i == 0 {
+    print(\"b\");
+} elif true {
+    print(\"c\");
+} else {
+    print(\"d\");
+}
+if false {
+    print(\"never\");
+} elif k == 3 {
+    print(\"k is 3\");
+}
+print(if k < 0: \"neg\" ? \"pos\");
+while false {
+    print(\"no\");
+}
+func early(n: any) -> any {
+    if n > 1 {
+        return n;
+        print(\"dead\");
+    }
+    return 0;
+}
+print(early(5));
+print(early(0));
+loop {
+    i = i + 1;
+    if i > 2 {
+        break;
+        print(\"dead\");
+    }
+}
+print(i);
+",
+        )],
+    );
+    assert_eq!(out, "b\nk is 3\npos\n5\n0\n3\n");
+}
+
+#[test]
+fn keeps_indexing_by_written_literals() {
+    let out = run(
+        "indexing",
+        &[(
+            "main.abs",
+            "let two = 2;
+let t = (1, 2, 3);
+print(t(2));
+print(t(two));
+print(t(1 + 1));
+",
+        )],
+    );
+    assert_eq!(out, "3\nnull\nnull\n");
+}
+
+#[test]
+fn optimizes_modules_without_running_their_ifs() {
+    let out = run(
+        "modules",
+        &[
+            (
+                "main.abs",
+                "mod \"lib.abs\" as lib;\nuse over, scale from lib;\nprint(over(5));\nprint(scale * 2);\n",
+            ),
+            (
+                "lib.abs",
+                "let pub scale = 4;
+func pub over(n: number) -> number {
+    let edge = 2;
+    if edge > 1 {
+        return n * edge;
+    }
+    return 0;
+}
+if true {
+    print(\"module if\");
+}
+",
+            ),
+        ],
+    );
+    assert_eq!(out, "10\n8\n");
+}
+
+#[test]
+fn caches_modules_apart_from_scripts() {
+    let dir = scripts(
+        "cached-module",
+        &[
+            (
+                "main.abs",
+                "mod \"lib.abs\" as l;\nuse v from l;\nprint(v);\n",
+            ),
+            (
+                "lib.abs",
+                "if true {\n    print(\"side\");\n}\nlet pub v = 1;\n",
+            ),
+        ],
+    );
+    // the script is cached first, the module of the same source must not load it
+    assert_eq!(absurd(&dir, &["-O", "lib.abs"]), "side\n");
+    assert_eq!(absurd(&dir, &["-O", "main.abs"]), "1\n");
+    assert_eq!(absurd(&dir, &["-O", "lib.abs"]), "side\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dumps_the_optimized_bytecode() {
+    let dir = scripts(
+        "dump",
+        &[(
+            "main.abs",
+            "let x = 1 + 2;\nif false {\n    print(x);\n}\nprint(x * 2);\n",
+        )],
+    );
+    let out = absurd(&dir, &["-O", "dump", "main.abs", "bytecode"]);
+    fs::remove_dir_all(&dir).unwrap();
+    let ops: Vec<&str> = out
+        .lines()
+        .map(|l| l.split_whitespace().nth(1).unwrap_or(""))
+        .collect();
+    assert_eq!(
+        ops,
+        [
+            "Const(0)",
+            "Define(0)",
+            "Get(0)",
+            "Callee",
+            "Const(1)",
+            "Call(1)",
+            "Pop"
+        ]
+    );
+}
@@ -0,0 +1,512 @@
+// resolves dependencies against local bare repositories standing in for remotes
+mod common;
+
+use common::{absurd, scripts};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=absurd",
+            "-c",
+            "user.email=absurd@localhost",
+        ])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+/// url of the bare repository `name`, created on first use
+fn remote(base: &Path, name: &str) -> String {
+    let bare = base.join(format!("{}.git", name));
+    if !bare.exists() {
+        git(base, &["init", "--quiet", "--bare", bare.to_str().unwrap()]);
+        git(base, &["clone", "--quiet", bare.to_str().unwrap(), name]);
+    }
+    format!("file://{}", bare.display())
+}
+
+/// commits and tags a release of the package `name`
+fn release(base: &Path, name: &str, version: &str, deps: &str) {
+    remote(base, name);
+    let work = base.join(name);
+    fs::write(
+        work.join("project.toml"),
+        format!(
+            "[package]\nname = \"{}\"\nversion = \"{}\"\n\n[dependencies]\n{}",
+            name, version, deps
+        ),
+    )
+    .unwrap();
+    fs::write(
+        work.join("lib.abs"),
+        format!("let pub version = \"{}\";\n", version),
+    )
+    .unwrap();
+    git(&work, &["add", "."]);
+    git(&work, &["commit", "--quiet", "-m", version]);
+    git(&work, &["tag", &format!("v{}", version)]);
+    git(&work, &["push", "--quiet", "origin", "HEAD", "--tags"]);
+}
+
+fn project(base: &Path, deps: &str) -> PathBuf {
+    let app = base.join("app");
+    fs::create_dir_all(&app).unwrap();
+    fs::write(
+        app.join("project.toml"),
+        format!(
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\n{}",
+            deps
+        ),
+    )
+    .unwrap();
+    app
+}
+
+fn locked_version(app: &Path, name: &str) -> String {
+    locked(app, name, "version")
+}
+
+/// the `field` of the lock entry of `name`
+fn locked(app: &Path, name: &str, field: &str) -> String {
+    let lock = fs::read_to_string(app.join("absurd.lock")).unwrap();
+    let entry = lock
+        .split("[[package]]")
+        .find(|p| p.contains(&format!("name = \"{}\"", name)))
+        .unwrap_or_else(|| panic!("'{}' isn't locked:\n{}", name, lock));
+    entry
+        .lines()
+        .find_map(|l| l.strip_prefix(&format!("{} = ", field)))
+        .unwrap_or_else(|| panic!("'{}' has no {}:\n{}", name, field, lock))
+        .trim_matches('"')
+        .to_string()
+}
+
+#[test]
+fn picks_compatible_tags_across_the_graph() {
+    let base = scripts("resolve", &[]);
+    for version in ["1.0.0", "1.2.0", "1.2.3", "1.5.0", "2.0.0"] {
+        release(&base, "log", version, "");
+    }
+    let log = remote(&base, "log");
+    release(
+        &base,
+        "utils",
+        "1.0.0",
+        &format!("log = {{ git = \"{}\", version = \"^1.2\" }}\n", log),
+    );
+    let utils = remote(&base, "utils");
+    let app = project(
+        &base,
+        &format!(
+            "utils = {{ git = \"{}\", version = \"^1.0\" }}\nlog = {{ git = \"{}\", version = \"~1.2\" }}\n",
+            utils, log
+        ),
+    );
+
+    let out = absurd(&app, &["install"]);
+    assert!(out.contains("installed 2 packages"), "{}", out);
+    assert_eq!(locked_version(&app, "log"), "1.2.3");
+    assert_eq!(locked_version(&app, "utils"), "1.0.0");
+
+    // a newer compatible release doesn't change the lockfile
+    release(&base, "log", "1.2.4", "");
+    let lock = fs::read_to_string(app.join("absurd.lock")).unwrap();
+    fs::remove_dir_all(app.join("mods")).unwrap();
+    absurd(&app, &["install"]);
+    assert_eq!(fs::read_to_string(app.join("absurd.lock")).unwrap(), lock);
+    assert_eq!(
+        fs::read_to_string(app.join("mods/log/lib.abs")).unwrap(),
+        "let pub version = \"1.2.3\";\n"
+    );
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn picks_older_versions_to_agree_on_a_shared_dependency() {
+    let base = scripts("diamond", &[]);
+    for version in ["1.0.0", "2.0.0"] {
+        release(&base, "log", version, "");
+    }
+    let log = remote(&base, "log");
+    let on_log = |req: &str| format!("log = {{ git = \"{}\", version = \"{}\" }}\n", log, req);
+    // the newest `net` moved to log 2, which `json` can't use
+    release(&base, "net", "1.0.0", &on_log("^1.0"));
+    release(&base, "net", "1.1.0", &on_log("^2.0"));
+    release(&base, "json", "1.0.0", &on_log("^1.0"));
+    let app = project(
+        &base,
+        &format!(
+            "net = {{ git = \"{}\", version = \"^1.0\" }}\njson = {{ git = \"{}\", version = \"^1.0\" }}\n",
+            remote(&base, "net"),
+            remote(&base, "json")
+        ),
+    );
+
+    let out = absurd(&app, &["install"]);
+    assert!(out.contains("installed 3 packages"), "{}", out);
+    assert_eq!(locked_version(&app, "net"), "1.0.0");
+    assert_eq!(locked_version(&app, "json"), "1.0.0");
+    assert_eq!(locked_version(&app, "log"), "1.0.0");
+    assert_eq!(
+        fs::read_to_string(app.join("mods/net/lib.abs")).unwrap(),
+        "let pub version = \"1.0.0\";\n"
+    );
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn locks_commits_and_restores_them() {
+    let base = scripts("lock", &[]);
+    release(&base, "log", "1.0.0", "");
+    let log = remote(&base, "log");
+    let app = project(
+        &base,
+        &format!("log = {{ git = \"{}\", version = \"^1.0\" }}\n", log),
+    );
+    let out = absurd(&app, &["install"]);
+    assert!(out.contains("installed 1 package\u{1b}"), "{}", out);
+
+    let commit = Command::new("git")
+        .args(["rev-parse", "v1.0.0^{commit}"])
+        .current_dir(base.join("log"))
+        .output()
+        .unwrap()
+        .stdout;
+    assert_eq!(
+        locked(&app, "log", "commit"),
+        String::from_utf8_lossy(&commit).trim()
+    );
+    assert_eq!(locked(&app, "log", "source"), format!("git+{}", log));
+    let hash = locked(&app, "log", "hash");
+    assert!(hash.starts_with("sha256:") && hash.len() == 71, "{}", hash);
+
+    // changed or missing files are installed again from the locked commit
+    let lib = app.join("mods/log/lib.abs");
+    fs::write(&lib, "let pub version = \"changed\";\n").unwrap();
+    absurd(&app, &["install"]);
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "let pub version = \"1.0.0\";\n"
+    );
+    fs::remove_dir_all(app.join("mods")).unwrap();
+    absurd(&app, &["install"]);
+    assert!(lib.is_file());
+    assert_eq!(locked(&app, "log", "hash"), hash);
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn keeps_the_cache_out_of_packages() {
+    let base = scripts("cached", &[]);
+    release(&base, "log", "1.0.0", "");
+    let log = remote(&base, "log");
+    let util = base.join("util");
+    fs::create_dir_all(&util).unwrap();
+    fs::write(util.join("lib.abs"), "let pub name = \"util\";\n").unwrap();
+    let app = project(
+        &base,
+        &format!(
+            "log = {{ git = \"{}\", version = \"^1.0\" }}\nutil = {{ path = \"../util\" }}\n",
+            log
+        ),
+    );
+    fs::write(
+        app.join("main.abs"),
+        "use version from log;\nuse name from util;\nprint(version);\nprint(name);\n",
+    )
+    .unwrap();
+    absurd(&app, &["install"]);
+
+    for _ in 0..2 {
+        let out = absurd(&app, &["main.abs"]);
+        assert!(out.contains("1.0.0\nutil\n"), "{}", out);
+    }
+    assert!(app.join(".absurd-cache").is_dir());
+    assert!(!app.join("mods/log/.absurd-cache").exists());
+    assert!(!util.join(".absurd-cache").exists());
+
+    // the installed files still match the lock, nothing is cloned again
+    let out = absurd(&app, &["install"]);
+    assert!(!out.contains("cloning"), "{}", out);
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn explains_conflicts() {
+    let base = scripts("conflict", &[]);
+    for version in ["1.2.0", "2.0.0"] {
+        release(&base, "log", version, "");
+    }
+    let log = remote(&base, "log");
+    release(
+        &base,
+        "utils",
+        "1.0.0",
+        &format!("log = {{ git = \"{}\", version = \"^1.2\" }}\n", log),
+    );
+    let utils = remote(&base, "utils");
+    let app = project(
+        &base,
+        &format!(
+            "utils = {{ git = \"{}\", version = \"^1.0\" }}\nlog = {{ git = \"{}\", version = \"^2.0\" }}\n",
+            utils, log
+        ),
+    );
+
+    let out = absurd(&app, &["install"]);
+    for line in [
+        "no version of 'log' satisfies all requirements:",
+        "app requires log ^2.0",
+        "utils 1.0.0 requires log ^1.2",
+        "available versions: 1.2.0, 2.0.0",
+    ] {
+        assert!(out.contains(line), "expected '{}' in:\n{}", line, out);
+    }
+    assert!(!app.join("absurd.lock").exists());
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn adds_and_removes_path_and_git_dependencies() {
+    let base = scripts("add", &[]);
+    for version in ["1.0.0", "1.1.0"] {
+        release(&base, "log", version, "");
+    }
+    let log = remote(&base, "log");
+    let greet = base.join("greet");
+    fs::create_dir_all(&greet).unwrap();
+    fs::write(
+        greet.join("project.toml"),
+        "[package]\nname = \"greet\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    fs::write(greet.join("lib.abs"), "let pub hi = \"hi\";\n").unwrap();
+    let app = project(&base, "# edited by absurd add\n");
+    fs::write(
+        app.join("main.abs"),
+        "use hi from greet;\nuse version from log;\nprint(hi);\nprint(version);\n",
+    )
+    .unwrap();
+
+    assert!(absurd(&app, &["add", "../greet"]).contains("added greet"));
+    assert!(absurd(&app, &["add", &log]).contains("added log 1.1.0"));
+    let out = absurd(&app, &["add", &log, "old", "--rev", "v1.0.0"]);
+    assert!(out.contains("added old"), "{}", out);
+    let manifest = fs::read_to_string(app.join("project.toml")).unwrap();
+    for line in [
+        "# edited by absurd add".to_string(),
+        "greet = { path = \"../greet\" }".to_string(),
+        format!("log = {{ version = \"^1.1.0\", git = \"{}\" }}", log),
+        format!("old = {{ git = \"{}\", rev = \"v1.0.0\" }}", log),
+    ] {
+        assert!(
+            manifest.contains(&line),
+            "expected '{}' in:\n{}",
+            line,
+            manifest
+        );
+    }
+    assert_eq!(locked_version(&app, "log"), "1.1.0");
+    assert!(app.join("mods/log").is_dir() && app.join("mods/old").is_dir());
+    assert_eq!(absurd(&app, &["main.abs"]), "hi\n1.1.0\n");
+
+    assert!(absurd(&app, &["remove", "log"]).contains("removed log"));
+    let manifest = fs::read_to_string(app.join("project.toml")).unwrap();
+    assert!(!manifest.contains("log ="), "{}", manifest);
+    assert!(manifest.contains("# edited by absurd add"), "{}", manifest);
+    let lock = fs::read_to_string(app.join("absurd.lock")).unwrap();
+    assert!(!lock.contains("name = \"log\""), "{}", lock);
+    assert!(!app.join("mods/log").exists());
+    let out = absurd(&app, &["remove", "nope"]);
+    assert!(
+        out.contains("'nope' isn't a dependency of the project"),
+        "{}",
+        out
+    );
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn loads_package_entry_once() {
+    let base = scripts("entry", &[]);
+    let lib = base.join("greet");
+    fs::create_dir_all(lib.join("src")).unwrap();
+    fs::write(
+        lib.join("project.toml"),
+        "[package]\nname = \"greet\"\nversion = \"1.0.0\"\nentry = \"src/lib.abs\"\n",
+    )
+    .unwrap();
+    fs::write(
+        lib.join("src/lib.abs"),
+        "print(\"loading greet\");\nlet pub name = \"greet\";\n",
+    )
+    .unwrap();
+    let app = project(&base, "greet = { path = \"../greet\" }\n");
+    fs::write(
+        app.join("main.abs"),
+        "use name from greet;\nuse name as other from greet;\nprint(name);\nprint(other);\n",
+    )
+    .unwrap();
+
+    let out = absurd(&app, &["main.abs"]);
+    assert_eq!(out.matches("loading greet").count(), 1, "{}", out);
+    assert_eq!(out.matches("greet\n").count(), 3, "{}", out);
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn reports_tree_and_outdated() {
+    let base = scripts("report", &[]);
+    for version in ["1.0.0", "1.1.0"] {
+        release(&base, "log", version, "");
+    }
+    let log = remote(&base, "log");
+    release(
+        &base,
+        "utils",
+        "1.0.0",
+        &format!("log = {{ git = \"{}\", version = \"^1.0\" }}\n", log),
+    );
+    let utils = remote(&base, "utils");
+    let app = project(
+        &base,
+        &format!(
+            "utils = {{ git = \"{}\", version = \"^1.0\" }}\nlog = {{ git = \"{}\", version = \"~1.0\" }}\n",
+            utils, log
+        ),
+    );
+    absurd(&app, &["install"]);
+
+    let tree = absurd(&app, &["tree"]);
+    assert!(tree.contains("log \u{1b}[34m1.0.0"), "{}", tree);
+    assert!(tree.contains("└── utils"), "{}", tree);
+    assert!(tree.contains("(*)"), "{}", tree);
+
+    release(&base, "log", "1.0.1", "");
+    release(&base, "log", "2.0.0", "");
+    let outdated = absurd(&app, &["outdated"]);
+    let row: Vec<&str> = outdated
+        .lines()
+        .find(|l| l.starts_with("log"))
+        .unwrap_or_else(|| panic!("{}", outdated))
+        .split_whitespace()
+        .collect();
+    assert_eq!(row, ["log", "1.0.0", "1.0.1", "2.0.0"]);
+    assert!(!outdated.contains("utils"), "{}", outdated);
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn shares_lock_across_workspace_members() {
+    let base = scripts("members", &[]);
+    release(&base, "log", "1.0.0", "");
+    let log = remote(&base, "log");
+    let root = base.join("repo");
+    let (lib, app) = (root.join("packages/lib"), root.join("packages/app"));
+    fs::create_dir_all(lib.join("tests")).unwrap();
+    fs::create_dir_all(app.join("tests")).unwrap();
+    fs::write(
+        root.join("project.toml"),
+        "[workspace]\nmembers = [\"packages/*\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        lib.join("project.toml"),
+        format!(
+            "[package]\nname = \"lib\"\nversion = \"0.1.0\"\n\n[dependencies]\nlog = {{ git = \"{}\", version = \"^1.0\" }}\n",
+            log
+        ),
+    )
+    .unwrap();
+    fs::write(
+        lib.join("lib.abs"),
+        "use version from log;\nlet pub name = version;\n",
+    )
+    .unwrap();
+    fs::write(
+        app.join("project.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nlib = { path = \"../lib\" }\n",
+    )
+    .unwrap();
+    fs::write(app.join("main.abs"), "use name from lib;\nprint(name);\n").unwrap();
+    fs::write(
+        lib.join("tests/lib.abs"),
+        "use assert from std::core::test;\nassert(true, \"lib works\");\n",
+    )
+    .unwrap();
+    fs::write(
+        app.join("tests/app.abs"),
+        "use assert from std::core::test;\nassert(true, \"app works\");\n",
+    )
+    .unwrap();
+
+    let out = absurd(&app, &["install"]);
+    assert!(out.contains("installed 1 package\u{1b}"), "{}", out);
+    assert_eq!(locked_version(&root, "log"), "1.0.0");
+    assert!(root.join("mods/log").is_dir());
+    assert!(!app.join("absurd.lock").exists() && !app.join("mods").exists());
+
+    let out = absurd(&app, &["main.abs"]);
+    assert!(out.contains("1.0.0"), "{}", out);
+    let out = absurd(&root, &["test"]);
+    assert!(out.contains("2 passed, 0 failed"), "{}", out);
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn installs_from_a_local_registry() {
+    let base = scripts("registry", &[]);
+    let registry = base.join("registry");
+    let log = base.join("log");
+    fs::create_dir_all(log.join("tests")).unwrap();
+    fs::write(log.join("tests/main.abs"), "print(\"test\");\n").unwrap();
+    for version in ["1.0.0", "1.1.0", "2.0.0"] {
+        fs::write(
+            log.join("project.toml"),
+            format!(
+                "[package]\nname = \"log\"\nversion = \"{}\"\nexclude = [\"tests\"]\n",
+                version
+            ),
+        )
+        .unwrap();
+        fs::write(
+            log.join("lib.abs"),
+            format!("let pub version = \"{}\";\n", version),
+        )
+        .unwrap();
+        let out = absurd(&log, &["publish", "--registry", "../registry"]);
+        assert!(
+            out.contains(&format!("published log {}", version)),
+            "{}",
+            out
+        );
+    }
+    let out = absurd(&log, &["publish", "--registry", "../registry"]);
+    assert!(out.contains("log 2.0.0 is already published"), "{}", out);
+
+    let app = project(&base, "");
+    let out = absurd(&app, &["add", "log@^1.0", "--registry", "../registry"]);
+    assert!(out.contains("added log 1.1.0"), "{}", out);
+    assert_eq!(locked_version(&app, "log"), "1.1.0");
+    assert!(app.join("mods/log/lib.abs").is_file());
+    assert!(!app.join("mods/log/tests").exists());
+
+    // the archive is checked before it's extracted
+    fs::remove_dir_all(app.join("mods")).unwrap();
+    let archive = registry.join("log/log-1.1.0.tar");
+    let mut tar = fs::read(&archive).unwrap();
+    tar[520] ^= 1;
+    fs::write(&archive, tar).unwrap();
+    let out = absurd(&app, &["install"]);
+    assert!(out.contains("checksum mismatch"), "{}", out);
+    fs::remove_dir_all(&base).unwrap();
+}
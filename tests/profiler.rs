@@ -0,0 +1,59 @@
+// profiles function calls with `--profile` and writes their folded stacks
+mod common;
+
+use common::{absurd, scripts};
+use std::{fs, path::Path};
+
+const SQUARES: &str = "func square(n: number) -> number {
+    return n * n;
+}
+print(square(2) + square(3) + square(4));
+";
+
+/// the calls and kind of the `name` row in the report
+fn row(out: &str, name: &str) -> Option<(String, String)> {
+    out.lines().find_map(|line| {
+        let cols: Vec<&str> = line.split_whitespace().collect();
+        match cols.as_slice() {
+            [calls, _, _, kind, n] if *n == name => Some((calls.to_string(), kind.to_string())),
+            _ => None,
+        }
+    })
+}
+
+/// the stacks of the folded file, without their times
+fn stacks(path: &Path) -> Vec<String> {
+    let folded = fs::read_to_string(path).unwrap();
+    folded
+        .lines()
+        .filter_map(|l| l.rsplit_once(' ').map(|(stack, _)| stack.to_string()))
+        .collect()
+}
+
+#[test]
+fn writes_the_report_and_folded_stacks() {
+    let dir = scripts("profile", &[("main.abs", SQUARES)]);
+    for engine in ["--engine=tree", "--engine=vm"] {
+        for (args, file) in [
+            (vec!["run", "main.abs", "--profile=out.txt"], "out.txt"),
+            (
+                vec!["run", "main.abs", "--profile", "out.folded"],
+                "out.folded",
+            ),
+            (vec!["run", "main.abs", "--profile"], "profile.folded"),
+        ] {
+            let out = absurd(&dir, &[&[engine], args.as_slice()].concat());
+            assert!(
+                out.starts_with("29\n") && out.contains("profile:"),
+                "{}",
+                out
+            );
+            assert_eq!(row(&out, "square"), Some(("3".into(), "func".into())));
+            assert_eq!(row(&out, "print"), Some(("1".into(), "native".into())));
+            assert!(out.contains("folded stacks written to"), "{}", out);
+            assert_eq!(stacks(&dir.join(file)), ["main;print", "main;square"]);
+            fs::remove_file(dir.join(file)).unwrap();
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
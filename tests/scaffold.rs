@@ -0,0 +1,72 @@
+// creates projects with `absurd new` and `absurd init`
+mod common;
+
+use common::scripts;
+use std::{fs, path::Path, process::Command};
+
+/// runs absurd with the `args` in the `dir`, returns if it succeeded and what it printed
+fn absurd(dir: &Path, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_absurd"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    let out = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    (output.status.success(), out)
+}
+
+#[test]
+fn creates_projects() {
+    let dir = scripts("new", &[]);
+    let (ok, out) = absurd(&dir, &["new", "apps/my-app"]);
+    assert!(ok, "{}", out);
+    let app = dir.join("apps/my-app");
+    let manifest = fs::read_to_string(app.join("project.toml")).unwrap();
+    assert!(manifest.contains("name = \"my-app\""), "{}", manifest);
+    assert!(app.join("src/main.abs").is_file() && app.join("tests/main.abs").is_file());
+
+    let (_, out) = absurd(&app, &["run"]);
+    assert!(out.contains("Hello, world!"), "{}", out);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn initializes_the_current_directory() {
+    let dir = scripts("init", &[("tool_2/src/main.abs", "print(\"kept\");\n")]);
+    let tool = dir.join("tool_2");
+    let (ok, out) = absurd(&tool, &["init"]);
+    assert!(ok, "{}", out);
+    let manifest = fs::read_to_string(tool.join("project.toml")).unwrap();
+    assert!(manifest.contains("name = \"tool_2\""), "{}", manifest);
+    assert_eq!(
+        fs::read_to_string(tool.join("src/main.abs")).unwrap(),
+        "print(\"kept\");\n"
+    );
+
+    let (ok, out) = absurd(&tool, &["init"]);
+    assert!(
+        !ok && out.contains("already contains a project.toml"),
+        "{}",
+        out
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rejects_invalid_names() {
+    let dir = scripts("names", &[("bad dir/.keep", "")]);
+    let (ok, out) = absurd(&dir, &["new", "bad name!"]);
+    assert!(!ok, "{}", out);
+    assert!(out.contains("invalid package name 'bad name!'"), "{}", out);
+    assert!(!dir.join("bad name!").exists());
+
+    let (ok, out) = absurd(&dir.join("bad dir"), &["init"]);
+    assert!(!ok, "{}", out);
+    assert!(out.contains("invalid package name 'bad dir'"), "{}", out);
+    assert!(!dir.join("bad dir/project.toml").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
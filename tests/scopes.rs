@@ -0,0 +1,172 @@
+// runs scripts reading and assigning variables of enclosing scopes
+mod common;
+
+use common::run;
+
+#[test]
+fn shadows_names_in_inner_scopes() {
+    let out = run(
+        "shadow",
+        "let name = \"global\";\nif true {\n    let name = \"inner\";\n    print(name);\n}\nprint(name);\n",
+    );
+    assert_eq!(out, "inner\nglobal\n");
+}
+
+#[test]
+fn shadows_names_in_nested_blocks_and_closures() {
+    let out = run(
+        "shadow-nested",
+        "let x = \"global\";
+func outer(tag: string) -> any {
+    use args from \"std::core::env\";
+    let mut x = \"outer\";
+    if true {
+        let mut x = \"block\";
+        if x != \"\" {
+            let x = \"if\";
+            let tag = x;
+            print(tag);
+        }
+        x = \"assigned\";
+        print(x);
+    }
+    for x in [\"loop\"] {
+        let y = x;
+        print(y);
+    }
+    let shadow = |x: string| string {
+        let y = x;
+        if true {
+            let x = \"closure block\";
+            print(x);
+        }
+        return y;
+    };
+    print(shadow(\"closure\"));
+    let rename = |s: string| string {
+        x = s;
+        return x;
+    };
+    rename(\"renamed\");
+    print(tag);
+    print(x);
+    return |s: string| string {
+        return \"{s}{x}\";
+    };
+}
+let read = outer(\"param\");
+print(read(\"read \"));
+print(x);
+",
+    );
+    assert_eq!(
+        out,
+        "if\nassigned\nloop\nclosure block\nclosure\nparam\nrenamed\nread renamed\nglobal\n"
+    );
+}
+
+#[test]
+fn reaches_locals_of_enclosing_scopes() {
+    let out = run(
+        "enclosing",
+        "func count(limit: number) -> number {
+    let mut total = 0;
+    for n in [1, 2, 3] {
+        let step = n * 10;
+        if n <= limit {
+            total = total + step;
+        }
+    }
+    func add(extra: number) -> number {
+        let before = total;
+        total = before + extra;
+        return total;
+    }
+    add(1);
+    return total;
+}
+print(count(2));
+func fact(n: number) -> number {
+    if n < 2 {
+        return 1;
+    }
+    return n * fact(n - 1);
+}
+print(fact(5));
+",
+    );
+    assert_eq!(out, "31\n120\n");
+}
+
+#[test]
+fn closures_share_captured_variables() {
+    let out = run(
+        "closures",
+        "func counter() -> any {
+    let mut count = 0;
+    return |step: number| number {
+        count = count + step;
+        return count;
+    };
+}
+let a = counter();
+let b = counter();
+print(a(1));
+print(a(1));
+print(b(1));
+let mut calls = 0;
+let twice = |n: number| number {
+    calls = calls + 1;
+    return n * 2;
+};
+func apply(f: any, x: number) -> number {
+    return f(x);
+}
+print(apply(twice, 4));
+print(calls);
+",
+    );
+    assert_eq!(out, "1\n2\n1\n8\n1\n");
+}
+
+#[test]
+fn nested_closures_capture_every_enclosing_scope() {
+    let out = run(
+        "nested",
+        "func outer() -> number {
+    let mut total = 0;
+    func middle(step: number) -> any {
+        let scale = 10;
+        return |n: number| number {
+            total = total + (n * scale) + step;
+            return total;
+        };
+    }
+    let add = middle(1);
+    add(1);
+    add(2);
+    return total;
+}
+print(outer());
+let mut misses = 0;
+func cached(f: any) -> any {
+    let mut last = 0;
+    let mut value = 0;
+    return |n: number| number {
+        if n != last {
+            misses = misses + 1;
+            last = n;
+            value = f(n);
+        }
+        return value;
+    };
+}
+let square = cached(|n: number| number : n * n);
+print(square(3));
+print(square(3));
+print(square(4));
+print(misses);
+",
+    );
+    assert_eq!(out, "32\n9\n9\n16\n2\n");
+}
@@ -0,0 +1,161 @@
+// runs scripts building strings with `{...}` placeholders
+mod common;
+
+use common::{engines, run, scripts};
+use std::fs;
+
+#[test]
+fn interpolates_expressions_of_every_type() {
+    let out = run(
+        "types",
+        "let name = \"abs\";
+let n = 3;
+let v = [1, 2];
+let t = (1, \"x\");
+print(\"{name} {n + 1} {v} {t} {true} {null} {'c'}\");
+print(\"{if n > 2: \"big\" ? \"small\"} {\"in {name}\"}\");
+",
+    );
+    assert_eq!(out, "abs 4 [1, 2] (1, x) true null c\nbig in abs\n");
+}
+
+#[test]
+fn interpolates_records_enums_and_functions() {
+    let out = run(
+        "compound",
+        "let p = { x: 1, y: \"a\" };
+enum Color { Red, Green }
+let c = Color::Green;
+func double(n: number) -> number {
+    return n * 2;
+}
+print(\"{p} {p:?}\");
+print(\"{c} {c:?} {double} {double:?}\");
+",
+    );
+    assert_eq!(
+        out,
+        "{ x: 1, y: a } { x: 1, y: \"a\" }\nColor::Green Color::Green <func double> <func double>\n"
+    );
+}
+
+#[test]
+fn escapes_literal_braces() {
+    let out = run(
+        "escapes",
+        "let x = 1;\nprint(\"\\{x} = {x}, \\{\\}, }\");\n",
+    );
+    assert_eq!(out, "{x} = 1, {}, }\n");
+}
+
+#[test]
+fn keeps_braces_in_commands_and_paths() {
+    let dir = scripts(
+        "raw",
+        &[
+            (
+                "main.abs",
+                "let hi = 1;\nsh \"echo {hi}\";\nmod \"{lib}.abs\" as lib;\nuse x from lib;\nprint(\"{x} {hi}\");\n",
+            ),
+            ("{lib}.abs", "let pub x = \"from \\{lib}\";\n"),
+        ],
+    );
+    let out = engines(&dir, &["main.abs"]);
+    assert!(out.starts_with("{hi}\n"), "{}", out);
+    assert!(out.ends_with("from {lib} 1\n"), "{}", out);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn evaluates_placeholders_in_every_iteration() {
+    let out = run(
+        "loops",
+        "let mut total = 0;
+for i in [1, 2, 3] {
+    total = total + i;
+    print(\"{i}: {total}\");
+}
+",
+    );
+    assert_eq!(out, "1: 1\n2: 3\n3: 6\n");
+}
+
+#[test]
+fn reports_unterminated_placeholders() {
+    let out = run("unterminated", "print(\"a {1 + 2\n");
+    assert!(out.contains("E0x102"), "{}", out);
+}
+
+#[test]
+fn formats_placeholders_by_their_spec() {
+    let out = run(
+        "specs",
+        "let price = 3.14159;
+let n = 42;
+let neg = -7;
+print(\"[{price:.2}] [{n:>6}] [{n:<4}] [{n:^6}] [{n:*>5}] [{n:06}] [{neg:04}]\");
+print(\"{n:x} {n:X} {n:b} {n:o} {price:.1e}\");
+enum Color { Red, Green }
+let c = Color::Red;
+let v = [1, \"a\", 'c'];
+print(\"{v:?} {v} {\"abc\":?} {\"abcdef\":.3}|{\"ab\":>4}|{c:?}\");
+",
+    );
+    assert_eq!(
+        out,
+        "[3.14] [    42] [42  ] [  42  ] [***42] [000042] [-007]\n2a 2A 101010 52 3.1e0\n[1, \"a\", 'c'] [1, a, c] \"abc\" abc|  ab|Color::Red\n"
+    );
+}
+
+#[test]
+fn reports_invalid_specs() {
+    let out = run("unknown", "let x = 1;\nprint(\"{x:zz}\");\n");
+    assert!(
+        out.contains("E0x108") && out.contains("invalid format spec 'zz'"),
+        "{}",
+        out
+    );
+    let out = run("template", "print(\"{:q}\");\n");
+    assert!(out.contains("invalid format spec 'q'"), "{}", out);
+    let out = run(
+        "width",
+        "let n = 1;\nprint(\"{n:99999999999999999999}\");\n",
+    );
+    assert!(
+        out.contains("invalid format spec '99999999999999999999'"),
+        "{}",
+        out
+    );
+    for (value, spec) in [("3.7", "x"), ("-5", "b"), ("\"a\"", "o")] {
+        let out = run(
+            "radix",
+            &format!("let v = {};\nprint(\"{{v:{}}}\");\n", value, spec),
+        );
+        assert!(
+            out.contains(&format!(
+                "format spec '{}' expects a non-negative integer",
+                spec
+            )),
+            "{}",
+            out
+        );
+    }
+}
+
+#[test]
+fn fills_runtime_templates() {
+    let out = run(
+        "templates",
+        "use format from \"std::literal::string\";
+let rows = [[\"apple\", 3], [\"kiwi\", 12.5]];
+for row in rows {
+    print(format(\"{:<6}|{:>6.1}|\", row[0], row[1]));
+}
+print(format(\"{} and {:?}, {} left\", 1, \"two\"));
+",
+    );
+    assert_eq!(
+        out,
+        "apple |   3.0|\nkiwi  |  12.5|\n1 and \"two\", {} left\n"
+    );
+}